@@ -0,0 +1,352 @@
+//! Command-line tool to transform coordinates between geodetic datums used in Japan.
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use anyhow::{anyhow, bail, ensure, Context};
+use clap::{Parser, ValueEnum};
+use csv::{ReaderBuilder, StringRecord, Writer, WriterBuilder};
+use jgd::{
+    BatchLayout, BatchReader, BatchWriter, Crs, DatumKind, Dms, DmsFormat, DmsStyle,
+    GeoJsonTransformer, Transformer,
+};
+
+/// Transforms coordinates in CSV/TSV between Tokyo, Tokyo97, JGD2000 and JGD2011.
+///
+/// 緯度・経度の列を変換後の値に置き換え、その他の列はそのまま出力する。
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Args {
+    /// Source datum.
+    #[arg(short, long)]
+    from: Datum,
+
+    /// Target datum.
+    #[arg(short, long)]
+    to: Datum,
+
+    /// Input files. Reads stdin if none.
+    files: Vec<PathBuf>,
+
+    /// Field delimiter.
+    #[arg(short, long, default_value_t = ',', conflicts_with = "tsv")]
+    delimiter: char,
+
+    /// Uses tab as the field delimiter.
+    #[arg(long)]
+    tsv: bool,
+
+    /// Column number of latitude, starting from 1.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    lat: u16,
+
+    /// Column number of longitude, starting from 1.
+    #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u16).range(1..))]
+    lon: u16,
+
+    /// Treats the first row of each input as a header.
+    #[arg(long)]
+    header: bool,
+
+    /// Reads degrees minutes seconds in any notation and writes like `N353929.1572`.
+    #[arg(long)]
+    dms: bool,
+
+    /// Decimal places of degrees, or of seconds with `--dms`.
+    #[arg(long)]
+    precision: Option<usize>,
+
+    /// Appends a column of the transformation methods applied to each row.
+    #[arg(long)]
+    method: bool,
+
+    /// Appends columns of the shift of latitude and longitude in seconds.
+    #[arg(long)]
+    shift: bool,
+
+    /// Skips invalid rows with a warning instead of exiting with an error.
+    #[arg(long)]
+    skip_invalid: bool,
+
+    /// Reads and writes point files in layouts modeled on GSI's TKY2JGD and PatchJGD instead of CSV/TSV.
+    #[arg(long, value_enum, conflicts_with_all = [
+        "delimiter", "tsv", "lat", "lon", "header", "dms", "precision", "method", "shift",
+    ])]
+    batch: Option<Layout>,
+
+    /// Reads and writes a GeoJSON FeatureCollection instead of CSV/TSV.
+    ///
+    /// `--method` adds the methods to the properties of each feature.
+    #[arg(long, conflicts_with_all = [
+        "delimiter", "tsv", "lat", "lon", "header", "dms", "precision", "shift", "skip_invalid",
+        "batch",
+    ])]
+    geojson: bool,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Datum {
+    Tokyo,
+    Tokyo97,
+    Jgd2000,
+    Jgd2011,
+}
+impl From<Datum> for DatumKind {
+    fn from(datum: Datum) -> Self {
+        match datum {
+            Datum::Tokyo => DatumKind::Tokyo,
+            Datum::Tokyo97 => DatumKind::Tokyo97,
+            Datum::Jgd2000 => DatumKind::Jgd2000,
+            Datum::Jgd2011 => DatumKind::Jgd2011,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Layout {
+    /// `name B L` in degrees minutes seconds
+    Dms,
+    /// `name B L` in decimal degrees
+    Degrees,
+    /// `name zone X Y` in meters
+    Plane,
+}
+impl From<Layout> for BatchLayout {
+    fn from(layout: Layout) -> Self {
+        match layout {
+            Layout::Dms => BatchLayout::Dms,
+            Layout::Degrees => BatchLayout::Degrees,
+            Layout::Plane => BatchLayout::Plane,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> anyhow::Result<()> {
+    let delimiter = if args.tsv { '\t' } else { args.delimiter };
+    ensure!(
+        delimiter.is_ascii(),
+        "delimiter must be ASCII: {:?}",
+        delimiter
+    );
+    ensure!(
+        args.lat != args.lon,
+        "latitude and longitude are in the same column"
+    );
+
+    let from = Crs::Geographic(args.from.into());
+    let to = Crs::Geographic(args.to.into());
+    if args.geojson {
+        return convert_geojson(&args);
+    }
+    let transformer = Transformer::new(from, to)?;
+    if let Some(layout) = args.batch {
+        return convert_batch(&args, &transformer, layout.into());
+    }
+    let converter = Converter {
+        transformer,
+        delimiter: delimiter as u8,
+        args: &args,
+    };
+
+    let mut writer = WriterBuilder::new()
+        .delimiter(delimiter as u8)
+        .flexible(true)
+        .from_writer(io::stdout().lock());
+    if args.files.is_empty() {
+        converter.convert("<stdin>", io::stdin().lock(), &mut writer, true)?;
+    }
+    for (i, path) in args.files.iter().enumerate() {
+        let file =
+            File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+        converter.convert(&path.display().to_string(), file, &mut writer, i == 0)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Converts batch files of GSI's tools, which have no header.
+fn convert_batch(
+    args: &Args,
+    transformer: &Transformer,
+    layout: BatchLayout,
+) -> anyhow::Result<()> {
+    let mut writer = BatchWriter::new(io::stdout().lock(), layout);
+    let mut convert = |name: &str, input: &mut dyn BufRead| -> anyhow::Result<()> {
+        let mut reader = BatchReader::new(input, layout);
+        while let Some(ret) = reader.next() {
+            let ret = match ret {
+                Ok(record) => record
+                    .transform(transformer)
+                    .map_err(|e| anyhow!("line {}: {}", reader.line(), e)),
+                Err(e) if e.is_io_error() => return Err(e.into()),
+                Err(e) => Err(e.into()),
+            };
+            match ret {
+                Ok(record) => writer.write(&record)?,
+                // BatchError の表示には行番号と原因が含まれる
+                Err(e) if args.skip_invalid => eprintln!("warning: {}: {}; skipped", name, e),
+                Err(e) => bail!("{}: {}", name, e),
+            }
+        }
+        Ok(())
+    };
+    if args.files.is_empty() {
+        convert("<stdin>", &mut io::stdin().lock())?;
+    }
+    for path in &args.files {
+        let file =
+            File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+        convert(&path.display().to_string(), &mut BufReader::new(file))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Converts a GeoJSON FeatureCollection feature by feature.
+fn convert_geojson(args: &Args) -> anyhow::Result<()> {
+    ensure!(args.files.len() <= 1, "GeoJSON accepts only one input");
+    let mut geojson = GeoJsonTransformer::new(args.from.into(), args.to.into())?;
+    if args.method {
+        geojson = geojson.with_method("method");
+    }
+    let writer = BufWriter::new(io::stdout().lock());
+    match args.files.first() {
+        Some(path) => {
+            let file =
+                File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+            geojson
+                .transform_collection(file, writer)
+                .with_context(|| path.display().to_string())?;
+        }
+        None => geojson
+            .transform_collection(io::stdin().lock(), writer)
+            .context("<stdin>")?,
+    }
+    Ok(())
+}
+
+struct Converter<'a> {
+    transformer: Transformer,
+    delimiter: u8,
+    args: &'a Args,
+}
+impl Converter<'_> {
+    /// Converts all rows of an input, writing the header only if `first`.
+    fn convert(
+        &self,
+        name: &str,
+        input: impl Read,
+        writer: &mut Writer<impl Write>,
+        first: bool,
+    ) -> anyhow::Result<()> {
+        let reader = ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(input);
+        let mut records = reader.into_records();
+
+        if self.args.header {
+            match records.next() {
+                Some(header) if first => writer.write_record(&self.header(header?))?,
+                Some(header) => drop(header?),
+                None => return Ok(()),
+            }
+        }
+
+        for result in records {
+            let (line, ret) = match result {
+                Ok(record) => (record.position().map(|p| p.line()), self.row(&record)),
+                Err(e) if e.is_io_error() => return Err(e.into()),
+                Err(e) => (e.position().map(|p| p.line()), Err(e.into())),
+            };
+            match ret {
+                Ok(row) => writer.write_record(&row)?,
+                Err(e) if self.args.skip_invalid => {
+                    eprintln!("warning: {}:{}: {:#}; skipped", name, line.unwrap_or(0), e)
+                }
+                Err(e) => bail!("{}:{}: {:#}", name, line.unwrap_or(0), e),
+            }
+        }
+        Ok(())
+    }
+
+    fn header(&self, mut header: StringRecord) -> StringRecord {
+        if self.args.method {
+            header.push_field("method");
+        }
+        if self.args.shift {
+            header.push_field("dlat_sec");
+            header.push_field("dlon_sec");
+        }
+        header
+    }
+
+    fn row(&self, record: &StringRecord) -> anyhow::Result<Vec<String>> {
+        let (lat_index, lon_index) = (
+            usize::from(self.args.lat) - 1,
+            usize::from(self.args.lon) - 1,
+        );
+        let field = |index: usize| {
+            record
+                .get(index)
+                .with_context(|| format!("column {} not found", index + 1))
+        };
+        let lat = self.parse(field(lat_index)?).context("latitude")?;
+        let lon = self.parse(field(lon_index)?).context("longitude")?;
+        let ([to_lat, to_lon], methods) = self.transformer.transform_with_methods([lat, lon])?;
+
+        let mut row: Vec<_> = record.iter().map(str::to_owned).collect();
+        row[lat_index] = self.format(to_lat, DmsFormat::format_lat)?;
+        row[lon_index] = self.format(to_lon, DmsFormat::format_lon)?;
+        if self.args.method {
+            row.push(methods.join("+"));
+        }
+        if self.args.shift {
+            row.push(format!("{:.6}", (to_lat - lat) * 3600.));
+            row.push(format!("{:.6}", (to_lon - lon) * 3600.));
+        }
+        Ok(row)
+    }
+
+    fn parse(&self, field: &str) -> anyhow::Result<f64> {
+        let field = field.trim();
+        Ok(if self.args.dms {
+            field.parse::<Dms>()?.to_degrees()
+        } else {
+            field
+                .parse()
+                .with_context(|| format!("invalid number: {:?}", field))?
+        })
+    }
+
+    fn format(
+        &self,
+        degrees: f64,
+        format_dms: fn(&DmsFormat, Dms) -> String,
+    ) -> anyhow::Result<String> {
+        Ok(if self.args.dms {
+            let mut format = DmsFormat::new(DmsStyle::Packed);
+            if let Some(precision) = self.args.precision {
+                format = format.with_precision(precision);
+            }
+            format_dms(&format, Dms::from_degrees(degrees)?)
+        } else {
+            format!("{:.*}", self.args.precision.unwrap_or(9), degrees)
+        })
+    }
+}
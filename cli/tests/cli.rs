@@ -0,0 +1,191 @@
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+use jgd::{DmsFormat, DmsStyle, LatLon, Tokyo97};
+
+fn jgd(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jgd"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> &str {
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+fn stderr(output: &Output) -> &str {
+    std::str::from_utf8(&output.stderr).unwrap()
+}
+
+#[test]
+fn csv() {
+    let output = jgd(
+        &[
+            "--from", "tokyo97", "--to", "jgd2000", "--header", "--method",
+        ],
+        "lat,lon,name\n35,135,a\n36.5,140.25,b\n",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let expected = [LatLon(35., 135.), LatLon(36.5, 140.25)].map(|degrees| {
+        let LatLon(lat, lon) = Tokyo97::new(degrees).unwrap().to_jgd2000().degrees();
+        format!("{:.9},{:.9}", lat, lon)
+    });
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "lat,lon,name,method\n{},a,3-parameter\n{},b,3-parameter\n",
+            expected[0], expected[1]
+        )
+    );
+}
+
+#[cfg(feature = "patchjgd")]
+#[test]
+fn method_per_row() {
+    let output = jgd(
+        &["-f", "jgd2000", "-t", "jgd2011", "--method"],
+        "38.26,140.87\n35,135\n",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let methods: Vec<_> = stdout(&output)
+        .lines()
+        .map(|line| line.rsplit(',').next().unwrap())
+        .collect();
+    // パラメータのない地点は変換されない
+    assert_eq!(methods, ["PatchJGD", ""]);
+}
+
+#[test]
+fn tsv_columns_and_shift() {
+    let output = jgd(
+        &[
+            "-f", "tokyo97", "-t", "jgd2000", "--tsv", "--lat", "3", "--lon", "2",
+        ],
+        "x\t135\t35\n",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let LatLon(lat, lon) = Tokyo97::new(LatLon(35., 135.))
+        .unwrap()
+        .to_jgd2000()
+        .degrees();
+    assert_eq!(stdout(&output), format!("x\t{:.9}\t{:.9}\n", lon, lat));
+
+    let output = jgd(&["-f", "tokyo97", "-t", "jgd2000", "--shift"], "35,135\n");
+    let shift = format!(",{:.6},{:.6}\n", (lat - 35.) * 3600., (lon - 135.) * 3600.);
+    assert!(stdout(&output).ends_with(&shift), "{}", stdout(&output));
+}
+
+#[test]
+fn dms() {
+    let output = jgd(
+        &[
+            "-f",
+            "tokyo97",
+            "-t",
+            "tokyo97",
+            "--dms",
+            "--precision",
+            "2",
+        ],
+        "353929.1572,139°44'28.8869\"\n",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "N353929.16,E1394428.89\n");
+}
+
+#[test]
+fn invalid_row() {
+    let input = "35,135\n35,x\n135,35\n36,140\n";
+    let output = jgd(&["-f", "tokyo97", "-t", "jgd2000"], input);
+    assert!(!output.status.success());
+    assert_eq!(stdout(&output).lines().count(), 1);
+    assert!(stderr(&output).starts_with("error: <stdin>:2: longitude"));
+
+    let output = jgd(&["-f", "tokyo97", "-t", "jgd2000", "--skip-invalid"], input);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output).lines().count(), 2);
+    let warnings: Vec<_> = stderr(&output).lines().collect();
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[1].starts_with("warning: <stdin>:3: degrees out of range"));
+}
+
+#[test]
+fn batch() {
+    let output = jgd(
+        &["-f", "tokyo97", "-t", "jgd2000", "--batch", "dms"],
+        "A001 350000.0000 1350000.0000\r\n",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let format = DmsFormat::new(DmsStyle::Packed).with_hemisphere(false);
+    let LatLon(lat, lon) = Tokyo97::new(LatLon(35., 135.))
+        .unwrap()
+        .to_jgd2000()
+        .degrees()
+        .to_dms()
+        .unwrap();
+    let expected = format!(
+        "A001 {} {}\r\n",
+        format.format_lat(lat),
+        format.format_lon(lon)
+    );
+    assert_eq!(stdout(&output), expected);
+
+    let output = jgd(
+        &["-f", "tokyo97", "-t", "jgd2000", "--batch", "plane"],
+        "P1 20 0 0\r\n",
+    );
+    assert!(!output.status.success());
+    assert_eq!(stderr(&output), "error: <stdin>: line 1: invalid zone\n");
+}
+
+#[test]
+fn geojson() {
+    let input = r#"{"type":"FeatureCollection","features":[{"type":"Feature","geometry":{"type":"Point","coordinates":[135,35]},"properties":{"name":"A"}}]}"#;
+    let output = jgd(
+        &["-f", "tokyo97", "-t", "jgd2000", "--geojson", "--method"],
+        input,
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let [lon, lat] = {
+        let LatLon(lat, lon) = Tokyo97::new(LatLon(35., 135.))
+            .unwrap()
+            .to_jgd2000()
+            .degrees();
+        [lon, lat]
+    };
+    let expected = format!(
+        r#"{{"type":"FeatureCollection","features":[{{"type":"Feature","geometry":{{"type":"Point","coordinates":[{:?},{:?}]}},"properties":{{"name":"A","method":"3-parameter"}}}}]}}"#,
+        lon, lat
+    );
+    assert_eq!(stdout(&output), expected);
+}
+
+#[test]
+fn geojson_conflicts() {
+    let output = jgd(
+        &[
+            "-f",
+            "tokyo97",
+            "-t",
+            "jgd2000",
+            "--geojson",
+            "--skip-invalid",
+        ],
+        "",
+    );
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("cannot be used with"));
+}
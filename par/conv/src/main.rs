@@ -1,14 +1,24 @@
 use std::{
     collections::BTreeSet,
-    convert::Infallible,
+    env,
     fmt::Display,
     io::{self, BufRead, Write},
-    str::{self, FromStr},
+    str,
 };
 
 use anyhow::{ensure, Context};
 
 fn main() {
+    // grid spacing in seconds, 3rd mesh by default
+    let spacing = match env::args().skip(1).collect::<Vec<_>>()[..] {
+        [] => Spacing(30, 45),
+        [ref lat, ref lon] => Spacing(
+            lat.parse().expect("lat spacing"),
+            lon.parse().expect("lon spacing"),
+        ),
+        _ => panic!("usage: conv [LAT_SECS LON_SECS] < PAR > IN"),
+    };
+
     io::stdin()
         .lock()
         .split(b'\n')
@@ -20,28 +30,28 @@ fn main() {
         .skip_while(|line| line != b"MeshCode   dB(sec)   dL(sec)")
         .skip(1) // header
         .map(|line| {
-            str::from_utf8(&line)
-                .expect("body must be ASCII")
-                .parse::<Record>()
-                .unwrap()
+            let line = str::from_utf8(&line).expect("body must be ASCII");
+            Record::parse(line, spacing)
         })
         // sort all records, since lines 378632 onwards of TKY2JGD.par are not sorted
         .collect::<BTreeSet<_>>()
         .into_iter()
-        .inspect(|record| eprintln!("{}", record))
         .for_each(|record| {
+            eprintln!("{}", record);
             io::stdout()
                 .write_all(&record.to_binary())
                 .expect("stdout must be valid")
         })
 }
 
+/// Grid spacing in seconds.
+#[derive(Debug, Clone, Copy)]
+struct Spacing(i64, i64);
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 struct Record(i16, i16, i32, i32);
-impl FromStr for Record {
-    type Err = Infallible;
-
-    fn from_str(mut line: &str) -> Result<Record, Self::Err> {
+impl Record {
+    fn parse(mut line: &str, spacing: Spacing) -> Record {
         fn parse_number(line: &mut &str, end: usize) -> anyhow::Result<f64> {
             ensure!(line.len() >= end);
             let (number, rest) = (&line[..end], &line[end..]);
@@ -52,24 +62,34 @@ impl FromStr for Record {
                 .context("failed to parse number")
         }
 
-        fn parse_meshcode(line: &mut &str, chunk: usize) -> anyhow::Result<(i64, i64)> {
-            let lat = parse_number(line, chunk)? as i64;
-            let lon = parse_number(line, chunk)? as i64;
+        // 6-digit 2nd mesh code or 8-digit 3rd mesh code
+        fn parse_meshcode(line: &mut &str) -> anyhow::Result<(i64, i64)> {
+            let end = line.find(' ').unwrap_or(line.len());
+            let code = &line[..end];
+            ensure!(code.len() == 6 || code.len() == 8, "length");
+            ensure!(code.bytes().all(|b| b.is_ascii_digit()), "digits");
+            *line = &line[end..];
+
+            let mut digits = code.bytes().map(|b| i64::from(b - b'0'));
+            let mut next = || digits.next().unwrap_or(0);
+            let (lat1, lon1) = (next() * 10 + next(), next() * 10 + next());
+            let (lat2, lon2) = (next(), next());
+            let (lat3, lon3) = (next(), next());
+
+            // seconds of southwest corner
+            let lat = lat1 * 2400 + lat2 * 300 + lat3 * 30;
+            let lon = (lon1 + 100) * 3600 + lon2 * 450 + lon3 * 45;
             Ok((lat, lon))
         }
+        let (lat_secs, lon_secs) = parse_meshcode(&mut line).expect("mesh code");
 
-        let (mesh1_lat, mesh1_lon) = parse_meshcode(&mut line, 2).expect("1st mesh");
-        let (mesh2_lat, mesh2_lon) = parse_meshcode(&mut line, 1).expect("2nd mesh");
-        let (mesh3_lat, mesh3_lon) = parse_meshcode(&mut line, 1).expect("3rd mesh");
-
-        // Serial number of 3rd mesh grids starting from 0 degree
-        fn to_grid_index(mesh1: i64, mesh2: i64, mesh3: i64) -> anyhow::Result<i16> {
-            (mesh1 * 80 + mesh2 * 10 + mesh3)
-                .try_into()
-                .context("grid overflowed")
+        // Serial number of grids starting from 0 degree
+        fn to_grid_index(secs: i64, spacing: i64) -> anyhow::Result<i16> {
+            ensure!(secs % spacing == 0, "not on grid");
+            (secs / spacing).try_into().context("grid overflowed")
         }
-        let index_lat = to_grid_index(mesh1_lat, mesh2_lat, mesh3_lat).expect("lat");
-        let index_lon = to_grid_index(mesh1_lon + 100, mesh2_lon, mesh3_lon).expect("lon");
+        let index_lat = to_grid_index(lat_secs, spacing.0).expect("lat");
+        let index_lon = to_grid_index(lon_secs, spacing.1).expect("lon");
 
         fn parse_usec(line: &mut &str) -> anyhow::Result<i32> {
             let us = (parse_number(line, 10)? * 1_000_000.) as i32;
@@ -80,7 +100,7 @@ impl FromStr for Record {
         let d_lat_us = parse_usec(&mut line).expect("dB(sec)");
         let d_lon_us = parse_usec(&mut line).expect("dL(sec)");
 
-        Ok(Record(index_lat, index_lon, d_lat_us, d_lon_us))
+        Record(index_lat, index_lon, d_lat_us, d_lon_us)
     }
 }
 impl Display for Record {
@@ -0,0 +1,453 @@
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+use crate::{Crs, DegreesError, Dms, DmsFormat, DmsStyle, LatLon, Transformer, Xy, Zone};
+
+/// Layout of a batch file modeled on GSI's TKY2JGD and PatchJGD.
+///
+/// 国土地理院の TKY2JGD, PatchJGD の「ファイル一括変換」を参考にしたテキスト形式。
+/// 1行に1点、点名と座標を空白、タブまたはカンマで区切る。空行は無視する。
+/// 国土地理院のプログラムの入出力とバイト単位で一致するかは検証していない。
+///
+/// 点名は区切り文字を含まない任意のバイト列で、Shift_JIS のまま読み書きできる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchLayout {
+    /// `点名 B L` in degrees minutes seconds like `353929.1572 1394428.8869`.
+    Dms,
+    /// `点名 B L` in decimal degrees like `35.658099222 139.741357472`.
+    Degrees,
+    /// `点名 系番号 X Y` in meters like `9 -37934.2345 -8119.1234`.
+    Plane,
+}
+
+/// Coordinate of a [`BatchRecord`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BatchPoint {
+    /// Latitude and longitude in degrees.
+    Degrees(LatLon),
+    /// Plane rectangular coordinate in the zone.
+    Plane(Zone, Xy),
+}
+
+/// A line of a batch file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchRecord {
+    name: Vec<u8>,
+    point: BatchPoint,
+}
+impl BatchRecord {
+    /// Constructs a [`BatchRecord`] with the point name in bytes.
+    pub fn new(name: impl Into<Vec<u8>>, point: BatchPoint) -> Self {
+        let name = name.into();
+        Self { name, point }
+    }
+
+    /// Returns the point name in bytes as in the file.
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    /// Returns the coordinate.
+    pub fn point(&self) -> BatchPoint {
+        self.point
+    }
+
+    /// Transforms the coordinate, keeping the name and the zone.
+    ///
+    /// 平面直角座標は、変換元の楕円体で経緯度に戻して変換し、変換先の楕円体で同じ系に投影する。
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BatchError`] without a line number if `transformer` is not between
+    /// geographic CRSs, or if the coordinate in degrees is out of range.
+    pub fn transform(&self, transformer: &Transformer) -> Result<Self, BatchError> {
+        let (Crs::Geographic(from), Crs::Geographic(to)) = (transformer.from(), transformer.to())
+        else {
+            let kind = ErrorKind::Crs(transformer.from(), transformer.to());
+            return Err(BatchError { line: None, kind });
+        };
+        let degrees = match self.point {
+            BatchPoint::Degrees(degrees) => degrees,
+            BatchPoint::Plane(zone, xy) => zone.projection(&from.ellipsoid()).unproject(xy),
+        };
+        let [lat, lon] = transformer
+            .transform([*degrees.lat(), *degrees.lon()])
+            .map_err(|e| BatchError {
+                line: None,
+                kind: ErrorKind::Degrees(e),
+            })?;
+        let point = match self.point {
+            BatchPoint::Degrees(_) => BatchPoint::Degrees(LatLon(lat, lon)),
+            BatchPoint::Plane(zone, _) => {
+                let xy = zone.projection(&to.ellipsoid()).project(LatLon(lat, lon));
+                BatchPoint::Plane(zone, xy)
+            }
+        };
+        Ok(Self::new(self.name.clone(), point))
+    }
+}
+
+/// Reader of a batch file, iterating over the records.
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{BatchLayout, BatchPoint, BatchReader, Dms, LatLon};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let input = "A001 353929.1572 1394428.8869\r\n";
+/// for record in BatchReader::new(input.as_bytes(), BatchLayout::Dms) {
+///     let record = record?;
+///     # assert_eq!(record.name(), b"A001");
+///     let BatchPoint::Degrees(degrees) = record.point() else { unreachable!() };
+/// }
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct BatchReader<R> {
+    reader: R,
+    layout: BatchLayout,
+    line: usize,
+    buf: Vec<u8>,
+}
+impl<R: BufRead> BatchReader<R> {
+    /// Constructs a [`BatchReader`] of the layout.
+    pub fn new(reader: R, layout: BatchLayout) -> Self {
+        Self {
+            reader,
+            layout,
+            line: 0,
+            buf: vec![],
+        }
+    }
+
+    /// Returns the line number of the last record read, starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    fn parse(&self) -> Result<Option<BatchRecord>, ErrorKind> {
+        let mut fields = self
+            .buf
+            .split(|b| matches!(b, b' ' | b'\t' | b',' | b'\r' | b'\n'))
+            .filter(|field| !field.is_empty());
+        let Some(name) = fields.next() else {
+            return Ok(None);
+        };
+        let mut next = |label| {
+            let field = fields.next().ok_or(ErrorKind::Missing(label))?;
+            std::str::from_utf8(field).map_err(|_| ErrorKind::Invalid(label))
+        };
+        let point = match self.layout {
+            BatchLayout::Dms => {
+                let mut dms = |label| -> Result<f64, ErrorKind> {
+                    let dms: Dms = next(label)?
+                        .parse()
+                        .map_err(|_| ErrorKind::Invalid(label))?;
+                    Ok(dms.to_degrees())
+                };
+                let degrees = LatLon(dms("B")?, dms("L")?);
+                BatchPoint::Degrees(degrees)
+            }
+            BatchLayout::Degrees => {
+                let mut degrees = |label| -> Result<f64, ErrorKind> {
+                    next(label)?.parse().map_err(|_| ErrorKind::Invalid(label))
+                };
+                BatchPoint::Degrees(LatLon(degrees("B")?, degrees("L")?))
+            }
+            BatchLayout::Plane => {
+                let zone = next("zone")?
+                    .parse()
+                    .ok()
+                    .and_then(Zone::new)
+                    .ok_or(ErrorKind::Invalid("zone"))?;
+                let mut meters = |label| -> Result<f64, ErrorKind> {
+                    next(label)?.parse().map_err(|_| ErrorKind::Invalid(label))
+                };
+                BatchPoint::Plane(zone, Xy::new(meters("X")?, meters("Y")?))
+            }
+        };
+        if let BatchPoint::Degrees(degrees) = point {
+            degrees.validate_degrees().map_err(ErrorKind::Degrees)?;
+        }
+        Ok(Some(BatchRecord::new(name, point)))
+    }
+}
+impl<R: BufRead> Iterator for BatchReader<R> {
+    type Item = Result<BatchRecord, BatchError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buf.clear();
+            self.line += 1;
+            let error = |kind| {
+                Some(Err(BatchError {
+                    line: Some(self.line),
+                    kind,
+                }))
+            };
+            match self.reader.read_until(b'\n', &mut self.buf) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => return error(ErrorKind::Io(e)),
+            }
+            match self.parse() {
+                Ok(Some(record)) => return Some(Ok(record)),
+                Ok(None) => continue,
+                Err(kind) => return error(kind),
+            }
+        }
+    }
+}
+
+/// Writer of a batch file.
+///
+/// 区切り文字は空白、改行は CRLF。小数点以下の桁数は、度分秒では秒を 4桁、十進度では 9桁、
+/// 平面直角座標では 4桁 (0.1mm) とする。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{BatchLayout, BatchPoint, BatchRecord, BatchWriter, LatLon};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut writer = BatchWriter::new(vec![], BatchLayout::Dms);
+/// let point = BatchPoint::Degrees(LatLon(35.5, 139.75));
+/// writer.write(&BatchRecord::new("A001", point))?;
+/// assert_eq!(writer.into_inner(), b"A001 353000.0000 1394500.0000\r\n");
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct BatchWriter<W> {
+    writer: W,
+    layout: BatchLayout,
+}
+impl<W: Write> BatchWriter<W> {
+    /// Constructs a [`BatchWriter`] of the layout.
+    pub fn new(writer: W, layout: BatchLayout) -> Self {
+        Self { writer, layout }
+    }
+
+    /// Writes a record as a line.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::Error`] from the writer, or of [`io::ErrorKind::InvalidInput`]
+    /// if the point does not match the layout.
+    pub fn write(&mut self, record: &BatchRecord) -> io::Result<()> {
+        self.writer.write_all(&record.name)?;
+        match (self.layout, record.point) {
+            (BatchLayout::Dms, BatchPoint::Degrees(degrees)) => {
+                let format = DmsFormat::new(DmsStyle::Packed).with_hemisphere(false);
+                let LatLon(lat, lon) = degrees
+                    .to_dms()
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+                let (lat, lon) = (format.format_lat(lat), format.format_lon(lon));
+                write!(self.writer, " {} {}", lat, lon)?;
+            }
+            (BatchLayout::Degrees, BatchPoint::Degrees(LatLon(lat, lon))) => {
+                write!(self.writer, " {:.9} {:.9}", lat, lon)?;
+            }
+            (BatchLayout::Plane, BatchPoint::Plane(zone, xy)) => {
+                write!(
+                    self.writer,
+                    " {} {:.4} {:.4}",
+                    zone.number(),
+                    xy.x(),
+                    xy.y()
+                )?;
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "point does not match the layout",
+                ))
+            }
+        }
+        self.writer.write_all(b"\r\n")
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Errors in reading a batch file or transforming a [`BatchRecord`].
+#[derive(Debug)]
+pub struct BatchError {
+    line: Option<usize>,
+    kind: ErrorKind,
+}
+impl BatchError {
+    /// Returns the line number starting from 1, if any.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// Returns `true` if the error is from the underlying reader.
+    pub fn is_io_error(&self) -> bool {
+        matches!(self.kind, ErrorKind::Io(_))
+    }
+}
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        match &self.kind {
+            ErrorKind::Io(e) => write!(f, "{}", e),
+            ErrorKind::Missing(label) => write!(f, "missing {}", label),
+            ErrorKind::Invalid(label) => write!(f, "invalid {}", label),
+            ErrorKind::Degrees(e) => write!(f, "{}", e),
+            ErrorKind::Crs(from, to) => {
+                write!(f, "not between geographic CRSs: {} to {}", from, to)
+            }
+        }
+    }
+}
+impl std::error::Error for BatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Io(e) => Some(e),
+            ErrorKind::Degrees(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum ErrorKind {
+    Io(io::Error),
+    Missing(&'static str),
+    Invalid(&'static str),
+    Degrees(DegreesError),
+    Crs(Crs, Crs),
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{Crs, DatumKind, LatLon, Transformer, Xy, Zone};
+
+    use super::{BatchLayout, BatchPoint, BatchReader, BatchRecord, BatchWriter};
+
+    fn read(input: &[u8], layout: BatchLayout) -> Vec<BatchRecord> {
+        BatchReader::new(input, layout)
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    fn write(records: &[BatchRecord], layout: BatchLayout) -> Vec<u8> {
+        let mut writer = BatchWriter::new(vec![], layout);
+        for record in records {
+            writer.write(record).unwrap();
+        }
+        writer.into_inner()
+    }
+
+    #[test]
+    fn dms_round_trip() {
+        // 点名は Shift_JIS の「基準点」
+        let input = b"\x8a\xee\x8f\x80\x93\x5f 353929.1572 1394428.8869\r\n\r\nB2 -353929.1572 1394428.8869\r\n";
+        let records = read(input, BatchLayout::Dms);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].name(), b"\x8a\xee\x8f\x80\x93\x5f");
+        let BatchPoint::Degrees(degrees) = records[1].point() else {
+            panic!()
+        };
+        assert_abs_diff_eq!(*degrees.lat(), -35.658099222, epsilon = 1e-9);
+
+        let expected = b"\x8a\xee\x8f\x80\x93\x5f 353929.1572 1394428.8869\r\nB2 -353929.1572 1394428.8869\r\n";
+        assert_eq!(write(&records, BatchLayout::Dms), expected);
+    }
+
+    #[test]
+    fn separators() {
+        let records = read(b"P1,35.5,\t139.75\nP2  36  140", BatchLayout::Degrees);
+        assert_eq!(
+            records[0].point(),
+            BatchPoint::Degrees(LatLon(35.5, 139.75))
+        );
+        assert_eq!(records[1].point(), BatchPoint::Degrees(LatLon(36., 140.)));
+        assert_eq!(
+            write(&records, BatchLayout::Degrees),
+            b"P1 35.500000000 139.750000000\r\nP2 36.000000000 140.000000000\r\n"
+        );
+    }
+
+    #[test]
+    fn plane() {
+        let records = read(b"P1 9 -37934.2345 -8119.1234\r\n", BatchLayout::Plane);
+        let zone = Zone::new(9).unwrap();
+        let xy = Xy::new(-37934.2345, -8119.1234);
+        assert_eq!(records[0].point(), BatchPoint::Plane(zone, xy));
+        assert_eq!(
+            write(&records, BatchLayout::Plane),
+            b"P1 9 -37934.2345 -8119.1234\r\n"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let reader = BatchReader::new(
+            &b"P1 35.5\nP2 35.5 x\nP3 135 35\n"[..],
+            BatchLayout::Degrees,
+        );
+        let messages: Vec<_> = reader.map(|ret| ret.unwrap_err().to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "line 1: missing L",
+                "line 2: invalid L",
+                "line 3: degrees out of range; may be lat and lon reversed?",
+            ]
+        );
+
+        let mut reader = BatchReader::new(&b"P1 20 0 0"[..], BatchLayout::Plane);
+        assert_eq!(
+            reader.next().unwrap().unwrap_err().to_string(),
+            "line 1: invalid zone"
+        );
+    }
+
+    #[test]
+    fn layout_mismatch() {
+        let record = BatchRecord::new("P1", BatchPoint::Degrees(LatLon(35., 135.)));
+        let mut writer = BatchWriter::new(vec![], BatchLayout::Plane);
+        assert!(writer.write(&record).is_err());
+    }
+
+    #[test]
+    fn transform_plane() {
+        let from = Crs::Geographic(DatumKind::Tokyo97);
+        let to = Crs::Geographic(DatumKind::Jgd2000);
+        let transformer = Transformer::new(from, to).unwrap();
+        let zone = Zone::new(9).unwrap();
+        let record = BatchRecord::new("P1", BatchPoint::Plane(zone, Xy::new(1000., 2000.)));
+        let ret = record.transform(&transformer).unwrap();
+        assert_eq!(ret.name(), b"P1");
+
+        let plane = Transformer::new(
+            Crs::Plane(DatumKind::Tokyo97, zone),
+            Crs::Plane(DatumKind::Jgd2000, zone),
+        )
+        .unwrap();
+        let [x, y] = plane.transform([1000., 2000.]).unwrap();
+        assert_eq!(ret.point(), BatchPoint::Plane(zone, Xy::new(x, y)));
+
+        let err = record.transform(&plane).unwrap_err();
+        assert_eq!(err.line(), None);
+        assert!(err.to_string().starts_with("not between geographic CRSs"));
+    }
+}
@@ -1,4 +1,10 @@
-use crate::{DegreesError, LatLon, BESSEL, ECEF, GRS80};
+use crate::{DegreesError, Helmert, Itrf, LatLon, BESSEL, ECEF, GRS80};
+
+#[cfg(any(feature = "tky2jgd", feature = "patchjgd"))]
+use crate::geodetic::SECS;
+
+#[cfg(feature = "std")]
+use crate::SemiDynamic;
 
 #[cfg(feature = "tky2jgd")]
 use crate::TKY2JGD;
@@ -11,7 +17,7 @@ use crate::TOUHOKUTAIHEIYOUOKI2011;
 /// 旧日本測地系。
 ///
 /// EPSG: 4301
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Tokyo {
     degrees: LatLon,
 }
@@ -37,6 +43,11 @@ impl Tokyo {
         Ok(Self { degrees })
     }
 
+    #[allow(dead_code)]
+    fn new_unchecked(degrees: LatLon) -> Self {
+        Self { degrees }
+    }
+
     /// Transforms to [`Jgd2000`].
     ///
     /// [`TKY2JGD`] を用いて変換される。精度は、一定の条件下で
@@ -82,13 +93,13 @@ impl Tokyo {
 /// 世界測地系を基準に、3パラメータによる変換式で定義された測地系 [(飛田, 1997)](crate#references)。
 ///
 /// 旧日本測地系で測量された座標には [`Tokyo`] の方が適している。
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Tokyo97 {
     degrees: LatLon,
 }
 impl Tokyo97 {
     /// Transformation parameters to ITRF94.
-    pub const TO_ITRF94: ECEF = ECEF::new(-146.414, 507.337, 680.507);
+    pub const TO_ITRF94: Helmert = Helmert::new(ECEF::new(-146.414, 507.337, 680.507));
 
     /// Constructs a [`Tokyo97`] with a coordinate in degrees.
     ///
@@ -131,7 +142,7 @@ impl Tokyo97 {
     pub fn to_jgd2000(&self) -> Jgd2000 {
         // https://www.gsi.go.jp/LAW/G2000-g2000faq-1.htm
         // > 測地成果2000での経度・緯度は、世界測地系であるITRF94座標系とGRS80の楕円体を使用して表します
-        let itrf94 = BESSEL.to_ecef(self.degrees) + Self::TO_ITRF94;
+        let itrf94 = Self::TO_ITRF94.transform(BESSEL.to_ecef(self.degrees));
         Jgd2000::new_unchecked(GRS80.to_geodetic(itrf94))
     }
 
@@ -155,11 +166,17 @@ impl Tokyo97 {
 /// 世界測地系。
 ///
 /// EPSG: 4612
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Jgd2000 {
     degrees: LatLon,
 }
 impl Jgd2000 {
+    /// Reference frame, ITRF94.
+    pub const ITRF: Itrf = Itrf::Itrf94;
+
+    /// Reference epoch in decimal years, 1997.0.
+    pub const EPOCH: f64 = 1997.0;
+
     /// Constructs a [`Jgd2000`] with a coordinate in degrees.
     ///
     /// # Errors
@@ -206,9 +223,24 @@ impl Jgd2000 {
     }
 
     /// Inverse of [`Tokyo::to_jgd2000`].
-    fn _to_tokyo(&self) {
-        // グリッドのキーは日本測地系だが、求めたいのも日本測地系なので、矛盾している。
-        // オリジナルの実装 modTky2jgd.bas:1108 は、精度や対応範囲を割り切っている。
+    ///
+    /// [`TKY2JGD`] の格子点は日本測地系の座標なので、[`Tokyo::to_jgd2000`] で戻した座標が
+    /// 一致するまで反復する。[`TKY2JGD`] の範囲外では3パラメータの逆変換となる。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use jgd::{Jgd2000, LatLon};
+    /// #
+    /// # let jgd2000 = Jgd2000::new(LatLon(35.0, 135.0)).unwrap();
+    /// let LatLon(lat, lon) = jgd2000.to_tokyo().degrees();
+    /// ```
+    #[cfg(feature = "tky2jgd")]
+    pub fn to_tokyo(&self) -> Tokyo {
+        let degrees = invert(self.degrees, |degrees| {
+            Tokyo::new_unchecked(degrees).to_jgd2000().degrees()
+        });
+        Tokyo::new_unchecked(degrees)
     }
 
     /// Inverse of [`Tokyo97::to_jgd2000`].
@@ -222,8 +254,8 @@ impl Jgd2000 {
     /// let LatLon(lat, lon) = jgd2000.to_tokyo97().degrees();
     /// ```
     pub fn to_tokyo97(&self) -> Tokyo97 {
-        let itrf94 = GRS80.to_ecef(self.degrees) - Tokyo97::TO_ITRF94;
-        Tokyo97::new_unchecked(BESSEL.to_geodetic(itrf94))
+        let itrf94 = GRS80.to_ecef(self.degrees);
+        Tokyo97::new_unchecked(BESSEL.to_geodetic(Tokyo97::TO_ITRF94.inverse(itrf94)))
     }
 
     /// Returnes coordinate in degrees.
@@ -246,21 +278,103 @@ impl Jgd2000 {
 /// 世界測地系。
 ///
 /// EPSG: 6668
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Jgd2011 {
     degrees: LatLon,
 }
 impl Jgd2011 {
-    #[allow(dead_code)]
+    /// Reference frame, ITRF2008.
+    pub const ITRF: Itrf = Itrf::Itrf2008;
+
+    /// Reference epoch in decimal years, 2011.4.
+    ///
+    /// 元期は2011年5月24日。
+    pub const EPOCH: f64 = 2011.4;
+
+    /// Constructs a [`Jgd2011`] with a coordinate in degrees.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DegreesError`] if the [`LatLon`] is out of range in degrees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use jgd::{LatLon, Jgd2011};
+    /// #
+    /// # fn main() -> anyhow::Result<()> {
+    /// let jgd2011 = Jgd2011::new(LatLon(35.0, 135.0))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new(degrees: LatLon) -> Result<Self, DegreesError> {
+        degrees.validate_degrees()?;
+        Ok(Self { degrees })
+    }
+
+    #[cfg(any(feature = "std", feature = "patchjgd"))]
     fn new_unchecked(degrees: LatLon) -> Self {
         Self { degrees }
     }
 
+    /// Corrects from the reference epoch to the observation epoch.
+    ///
+    /// 元期の座標を、[`SemiDynamic`] の今期の座標に補正する (セミ・ダイナミック補正)。
+    /// パラメータが存在しない地域では `None` を返す。
+    /// 緯度・経度のみを補正し、高さは補正しない。楕円体高の補正量は [`SemiDynamic::bilinear`] で得られる。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use jgd::{Jgd2011, LatLon, SemiDynamic};
+    /// #
+    /// # let semidyna = SemiDynamic::parse("").unwrap();
+    /// # let jgd2011 = Jgd2011::new(LatLon(35.0, 135.0)).unwrap();
+    /// let observation = jgd2011.to_observation_epoch(&semidyna);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn to_observation_epoch(&self, par: &SemiDynamic) -> Option<Jgd2011> {
+        par.forward(self.degrees).map(Self::new_unchecked)
+    }
+
+    /// Inverse of [`Jgd2011::to_observation_epoch`].
+    ///
+    /// 今期の座標を、元期の座標に補正する。
+    /// パラメータが存在しない地域では `None` を返す。高さは補正しない。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use jgd::{Jgd2011, LatLon, SemiDynamic};
+    /// #
+    /// # let semidyna = SemiDynamic::parse("").unwrap();
+    /// # let jgd2011 = Jgd2011::new(LatLon(35.0, 135.0)).unwrap();
+    /// let reference = jgd2011.to_reference_epoch(&semidyna);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn to_reference_epoch(&self, par: &SemiDynamic) -> Option<Jgd2011> {
+        par.backward(self.degrees).map(Self::new_unchecked)
+    }
+
     /// Inverse of [`Jgd2000::to_jgd2011`].
     ///
     /// [`TOUHOKUTAIHEIYOUOKI2011`] を用いて逆変換される。
-    fn _to_jgd2000(&self) {
-        // Jgd2000::_to_tokyo() と同様の課題あり
+    /// [`Jgd2000::to_tokyo`] と同様に、[`Jgd2000::to_jgd2011`] で戻した座標が一致するまで反復する。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use jgd::{Jgd2000, LatLon};
+    /// #
+    /// # let jgd2011 = Jgd2000::new(LatLon(35.0, 135.0)).unwrap().to_jgd2011();
+    /// let LatLon(lat, lon) = jgd2011.to_jgd2000().degrees();
+    /// ```
+    #[cfg(feature = "patchjgd")]
+    pub fn to_jgd2000(&self) -> Jgd2000 {
+        let degrees = invert(self.degrees, |degrees| {
+            Jgd2000::new_unchecked(degrees).to_jgd2011().degrees()
+        });
+        Jgd2000::new_unchecked(degrees)
     }
 
     /// Returnes coordinate in degrees.
@@ -278,7 +392,21 @@ impl Jgd2011 {
     }
 }
 
-/// 平面直角座標系
-// https://vldb.gsi.go.jp/sokuchi/surveycalc/surveycalc/algorithm/xy2bl/xy2bl.htm
-// https://sw1227.hatenablog.com/entry/2018/11/30/200702
-struct _PlaneRectangular {}
+/// Solves `forward(x) == target` by fixed-point iteration from `target`.
+///
+/// 変換量は格子間隔に比べて小さく滑らかなので、数回で収束する。
+#[cfg(any(feature = "tky2jgd", feature = "patchjgd"))]
+fn invert(target: LatLon, forward: impl Fn(LatLon) -> LatLon) -> LatLon {
+    const TOLERANCE: f64 = 1e-9 / SECS;
+
+    let mut ret = target;
+    for _ in 0..10 {
+        let diff = target - forward(ret);
+        ret = ret + diff;
+        let LatLon(d_lat, d_lon) = diff;
+        if d_lat.abs() < TOLERANCE && d_lon.abs() < TOLERANCE {
+            break;
+        }
+    }
+    ret
+}
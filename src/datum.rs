@@ -0,0 +1,299 @@
+use crate::{DegreesError, Ellipsoid, Jgd2000, Jgd2011, LatLon, Tokyo, Tokyo97, BESSEL, GRS80};
+
+/// A geodetic datum in which a coordinate is expressed.
+///
+/// [`Tokyo`], [`Tokyo97`], [`Jgd2000`], [`Jgd2011`] に共通する振る舞い。
+/// 測地系を問わない関数を書くために使う。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{Datum, Jgd2011, LatLon, Tokyo97};
+///
+/// fn describe<D: Datum>(coord: &D) -> String {
+///     format!("{} {:.6}", D::NAME, coord.degrees())
+/// }
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let tokyo97 = Tokyo97::new(LatLon(35.0, 135.0))?;
+/// let jgd2011 = tokyo97.transform_to::<Jgd2011>();
+/// # assert_eq!(describe(&tokyo97), "Tokyo 97 35.000000, 135.000000");
+/// let s = describe(&jgd2011);
+/// #   Ok(())
+/// # }
+/// ```
+pub trait Datum: Sized {
+    /// Runtime identifier of the datum.
+    const KIND: DatumKind;
+
+    /// Name of the datum.
+    const NAME: &'static str = Self::KIND.name();
+
+    /// EPSG code of the geographic 2D CRS, if registered.
+    ///
+    /// [`Tokyo97`] は EPSG に登録されていないため `None`。
+    const EPSG: Option<u32> = Self::KIND.epsg();
+
+    /// Reference ellipsoid.
+    const ELLIPSOID: Ellipsoid = Self::KIND.ellipsoid();
+
+    /// Constructs with a coordinate in degrees.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DegreesError`] if the [`LatLon`] is out of range in degrees.
+    fn new(degrees: LatLon) -> Result<Self, DegreesError>;
+
+    /// Returns coordinate in degrees.
+    fn degrees(&self) -> LatLon;
+
+    /// Transforms to datum `T` along the available path.
+    ///
+    /// 変換経路は型によって決まり、経路のない組み合わせはコンパイルエラーとなる。
+    /// 変換の精度は経路上の各メソッドのドキュメントを参照。
+    fn transform_to<T: Datum>(&self) -> T
+    where
+        Self: TransformTo<T>,
+    {
+        TransformTo::transform(self)
+    }
+}
+
+/// Identifier of a [`Datum`] known at runtime.
+///
+/// 実行時に決まる測地系。EPSG コードなどから [`Crs`](crate::Crs) を経由して得られる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DatumKind {
+    /// [`Tokyo`]
+    Tokyo,
+    /// [`Tokyo97`]
+    Tokyo97,
+    /// [`Jgd2000`]
+    Jgd2000,
+    /// [`Jgd2011`]
+    Jgd2011,
+}
+impl DatumKind {
+    /// All datums.
+    pub const ALL: [DatumKind; 4] = [
+        DatumKind::Tokyo,
+        DatumKind::Tokyo97,
+        DatumKind::Jgd2000,
+        DatumKind::Jgd2011,
+    ];
+
+    /// Returns the name of the datum.
+    pub const fn name(self) -> &'static str {
+        match self {
+            DatumKind::Tokyo => "Tokyo",
+            DatumKind::Tokyo97 => "Tokyo 97",
+            DatumKind::Jgd2000 => "JGD2000",
+            DatumKind::Jgd2011 => "JGD2011",
+        }
+    }
+
+    /// Returns the EPSG code of the geographic 2D CRS, if registered.
+    pub const fn epsg(self) -> Option<u32> {
+        match self {
+            DatumKind::Tokyo => Some(4301),
+            DatumKind::Tokyo97 => None,
+            DatumKind::Jgd2000 => Some(4612),
+            DatumKind::Jgd2011 => Some(6668),
+        }
+    }
+
+    /// Returns the reference ellipsoid.
+    pub const fn ellipsoid(self) -> Ellipsoid {
+        match self {
+            DatumKind::Tokyo | DatumKind::Tokyo97 => BESSEL,
+            DatumKind::Jgd2000 | DatumKind::Jgd2011 => GRS80,
+        }
+    }
+}
+
+/// Transformation into datum `T`.
+///
+/// [`Datum::transform_to`] から使われる。
+pub trait TransformTo<T: Datum> {
+    /// Transforms to `T`.
+    fn transform(&self) -> T;
+}
+impl<T: Datum + Copy> TransformTo<T> for T {
+    fn transform(&self) -> T {
+        *self
+    }
+}
+
+impl Datum for Tokyo {
+    const KIND: DatumKind = DatumKind::Tokyo;
+
+    fn new(degrees: LatLon) -> Result<Self, DegreesError> {
+        Tokyo::new(degrees)
+    }
+
+    fn degrees(&self) -> LatLon {
+        Tokyo::degrees(self)
+    }
+}
+
+impl Datum for Tokyo97 {
+    const KIND: DatumKind = DatumKind::Tokyo97;
+
+    fn new(degrees: LatLon) -> Result<Self, DegreesError> {
+        Tokyo97::new(degrees)
+    }
+
+    fn degrees(&self) -> LatLon {
+        Tokyo97::degrees(self)
+    }
+}
+
+impl Datum for Jgd2000 {
+    const KIND: DatumKind = DatumKind::Jgd2000;
+
+    fn new(degrees: LatLon) -> Result<Self, DegreesError> {
+        Jgd2000::new(degrees)
+    }
+
+    fn degrees(&self) -> LatLon {
+        Jgd2000::degrees(self)
+    }
+}
+
+impl Datum for Jgd2011 {
+    const KIND: DatumKind = DatumKind::Jgd2011;
+
+    fn new(degrees: LatLon) -> Result<Self, DegreesError> {
+        Jgd2011::new(degrees)
+    }
+
+    fn degrees(&self) -> LatLon {
+        Jgd2011::degrees(self)
+    }
+}
+
+#[cfg(feature = "tky2jgd")]
+impl TransformTo<Jgd2000> for Tokyo {
+    fn transform(&self) -> Jgd2000 {
+        self.to_jgd2000()
+    }
+}
+
+#[cfg(feature = "tky2jgd")]
+impl TransformTo<Tokyo97> for Tokyo {
+    fn transform(&self) -> Tokyo97 {
+        self.to_jgd2000().to_tokyo97()
+    }
+}
+
+#[cfg(all(feature = "tky2jgd", feature = "patchjgd"))]
+impl TransformTo<Jgd2011> for Tokyo {
+    fn transform(&self) -> Jgd2011 {
+        self.to_jgd2000().to_jgd2011()
+    }
+}
+
+impl TransformTo<Jgd2000> for Tokyo97 {
+    fn transform(&self) -> Jgd2000 {
+        self.to_jgd2000()
+    }
+}
+
+#[cfg(feature = "patchjgd")]
+impl TransformTo<Jgd2011> for Tokyo97 {
+    fn transform(&self) -> Jgd2011 {
+        self.to_jgd2000().to_jgd2011()
+    }
+}
+
+impl TransformTo<Tokyo97> for Jgd2000 {
+    fn transform(&self) -> Tokyo97 {
+        self.to_tokyo97()
+    }
+}
+
+#[cfg(feature = "patchjgd")]
+impl TransformTo<Jgd2011> for Jgd2000 {
+    fn transform(&self) -> Jgd2011 {
+        self.to_jgd2011()
+    }
+}
+
+#[cfg(feature = "tky2jgd")]
+impl TransformTo<Tokyo> for Tokyo97 {
+    fn transform(&self) -> Tokyo {
+        self.to_jgd2000().to_tokyo()
+    }
+}
+
+#[cfg(feature = "tky2jgd")]
+impl TransformTo<Tokyo> for Jgd2000 {
+    fn transform(&self) -> Tokyo {
+        self.to_tokyo()
+    }
+}
+
+#[cfg(all(feature = "tky2jgd", feature = "patchjgd"))]
+impl TransformTo<Tokyo> for Jgd2011 {
+    fn transform(&self) -> Tokyo {
+        self.to_jgd2000().to_tokyo()
+    }
+}
+
+#[cfg(feature = "patchjgd")]
+impl TransformTo<Tokyo97> for Jgd2011 {
+    fn transform(&self) -> Tokyo97 {
+        self.to_jgd2000().to_tokyo97()
+    }
+}
+
+#[cfg(feature = "patchjgd")]
+impl TransformTo<Jgd2000> for Jgd2011 {
+    fn transform(&self) -> Jgd2000 {
+        self.to_jgd2000()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Datum, DatumKind, Jgd2000, Jgd2011, LatLon, Tokyo, Tokyo97};
+
+    fn epsg<D: Datum>() -> Option<u32> {
+        D::EPSG
+    }
+
+    #[test]
+    fn identifiers() {
+        assert_eq!(epsg::<Tokyo>(), Some(4301));
+        assert_eq!(epsg::<Tokyo97>(), None);
+        assert_eq!(epsg::<Jgd2000>(), Some(4612));
+        assert_eq!(epsg::<Jgd2011>(), Some(6668));
+        assert_eq!(Tokyo97::KIND, DatumKind::Tokyo97);
+        assert_eq!(Jgd2011::NAME, "JGD2011");
+    }
+
+    #[test]
+    fn identity() {
+        let jgd2000 = Jgd2000::new(LatLon(35., 135.)).unwrap();
+        assert_eq!(jgd2000.transform_to::<Jgd2000>(), jgd2000);
+    }
+
+    #[test]
+    fn path() {
+        let tokyo97 = Tokyo97::new(LatLon(35., 135.)).unwrap();
+        let ret = tokyo97.transform_to::<Jgd2000>();
+        assert_eq!(ret, tokyo97.to_jgd2000());
+        let ret = ret.transform_to::<Tokyo97>();
+        assert_eq!(ret, tokyo97.to_jgd2000().to_tokyo97());
+    }
+
+    #[cfg(feature = "patchjgd")]
+    #[test]
+    fn composed() {
+        let tokyo97 = Tokyo97::new(LatLon(38., 141.)).unwrap();
+        let ret: Jgd2011 = tokyo97.transform_to();
+        assert_eq!(ret, tokyo97.to_jgd2000().to_jgd2011());
+        assert_eq!(<Jgd2011 as Datum>::degrees(&ret), ret.degrees());
+        assert_eq!(ret.transform_to::<Tokyo97>(), ret.to_jgd2000().to_tokyo97());
+    }
+}
@@ -0,0 +1,890 @@
+use std::{
+    fmt::{self, Write},
+    str::FromStr,
+};
+
+use crate::{Dms, DmsError, LatLon};
+
+/// Order of latitude and longitude in text.
+///
+/// 文字列中の緯度・経度の順序。
+/// 半球を表す文字 (`N`, `E` など) がある場合は、その文字による。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AxisOrder {
+    /// Latitude first.
+    #[default]
+    LatLon,
+    /// Longitude first.
+    LonLat,
+}
+
+/// Parses degrees minutes seconds.
+///
+/// 次の表記に対応する。全角の数字や記号も使用できる。
+///
+/// - 記号: `35°39'29.1572"N`, `35°39′29.1572″`
+/// - 漢字: `35度39分29.1572秒`, `北緯35度39分29.1572秒`
+/// - 区切り: `35 39 29.1572`, `35:39:29.1572`
+/// - 国土地理院の連結形式: `353929.1572`, `1394428.8869`, `N353929.1572`
+/// - 十進度: `35.658099`, `35.658099°`
+///
+/// 半球は先頭または末尾の `N`, `S`, `E`, `W` で表し、`S` と `W` は負の値となる。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::Dms;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let dms: Dms = "35°39'29.1572\"N".parse()?;
+/// # assert_eq!(dms, Dms::new(35, 39, 29.1572)?);
+/// let dms: Dms = "1394428.8869".parse()?;
+/// # assert_eq!(dms, Dms::new(139, 44, 28.8869)?);
+/// #   Ok(())
+/// # }
+/// ```
+impl FromStr for Dms {
+    type Err = ParseDmsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (dms, _) = parse_dms(s)?;
+        Ok(dms)
+    }
+}
+
+/// Parses a pair of latitude and longitude in [`AxisOrder::LatLon`].
+///
+/// 緯度と経度はカンマ `,` 読点 `、` または空白で区切る。
+/// 各座標の表記は [`Dms`] の [`FromStr`] と同じ。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{Dms, LatLon};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let dms: LatLon<Dms> = "35°39'29.1572\"N, 139°44'28.8869\"E".parse()?;
+/// let degrees = dms.to_degrees();
+/// #   Ok(())
+/// # }
+/// ```
+impl FromStr for LatLon<Dms> {
+    type Err = ParseDmsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with_order(s, AxisOrder::LatLon)
+    }
+}
+
+impl LatLon<Dms> {
+    /// Parses a pair of latitude and longitude in `order`.
+    ///
+    /// 半球を表す文字がある場合は `order` より優先される。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jgd::{AxisOrder, Dms, LatLon};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let dms = LatLon::<Dms>::parse_with_order("1394428.8869 353929.1572", AxisOrder::LonLat)?;
+    /// # assert_eq!(dms, LatLon(Dms::new(35, 39, 29.1572)?, Dms::new(139, 44, 28.8869)?));
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn parse_with_order(s: &str, order: AxisOrder) -> Result<Self, ParseDmsError> {
+        let (first, second) = split_pair(s)?;
+        let (first, first_axis) = parse_dms(first)?;
+        let (second, second_axis) = parse_dms(second)?;
+
+        let lat_first = match (first_axis, second_axis) {
+            (Some(a), Some(b)) if a == b => {
+                return Err(ParseDmsError::new(
+                    s,
+                    "both coordinates are on the same axis",
+                ))
+            }
+            (Some(axis), _) => axis == Axis::Lat,
+            (_, Some(axis)) => axis == Axis::Lon,
+            (None, None) => order == AxisOrder::LatLon,
+        };
+        Ok(if lat_first {
+            LatLon(first, second)
+        } else {
+            LatLon(second, first)
+        })
+    }
+}
+
+/// Errors in parsing [`Dms`] or [`LatLon<Dms>`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDmsError {
+    token: String,
+    message: &'static str,
+}
+impl ParseDmsError {
+    fn new(token: &str, message: &'static str) -> Self {
+        Self {
+            token: token.to_string(),
+            message,
+        }
+    }
+
+    /// Returns the offending part of the input.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+impl fmt::Display for ParseDmsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {:?}", self.message, self.token)
+    }
+}
+impl std::error::Error for ParseDmsError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    Lat,
+    Lon,
+}
+
+/// Input with each character normalized to half-width.
+struct Text<'a> {
+    input: &'a str,
+    /// Byte offset in the input and the normalized character.
+    chars: Vec<(usize, char)>,
+}
+impl<'a> Text<'a> {
+    fn new(input: &'a str) -> Self {
+        let chars = input
+            .char_indices()
+            .map(|(i, c)| (i, normalize(c)))
+            .collect();
+        Self { input, chars }
+    }
+
+    fn get(&self, i: usize) -> Option<char> {
+        self.chars.get(i).map(|&(_, c)| c)
+    }
+
+    /// Returns the original text of characters in `start..end`.
+    fn slice(&self, start: usize, end: usize) -> &'a str {
+        let offset = |i: usize| self.chars.get(i).map_or(self.input.len(), |&(o, _)| o);
+        &self.input[offset(start)..offset(end)]
+    }
+
+    fn normalized(&self, start: usize, end: usize) -> String {
+        self.chars[start..end].iter().map(|&(_, c)| c).collect()
+    }
+
+    fn error(&self, start: usize, end: usize, message: &'static str) -> ParseDmsError {
+        ParseDmsError::new(self.slice(start, end), message)
+    }
+}
+
+fn normalize(c: char) -> char {
+    let c = match c {
+        // 全角英数字・記号
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        '\u{3000}' => ' ',
+        '\u{2212}' | '\u{2010}' | '\u{2013}' => '-',
+        '\u{2032}' | '\u{2019}' => '\'',
+        '\u{2033}' | '\u{201D}' => '"',
+        '\u{BA}' | '\u{2DA}' => '°',
+        '、' => ',',
+        _ => c,
+    };
+    c.to_ascii_uppercase()
+}
+
+fn hemisphere(c: char) -> Option<(Axis, bool)> {
+    match c {
+        'N' => Some((Axis::Lat, false)),
+        'S' => Some((Axis::Lat, true)),
+        'E' => Some((Axis::Lon, false)),
+        'W' => Some((Axis::Lon, true)),
+        _ => None,
+    }
+}
+
+fn hemisphere_ja(s: &str) -> Option<(Axis, bool)> {
+    match s {
+        "北緯" => Some((Axis::Lat, false)),
+        "南緯" => Some((Axis::Lat, true)),
+        "東経" => Some((Axis::Lon, false)),
+        "西経" => Some((Axis::Lon, true)),
+        _ => None,
+    }
+}
+
+fn unit_of(c: char) -> Option<usize> {
+    match c {
+        '°' | '度' => Some(0),
+        '\'' | '分' => Some(1),
+        '"' | '秒' => Some(2),
+        _ => None,
+    }
+}
+
+/// A number with an optional unit: 0 for degrees, 1 for minutes and 2 for seconds.
+struct Component {
+    start: usize,
+    end: usize,
+    unit: Option<usize>,
+}
+
+fn parse_dms(input: &str) -> Result<(Dms, Option<Axis>), ParseDmsError> {
+    let text = Text::new(input);
+    let is_space = |i: usize| text.get(i).is_some_and(char::is_whitespace);
+
+    let mut lo = 0;
+    let mut hi = text.chars.len();
+    while lo < hi && is_space(lo) {
+        lo += 1;
+    }
+    while lo < hi && is_space(hi - 1) {
+        hi -= 1;
+    }
+    if lo == hi {
+        return Err(ParseDmsError::new(input, "empty coordinate"));
+    }
+
+    // 半球
+    let mut hemi = None;
+    if let Some(h) = text.get(lo).and_then(hemisphere) {
+        hemi = Some(h);
+        lo += 1;
+    } else if let Some(h) = (lo + 2 <= hi)
+        .then(|| hemisphere_ja(&text.normalized(lo, lo + 2)))
+        .flatten()
+    {
+        hemi = Some(h);
+        lo += 2;
+    }
+    if let Some(h) = text.get(hi - 1).filter(|_| lo < hi).and_then(hemisphere) {
+        if hemi.is_some() {
+            return Err(text.error(hi - 1, hi, "duplicate hemisphere"));
+        }
+        hemi = Some(h);
+        hi -= 1;
+    }
+    while lo < hi && is_space(lo) {
+        lo += 1;
+    }
+    while lo < hi && is_space(hi - 1) {
+        hi -= 1;
+    }
+
+    // 符号
+    let mut negative = hemi.is_some_and(|(_, negative)| negative);
+    if let Some(sign @ ('-' | '+')) = text.get(lo) {
+        if hemi.is_some() {
+            return Err(text.error(lo, lo + 1, "sign with hemisphere"));
+        }
+        negative = sign == '-';
+        lo += 1;
+    }
+
+    // 数値と単位の組
+    let mut components = Vec::new();
+    let mut separated = false;
+    let mut i = lo;
+    while i < hi {
+        let c = text.get(i).unwrap_or_default();
+        if c.is_whitespace() || c == ':' {
+            separated = true;
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < hi && text.get(i).is_some_and(|c| c.is_ascii_digit() || c == '.') {
+            i += 1;
+        }
+        if start == i {
+            return Err(text.error(i, i + 1, "unexpected character"));
+        }
+        let end = i;
+        let mut unit = text.get(i).filter(|_| i < hi).and_then(unit_of);
+        if unit.is_some() {
+            i += 1;
+            // '' で秒
+            if unit == Some(1) && i < hi && text.get(i) == Some('\'') {
+                unit = Some(2);
+                i += 1;
+            }
+        }
+        components.push(Component { start, end, unit });
+    }
+
+    let dms = match components.as_slice() {
+        [] => return Err(text.error(lo, hi, "missing number")),
+        [Component {
+            start,
+            end,
+            unit: None,
+        }] if !separated => parse_single(&text, *start, *end)?,
+        _ => parse_components(&text, &components)?,
+    };
+    let dms = if negative { -dms } else { dms };
+    Ok((dms, hemi.map(|(axis, _)| axis)))
+}
+
+/// Parses a number in the GSI's packed format or decimal degrees.
+fn parse_single(text: &Text, start: usize, end: usize) -> Result<Dms, ParseDmsError> {
+    let number = text.normalized(start, end);
+    let (int, frac) = number.split_once('.').unwrap_or((&number, ""));
+    match int.len() {
+        0..=3 => {
+            let degrees = parse_number(text, start, end)?;
+            Dms::from_degrees(degrees).map_err(|_| text.error(start, end, "invalid number"))
+        }
+        4 => Err(text.error(start, end, "ambiguous number of digits")),
+        n => {
+            // DDDMMSS.sss
+            let d = int[..n - 4]
+                .parse()
+                .map_err(|_| text.error(start, end, "invalid number"))?;
+            let m = int[n - 4..n - 2].parse().unwrap_or_default();
+            let s = format!("{}.{}", &int[n - 2..], frac)
+                .parse()
+                .map_err(|_| text.error(start, end, "invalid number"))?;
+            Dms::new(d, m, s).map_err(|e| {
+                let message = match e {
+                    DmsError::Minutes(_) => "minutes out of range",
+                    DmsError::Seconds(_) => "seconds out of range",
+                    DmsError::Degrees(_) => "degrees out of range",
+                };
+                text.error(start, end, message)
+            })
+        }
+    }
+}
+
+fn parse_components(text: &Text, components: &[Component]) -> Result<Dms, ParseDmsError> {
+    let mut values = [0.; 3];
+    let mut last = None;
+    for (i, c) in components.iter().enumerate() {
+        let unit = c.unit.unwrap_or(last.map_or(0, |u| u + 1));
+        if unit > 2 {
+            return Err(text.error(c.start, c.end, "too many components"));
+        }
+        if last.is_some_and(|last| unit <= last) {
+            return Err(text.error(c.start, c.end + c.unit.map_or(0, |_| 1), "unexpected unit"));
+        }
+        let value = parse_number(text, c.start, c.end)?;
+        let is_last = i + 1 == components.len();
+        if !is_last && value.fract() != 0. {
+            return Err(text.error(c.start, c.end, "fraction in non-last component"));
+        }
+        if unit > 0 && value >= 60. {
+            let message = if unit == 1 {
+                "minutes out of range"
+            } else {
+                "seconds out of range"
+            };
+            return Err(text.error(c.start, c.end, message));
+        }
+        values[unit] = value;
+        last = Some(unit);
+    }
+
+    // 端数を下位の単位に繰り下げる
+    let [d, m, s] = values;
+    let (d, m) = (d.trunc(), m + d.fract() * 60.);
+    let (m, s) = (m.trunc(), s + m.fract() * 60.);
+    Ok(Dms::new_unchecked(false, d as u32, m as u32, s))
+}
+
+fn parse_number(text: &Text, start: usize, end: usize) -> Result<f64, ParseDmsError> {
+    let number = text.normalized(start, end);
+    if number.starts_with('.') || number.ends_with('.') {
+        return Err(text.error(start, end, "invalid number"));
+    }
+    number
+        .parse()
+        .map_err(|_| text.error(start, end, "invalid number"))
+}
+
+/// Splits a pair of coordinates.
+fn split_pair(input: &str) -> Result<(&str, &str), ParseDmsError> {
+    let text = Text::new(input);
+    let separators = (0..text.chars.len())
+        .filter(|&i| matches!(text.get(i), Some(',' | ';')))
+        .collect::<Vec<_>>();
+    match separators.as_slice() {
+        [i] => return Ok((text.slice(0, *i), text.slice(i + 1, text.chars.len()))),
+        [] => {}
+        [_, i, ..] => return Err(text.error(*i, i + 1, "too many separators")),
+    }
+
+    // 空白で区切られた語
+    let mut words = Vec::new();
+    let mut start = None;
+    for i in 0..=text.chars.len() {
+        let space = text.get(i).map_or(true, char::is_whitespace);
+        match (start, space) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                words.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    let split = |i: usize| {
+        let (_, end) = words[i - 1];
+        let (start, _) = words[i];
+        (text.slice(0, end), text.slice(start, text.chars.len()))
+    };
+    let is_hemisphere = |i: usize| text.get(i).and_then(hemisphere).is_some();
+    let is_hemisphere_ja =
+        |i: usize| i + 2 <= text.chars.len() && hemisphere_ja(&text.normalized(i, i + 2)).is_some();
+
+    if words.len() < 2 {
+        return Err(ParseDmsError::new(input, "missing longitude or latitude"));
+    }
+    // 末尾の半球の直後、または先頭の半球の直前で区切る
+    for i in 1..words.len() {
+        let (_, prev_end) = words[i - 1];
+        let (start, _) = words[i];
+        if is_hemisphere(prev_end - 1) || is_hemisphere(start) || is_hemisphere_ja(start) {
+            return Ok(split(i));
+        }
+    }
+    if words.len() % 2 == 0 {
+        return Ok(split(words.len() / 2));
+    }
+    Err(ParseDmsError::new(
+        input,
+        "cannot split into latitude and longitude",
+    ))
+}
+
+/// Style of [`DmsFormat`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DmsStyle {
+    /// Decimal degrees: `35.65809922°N`
+    Decimal,
+    /// Degrees minutes seconds with symbols: `35°39'29.1572"N`
+    #[default]
+    Symbol,
+    /// Degrees minutes seconds in Japanese: `北緯35度39分29.1572秒`
+    Japanese,
+    /// GSI's packed format: `N353929.1572`
+    Packed,
+}
+
+/// Formatter of [`Dms`].
+///
+/// [`Dms`] の書式。出力した文字列は [`FromStr`] で元の値に戻せる。
+///
+/// 精度は最後の成分 (十進度では度、それ以外では秒) の小数点以下の桁数で、
+/// 既定値は [`DmsStyle::Decimal`] で 8桁、それ以外で 4桁。
+/// 丸めによって 60秒となる場合は分・度に繰り上げられる。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{Dms, DmsFormat, DmsStyle, LatLon};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let dms = LatLon(Dms::new(35, 39, 29.1572)?, Dms::new(139, 44, 28.8869)?);
+/// let format = DmsFormat::new(DmsStyle::Japanese).with_precision(2);
+/// assert_eq!(format.format(dms), "北緯35度39分29.16秒, 東経139度44分28.89秒");
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DmsFormat {
+    style: DmsStyle,
+    precision: usize,
+    padding: bool,
+    hemisphere: bool,
+}
+impl DmsFormat {
+    /// Constructs a [`DmsFormat`] with the default precision, padding and hemisphere.
+    pub const fn new(style: DmsStyle) -> Self {
+        let precision = match style {
+            DmsStyle::Decimal => 8,
+            _ => 4,
+        };
+        Self {
+            style,
+            precision,
+            padding: true,
+            hemisphere: true,
+        }
+    }
+
+    /// Returns self with the number of decimal places.
+    pub const fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Returns self with or without zero padding.
+    ///
+    /// 度を緯度 2桁、経度 3桁、分・秒を 2桁に揃える。既定で有効。
+    /// [`DmsStyle::Packed`] の分・秒は常に 2桁となる。
+    pub const fn with_padding(mut self, padding: bool) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Returns self with or without the hemisphere instead of the sign.
+    ///
+    /// 符号の代わりに `N`, `S`, `E`, `W` (日本語では北緯・南緯・東経・西経) を付ける。既定で有効。
+    pub const fn with_hemisphere(mut self, hemisphere: bool) -> Self {
+        self.hemisphere = hemisphere;
+        self
+    }
+
+    /// Formats a latitude.
+    pub fn format_lat(&self, lat: Dms) -> String {
+        let mut ret = String::new();
+        let _ = self.write(&mut ret, lat, Some(Axis::Lat));
+        ret
+    }
+
+    /// Formats a longitude.
+    pub fn format_lon(&self, lon: Dms) -> String {
+        let mut ret = String::new();
+        let _ = self.write(&mut ret, lon, Some(Axis::Lon));
+        ret
+    }
+
+    /// Formats a pair of latitude and longitude separated by a comma.
+    pub fn format(&self, dms: LatLon<Dms>) -> String {
+        let LatLon(lat, lon) = dms;
+        format!("{}, {}", self.format_lat(lat), self.format_lon(lon))
+    }
+
+    fn write(&self, f: &mut impl fmt::Write, dms: Dms, axis: Option<Axis>) -> fmt::Result {
+        let negative = dms.is_negative();
+        let hemisphere = axis
+            .filter(|_| self.hemisphere)
+            .map(|axis| match (axis, negative) {
+                (Axis::Lat, false) => ('N', "北緯"),
+                (Axis::Lat, true) => ('S', "南緯"),
+                (Axis::Lon, false) => ('E', "東経"),
+                (Axis::Lon, true) => ('W', "西経"),
+            });
+        let d_width = match axis.filter(|_| self.padding) {
+            Some(Axis::Lat) => 2,
+            Some(Axis::Lon) => 3,
+            None => 1,
+        };
+        let ms_width = if self.padding || self.style == DmsStyle::Packed {
+            2
+        } else {
+            1
+        };
+        let p = self.precision;
+
+        match (hemisphere, self.style) {
+            (Some((_, ja)), DmsStyle::Japanese) => f.write_str(ja)?,
+            (Some((c, _)), DmsStyle::Packed) => f.write_char(c)?,
+            (None, _) if negative => f.write_char('-')?,
+            _ => {}
+        }
+
+        if self.style == DmsStyle::Decimal {
+            let degrees = dms.to_degrees().abs();
+            // 整数部の桁数に小数点と小数部の桁数を加える
+            let width = d_width + if p > 0 { p + 1 } else { 0 };
+            write!(f, "{:0width$.p$}°", degrees)?;
+        } else {
+            let (d, m, s) = round_dms(dms.d(), dms.m(), dms.s(), p);
+            let s_width = ms_width + if p > 0 { p + 1 } else { 0 };
+            match self.style {
+                DmsStyle::Japanese => {
+                    write!(f, "{d:0d_width$}度{m:0ms_width$}分{s:0s_width$.p$}秒")?
+                }
+                DmsStyle::Packed => write!(f, "{d:0d_width$}{m:02}{s:0s_width$.p$}")?,
+                _ => write!(f, "{d:0d_width$}°{m:0ms_width$}'{s:0s_width$.p$}\"")?,
+            }
+        }
+
+        match (hemisphere, self.style) {
+            (Some((c, _)), DmsStyle::Decimal | DmsStyle::Symbol) => f.write_char(c),
+            _ => Ok(()),
+        }
+    }
+}
+impl Default for DmsFormat {
+    fn default() -> Self {
+        Self::new(DmsStyle::default())
+    }
+}
+
+/// Rounds seconds to `precision` decimal places and carries into minutes and degrees.
+fn round_dms(d: u32, m: u32, s: f64, precision: usize) -> (u32, u32, f64) {
+    let scale = 10f64.powi(precision as i32);
+    let s = (s * scale).round() / scale;
+    if s < 60. {
+        return (d, m, s);
+    }
+    let (m, s) = (m + 1, s - 60.);
+    if m < 60 {
+        (d, m, s)
+    } else {
+        (d + 1, m - 60, s)
+    }
+}
+
+/// Formats in [`DmsStyle::Symbol`] without the hemisphere.
+///
+/// 精度を指定した場合は秒の小数点以下の桁数となる。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::Dms;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let dms = Dms::new(35, 39, 29.1572)?;
+/// assert_eq!(format!("{}", dms), "35°39'29.1572\"");
+/// assert_eq!(format!("{:.2}", dms), "35°39'29.16\"");
+/// #   Ok(())
+/// # }
+/// ```
+impl fmt::Display for Dms {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match f.precision() {
+            Some(p) => DmsFormat::new(DmsStyle::Symbol)
+                .with_precision(p)
+                .with_padding(false)
+                .write(f, *self, None),
+            None => {
+                if self.is_negative() {
+                    f.write_char('-')?;
+                }
+                write!(f, "{}°{}'{}\"", self.d(), self.m(), self.s())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AxisOrder, Dms, DmsFormat, DmsStyle, LatLon};
+
+    const LAT: Dms = Dms::new_unchecked(false, 35, 39, 29.1572);
+    const LON: Dms = Dms::new_unchecked(false, 139, 44, 28.8869);
+
+    #[test]
+    fn notations() {
+        for s in [
+            "35°39'29.1572\"N",
+            "35°39′29.1572″",
+            "N 35°39'29.1572\"",
+            "35度39分29.1572秒",
+            "北緯35度39分29.1572秒",
+            "３５度３９分２９．１５７２秒",
+            "35 39 29.1572",
+            "35:39:29.1572",
+            "353929.1572",
+            "N353929.1572",
+            "35°39'29.1572",
+            "35°39'29.1572''",
+        ] {
+            assert_eq!(s.parse(), Ok(LAT), "{}", s);
+        }
+        assert_eq!("1394428.8869".parse(), Ok(LON));
+        assert_eq!("E1394428.8869".parse(), Ok(LON));
+    }
+
+    #[test]
+    fn fractions() {
+        assert_eq!("35.5".parse(), Ok(Dms::new_unchecked(false, 35, 30, 0.0)));
+        assert_eq!(
+            "35°39.5'".parse(),
+            Ok(Dms::new_unchecked(false, 35, 39, 30.0))
+        );
+        assert_eq!(
+            "35°39'29\"".parse(),
+            Ok(Dms::new_unchecked(false, 35, 39, 29.0))
+        );
+        assert_eq!("35″".parse(), Ok(Dms::new_unchecked(false, 0, 0, 35.0)));
+    }
+
+    #[test]
+    fn hemisphere() {
+        assert_eq!("35°30'S".parse(), Ok(Dms::new_unchecked(true, 35, 30, 0.0)));
+        assert_eq!(
+            "W1394428.8869".parse(),
+            Ok(Dms::new_unchecked(true, 139, 44, 28.8869))
+        );
+        assert_eq!(
+            "南緯0度30分".parse(),
+            Ok(Dms::new_unchecked(true, 0, 30, 0.0))
+        );
+        assert_eq!("-35°30'".parse(), Ok(Dms::new_unchecked(true, 35, 30, 0.0)));
+    }
+
+    #[test]
+    fn errors() {
+        for (s, message) in [
+            ("", "empty coordinate: \"\""),
+            ("35°3x'", "unexpected character: \"x\""),
+            ("35°61'", "minutes out of range: \"61\""),
+            ("356129", "minutes out of range: \"356129\""),
+            ("35°39'60\"", "seconds out of range: \"60\""),
+            ("3539", "ambiguous number of digits: \"3539\""),
+            ("35'39°", "unexpected unit: \"39°\""),
+            ("35.5°39'", "fraction in non-last component: \"35.5\""),
+            ("N35°S", "duplicate hemisphere: \"S\""),
+            ("-35°S", "sign with hemisphere: \"-\""),
+            ("35.3.9", "invalid number: \"35.3.9\""),
+            ("３５度３ｘ分", "unexpected character: \"ｘ\""),
+        ] {
+            let err = s.parse::<Dms>().unwrap_err();
+            assert_eq!(err.to_string(), message, "{}", s);
+        }
+    }
+
+    #[test]
+    fn lat_lon() {
+        for s in [
+            "35°39'29.1572\"N, 139°44'28.8869\"E",
+            "139°44'28.8869\"E, 35°39'29.1572\"N",
+            "35°39'29.1572\"N 139°44'28.8869\"E",
+            "N35 39 29.1572 E139 44 28.8869",
+            "北緯35度39分29.1572秒 東経139度44分28.8869秒",
+            "35 39 29.1572 139 44 28.8869",
+            "353929.1572,1394428.8869",
+            "３５３９２９．１５７２、１３９４４２８．８８６９",
+        ] {
+            assert_eq!(s.parse(), Ok(LatLon(LAT, LON)), "{}", s);
+        }
+    }
+
+    #[test]
+    fn axis_order() {
+        let ret = LatLon::<Dms>::parse_with_order("1394428.8869 353929.1572", AxisOrder::LonLat);
+        assert_eq!(ret, Ok(LatLon(LAT, LON)));
+        let ret = LatLon::<Dms>::parse_with_order("35N 139E", AxisOrder::LonLat);
+        assert_eq!(
+            ret,
+            Ok(LatLon(
+                Dms::new_unchecked(false, 35, 0, 0.0),
+                Dms::new_unchecked(false, 139, 0, 0.0)
+            ))
+        );
+
+        let err = "35N 36N".parse::<LatLon<Dms>>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "both coordinates are on the same axis: \"35N 36N\""
+        );
+        let err = "35 39 29".parse::<LatLon<Dms>>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "cannot split into latitude and longitude: \"35 39 29\""
+        );
+    }
+
+    #[test]
+    fn format_styles() {
+        let dms = LatLon(LAT, LON);
+        for (style, exp) in [
+            (DmsStyle::Decimal, "35.65809922°N, 139.74135747°E"),
+            (DmsStyle::Symbol, "35°39'29.1572\"N, 139°44'28.8869\"E"),
+            (
+                DmsStyle::Japanese,
+                "北緯35度39分29.1572秒, 東経139度44分28.8869秒",
+            ),
+            (DmsStyle::Packed, "N353929.1572, E1394428.8869"),
+        ] {
+            assert_eq!(DmsFormat::new(style).format(dms), exp);
+        }
+    }
+
+    #[test]
+    fn format_padding() {
+        let dms = LatLon(
+            Dms::new_unchecked(true, 5, 3, 2.5),
+            Dms::new_unchecked(false, 9, 8, 7.25),
+        );
+        let format = DmsFormat::new(DmsStyle::Symbol).with_precision(1);
+        assert_eq!(format.format(dms), "05°03'02.5\"S, 009°08'07.3\"E");
+        let format = format.with_padding(false).with_hemisphere(false);
+        assert_eq!(format.format(dms), "-5°3'2.5\", 9°8'7.3\"");
+        let format = DmsFormat::new(DmsStyle::Packed).with_padding(false);
+        assert_eq!(
+            format.format_lat(Dms::new_unchecked(true, 0, 30, 0.0)),
+            "S03000.0000"
+        );
+        let format = DmsFormat::new(DmsStyle::Decimal).with_precision(3);
+        assert_eq!(
+            format.format_lon(Dms::new_unchecked(false, 9, 30, 0.0)),
+            "009.500°E"
+        );
+    }
+
+    #[test]
+    fn format_carry() {
+        let format = DmsFormat::new(DmsStyle::Symbol).with_precision(3);
+        assert_eq!(
+            format.format_lat(Dms::new_unchecked(false, 35, 59, 59.9996)),
+            "36°00'00.000\"N"
+        );
+        assert_eq!(
+            format.format_lat(Dms::new_unchecked(false, 35, 58, 59.9996)),
+            "35°59'00.000\"N"
+        );
+        let format = format.with_precision(0);
+        assert_eq!(
+            format.format_lon(Dms::new_unchecked(false, 139, 59, 59.5)),
+            "140°00'00\"E"
+        );
+    }
+
+    #[test]
+    fn format_round_trip() {
+        let values = [
+            LAT,
+            Dms::new_unchecked(true, 35, 59, 59.99996),
+            Dms::new_unchecked(true, 0, 30, 0.5),
+            Dms::new_unchecked(false, 0, 0, 0.0),
+        ];
+        for style in [
+            DmsStyle::Decimal,
+            DmsStyle::Symbol,
+            DmsStyle::Japanese,
+            DmsStyle::Packed,
+        ] {
+            for padding in [true, false] {
+                for hemisphere in [true, false] {
+                    let format = DmsFormat::new(style)
+                        .with_padding(padding)
+                        .with_hemisphere(hemisphere)
+                        .with_precision(12);
+                    for dms in values {
+                        let s = format.format(LatLon(dms, dms));
+                        let ret = s.parse::<LatLon<Dms>>().unwrap().to_degrees();
+                        let exp = LatLon(dms, dms).to_degrees();
+                        assert!((ret.0 - exp.0).abs() < 1e-12, "{}", s);
+                        assert!((ret.1 - exp.1).abs() < 1e-12, "{}", s);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(LAT.to_string(), "35°39'29.1572\"");
+        assert_eq!(
+            Dms::new_unchecked(true, 35, 30, 1.5).to_string(),
+            "-35°30'1.5\""
+        );
+        assert_eq!(
+            format!("{:.2}", Dms::new_unchecked(true, 0, 59, 59.999)),
+            "-1°0'0.00\""
+        );
+        let dms = LatLon(LAT, LON);
+        assert_eq!(dms.to_string().parse(), Ok(dms));
+    }
+}
@@ -1,18 +1,106 @@
-use std::ops::{Add, Sub};
+use core::ops::{Add, Div, Mul, Neg, Sub};
 
 use crate::LatLon;
 
+#[cfg(not(any(feature = "std", test)))]
+use crate::math::Float;
+
 /// Earth-centered, Earth-fixed coordinate.
-#[derive(Debug, Clone, Copy)]
+///
+/// 地心直交座標。単位はメートル。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{ECEF, GRS80};
+///
+/// let ecef = ECEF::new(-3959340.203, 3352854.274, 3697471.413);
+/// let degrees = GRS80.to_geodetic(ecef);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct ECEF {
     x: f64,
     y: f64,
     z: f64,
 }
 impl ECEF {
-    pub(crate) const fn new(x: f64, y: f64, z: f64) -> Self {
+    /// Constructs a [`ECEF`] with X, Y and Z in meters.
+    pub const fn new(x: f64, y: f64, z: f64) -> Self {
         Self { x, y, z }
     }
+
+    /// Returns X in meters.
+    pub fn x(&self) -> f64 {
+        self.x
+    }
+
+    /// Returns Y in meters.
+    pub fn y(&self) -> f64 {
+        self.y
+    }
+
+    /// Returns Z in meters.
+    pub fn z(&self) -> f64 {
+        self.z
+    }
+
+    /// Returns the length as a vector from the center of the Earth.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jgd::ECEF;
+    ///
+    /// let norm = ECEF::new(3.0, 4.0, 12.0).norm();
+    /// # assert_eq!(norm, 13.0);
+    /// ```
+    pub fn norm(&self) -> f64 {
+        self.dot(*self).sqrt()
+    }
+
+    /// Returns the straight distance to `other` in meters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jgd::ECEF;
+    ///
+    /// let distance = ECEF::new(1.0, 2.0, 3.0).distance(ECEF::new(4.0, 6.0, 3.0));
+    /// # assert_eq!(distance, 5.0);
+    /// ```
+    pub fn distance(&self, other: ECEF) -> f64 {
+        (*self - other).norm()
+    }
+
+    /// Returns the dot product with `rhs`.
+    pub fn dot(&self, rhs: ECEF) -> f64 {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+
+    /// Returns self with function `f` applied to each of X, Y and Z.
+    pub fn map(self, f: impl Fn(f64) -> f64) -> Self {
+        Self::new(f(self.x), f(self.y), f(self.z))
+    }
+}
+impl From<[f64; 3]> for ECEF {
+    fn from([x, y, z]: [f64; 3]) -> Self {
+        Self::new(x, y, z)
+    }
+}
+impl From<ECEF> for [f64; 3] {
+    fn from(ECEF { x, y, z }: ECEF) -> Self {
+        [x, y, z]
+    }
+}
+impl From<(f64, f64, f64)> for ECEF {
+    fn from((x, y, z): (f64, f64, f64)) -> Self {
+        Self::new(x, y, z)
+    }
+}
+impl From<ECEF> for (f64, f64, f64) {
+    fn from(ECEF { x, y, z }: ECEF) -> Self {
+        (x, y, z)
+    }
 }
 impl Add for ECEF {
     type Output = Self;
@@ -34,6 +122,24 @@ impl Sub for ECEF {
         }
     }
 }
+impl Neg for ECEF {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        self.map(|x| -x)
+    }
+}
+impl Mul<f64> for ECEF {
+    type Output = Self;
+    fn mul(self, rhs: f64) -> Self::Output {
+        self.map(|x| x * rhs)
+    }
+}
+impl Div<f64> for ECEF {
+    type Output = Self;
+    fn div(self, rhs: f64) -> Self::Output {
+        self.map(|x| x / rhs)
+    }
+}
 
 /// GRS80 ellipsoid.
 pub const GRS80: Ellipsoid = Ellipsoid {
@@ -47,8 +153,24 @@ pub const BESSEL: Ellipsoid = Ellipsoid {
     polar_radius: 6356078.963,
 };
 
+/// WGS84 ellipsoid.
+pub const WGS84: Ellipsoid = Ellipsoid::from_inverse_flattening(6378137.0, 298.257223563);
+
 /// Earth ellipsoid.
-#[derive(Debug, Clone)]
+///
+/// 地球楕円体。
+/// [`GRS80`], [`BESSEL`], [`WGS84`] 以外の楕円体や球も定義できる。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::Ellipsoid;
+///
+/// // International 1924
+/// let hayford = Ellipsoid::from_inverse_flattening(6378388.0, 297.0);
+/// let sphere = Ellipsoid::from_radii(6371000.0, 6371000.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Ellipsoid {
     // 赤道半径 (メートル)
     equatorial_radius: f64,
@@ -57,15 +179,41 @@ pub struct Ellipsoid {
     polar_radius: f64,
 }
 impl Ellipsoid {
+    /// Constructs an [`Ellipsoid`] from the equatorial and polar radii in meters.
+    pub const fn from_radii(equatorial_radius: f64, polar_radius: f64) -> Self {
+        Self {
+            equatorial_radius,
+            polar_radius,
+        }
+    }
+
+    /// Constructs an [`Ellipsoid`] from the equatorial radius in meters and the inverse flattening.
+    ///
+    /// 逆扁平率が無限大の場合は球となる。
+    pub const fn from_inverse_flattening(equatorial_radius: f64, inverse_flattening: f64) -> Self {
+        Self {
+            equatorial_radius,
+            polar_radius: equatorial_radius - equatorial_radius / inverse_flattening,
+        }
+    }
+
+    /// Constructs an [`Ellipsoid`] from the equatorial radius in meters and the squared eccentricity.
+    pub fn from_eccentricity_squared(equatorial_radius: f64, eccentricity_squared: f64) -> Self {
+        Self {
+            equatorial_radius,
+            polar_radius: equatorial_radius * (1. - eccentricity_squared).sqrt(),
+        }
+    }
+
     /// Converts a geodetic coordinate to [ECEF].
     pub fn to_ecef(&self, degree: LatLon) -> ECEF {
         let LatLon(lat, lon) = degree.map(f64::to_radians);
-        let geoid = self.equatorial_radius
-            / (1.0 - self.equatorial_eccentricity() * lat.sin().powi(2)).sqrt();
+        let geoid =
+            self.equatorial_radius / (1.0 - self.eccentricity_squared() * lat.sin().powi(2)).sqrt();
         ECEF::new(
             geoid * lat.cos() * lon.cos(),
             geoid * lat.cos() * lon.sin(),
-            geoid * (1.0 - self.equatorial_eccentricity()) * lat.sin(),
+            geoid * (1.0 - self.eccentricity_squared()) * lat.sin(),
         )
     }
 
@@ -73,44 +221,294 @@ impl Ellipsoid {
     pub fn to_geodetic(&self, ecef: ECEF) -> LatLon {
         let p = ecef.x.hypot(ecef.y);
         let theta = ((ecef.z * self.equatorial_radius) / (p * self.polar_radius)).atan();
-        let lat = (ecef.z + self.polar_eccentricity() * self.polar_radius * (theta.sin().powi(3)))
-            .atan2(
-                p - self.equatorial_eccentricity() * self.equatorial_radius * (theta.cos().powi(3)),
-            );
+        let lat = (ecef.z
+            + self.second_eccentricity_squared() * self.polar_radius * (theta.sin().powi(3)))
+        .atan2(p - self.eccentricity_squared() * self.equatorial_radius * (theta.cos().powi(3)));
         let lon = ecef.y.atan2(ecef.x);
         LatLon(lat, lon).map(f64::to_degrees)
     }
 
-    /// 赤道離心率 = (赤道半径^2 - 極半径^2) / 赤道半径^2
-    fn equatorial_eccentricity(&self) -> f64 {
+    /// Returns the equatorial radius (semi-major axis) in meters.
+    pub fn equatorial_radius(&self) -> f64 {
+        self.equatorial_radius
+    }
+
+    /// Returns the polar radius (semi-minor axis) in meters.
+    pub fn polar_radius(&self) -> f64 {
+        self.polar_radius
+    }
+
+    /// 扁平率 = (赤道半径 - 極半径) / 赤道半径
+    pub fn flattening(&self) -> f64 {
+        (self.equatorial_radius - self.polar_radius) / self.equatorial_radius
+    }
+
+    /// 逆扁平率 = 1 / 扁平率
+    ///
+    /// 球の場合は無限大となる。
+    pub fn inverse_flattening(&self) -> f64 {
+        self.flattening().recip()
+    }
+
+    /// 第三扁平率 = (赤道半径 - 極半径) / (赤道半径 + 極半径)
+    pub fn third_flattening(&self) -> f64 {
+        (self.equatorial_radius - self.polar_radius) / (self.equatorial_radius + self.polar_radius)
+    }
+
+    /// 第一離心率
+    pub fn eccentricity(&self) -> f64 {
+        self.eccentricity_squared().sqrt()
+    }
+
+    /// 第一離心率の2乗 = (赤道半径^2 - 極半径^2) / 赤道半径^2
+    pub fn eccentricity_squared(&self) -> f64 {
         let e2 = self.equatorial_radius.powi(2);
         let p2 = self.polar_radius.powi(2);
         (e2 - p2) / e2
     }
 
-    /// 極離心率 = (赤道半径^2 - 極半径^2) / 極半径^2
-    fn polar_eccentricity(&self) -> f64 {
+    /// 第二離心率の2乗 = (赤道半径^2 - 極半径^2) / 極半径^2
+    pub fn second_eccentricity_squared(&self) -> f64 {
         let e2 = self.equatorial_radius.powi(2);
         let p2 = self.polar_radius.powi(2);
         (e2 - p2) / p2
     }
+
+    /// Returns the arithmetic mean radius in meters.
+    ///
+    /// 平均半径 = (2 * 赤道半径 + 極半径) / 3
+    pub fn mean_radius(&self) -> f64 {
+        (2. * self.equatorial_radius + self.polar_radius) / 3.
+    }
+
+    /// Returns the radius of curvature in the meridian at latitude in degrees.
+    ///
+    /// 子午線曲率半径 M (メートル)。
+    pub fn meridian_radius(&self, lat: f64) -> f64 {
+        let w2 = self.w_squared(lat);
+        self.equatorial_radius * (1. - self.eccentricity_squared()) / (w2 * w2.sqrt())
+    }
+
+    /// Returns the radius of curvature in the prime vertical at latitude in degrees.
+    ///
+    /// 卯酉線曲率半径 N (メートル)。
+    pub fn prime_vertical_radius(&self, lat: f64) -> f64 {
+        self.equatorial_radius / self.w_squared(lat).sqrt()
+    }
+
+    /// Returns the length of the meridian arc from the equator to latitude in degrees.
+    ///
+    /// 赤道から緯度 `lat` までの子午線弧長 (メートル)。南緯では負の値となる。
+    /// 国土地理院の平面直角座標への換算式と同じく、第三扁平率の5次までの級数展開による。
+    pub fn meridian_arc_length(&self, lat: f64) -> f64 {
+        let n = self.third_flattening();
+        let [n2, n3, n4, n5] = [n.powi(2), n.powi(3), n.powi(4), n.powi(5)];
+        let coefficients = [
+            -3. / 2. * (n - n3 / 8. - n5 / 64.),
+            15. / 16. * (n2 - n4 / 4.),
+            -35. / 48. * (n3 - 5. / 16. * n5),
+            315. / 512. * n4,
+            -693. / 1280. * n5,
+        ];
+        let phi = lat.to_radians();
+        let series = coefficients
+            .iter()
+            .zip(1..)
+            .map(|(a, j)| a * (2. * j as f64 * phi).sin())
+            .sum::<f64>();
+        let a0 = 1. + n2 / 4. + n4 / 64.;
+        self.equatorial_radius / (1. + n) * (a0 * phi + series)
+    }
+
+    /// Returns the length of the meridian arc by numerical integration.
+    ///
+    /// [`Ellipsoid::meridian_arc_length`] の検証用。
+    /// 子午線曲率半径をガウス・ルジャンドル求積法で積分する。
+    pub fn meridian_arc_length_exact(&self, lat: f64) -> f64 {
+        // 8点のガウス・ルジャンドル求積の分点と重み
+        const NODES: [(f64, f64); 4] = [
+            (0.1834346424956498, 0.362683783378362),
+            (0.525532409916329, 0.3137066458778873),
+            (0.7966664774136267, 0.2223810344533745),
+            (0.9602898564975363, 0.1012285362903763),
+        ];
+        // 5度毎に分割する
+        let intervals = (lat.abs() / 5.).ceil().max(1.);
+        let h = lat / intervals;
+        let mut ret = 0.;
+        for i in 0..intervals as usize {
+            let mid = h * (i as f64 + 0.5);
+            for (x, w) in NODES {
+                let d = h / 2. * x;
+                ret += w * (self.meridian_radius(mid - d) + self.meridian_radius(mid + d));
+            }
+        }
+        ret * (h / 2.).to_radians()
+    }
+
+    /// Returns the latitude in degrees at which the meridian arc from the equator has the length.
+    ///
+    /// [`Ellipsoid::meridian_arc_length`] の逆。
+    /// 正距緯度からの級数展開を初期値として、ニュートン法で反復する。
+    pub fn meridian_arc_latitude(&self, length: f64) -> f64 {
+        let n = self.third_flattening();
+        let [n2, n3, n4] = [n.powi(2), n.powi(3), n.powi(4)];
+        let coefficients = [
+            3. / 2. * n - 27. / 32. * n3,
+            21. / 16. * n2 - 55. / 32. * n4,
+            151. / 96. * n3,
+            1097. / 512. * n4,
+        ];
+        // 正距緯度
+        let mu = length / self.rectifying_radius();
+        let series = coefficients
+            .iter()
+            .zip(1..)
+            .map(|(b, j)| b * (2. * j as f64 * mu).sin())
+            .sum::<f64>();
+        let mut ret = (mu + series).to_degrees();
+
+        for _ in 0..5 {
+            let d = (length - self.meridian_arc_length(ret)) / self.meridian_radius(ret);
+            ret += d.to_degrees();
+            if d.abs() < 1e-15 {
+                break;
+            }
+        }
+        ret
+    }
+
+    /// Returns the rectifying radius in meters.
+    ///
+    /// 子午線弧長を正距緯度 (ラジアン) で割った値。子午線の全長は 2π倍となる。
+    pub fn rectifying_radius(&self) -> f64 {
+        let n = self.third_flattening();
+        self.equatorial_radius / (1. + n) * (1. + n.powi(2) / 4. + n.powi(4) / 64.)
+    }
+
+    /// Returns the Gaussian mean radius of curvature at latitude in degrees.
+    ///
+    /// ガウスの平均曲率半径 = √(M N)
+    pub fn gaussian_radius(&self, lat: f64) -> f64 {
+        (self.meridian_radius(lat) * self.prime_vertical_radius(lat)).sqrt()
+    }
+
+    /// W^2 = 1 - e^2 sin^2(lat)
+    fn w_squared(&self, lat: f64) -> f64 {
+        1. - self.eccentricity_squared() * lat.to_radians().sin().powi(2)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use approx::assert_ulps_eq;
+    use approx::{assert_abs_diff_eq, assert_ulps_eq};
 
-    use super::{BESSEL, GRS80};
+    use super::{Ellipsoid, BESSEL, ECEF, GRS80, WGS84};
 
     #[test]
     fn grs80() {
-        assert_ulps_eq!(GRS80.equatorial_eccentricity(), 0.006694379990141124);
-        assert_ulps_eq!(GRS80.polar_eccentricity(), 0.006739496742276239);
+        assert_ulps_eq!(GRS80.eccentricity_squared(), 0.006694379990141124);
+        assert_ulps_eq!(GRS80.second_eccentricity_squared(), 0.006739496742276239);
     }
 
     #[test]
     fn bessel() {
-        assert_ulps_eq!(BESSEL.equatorial_eccentricity(), 0.006674372174974933);
-        assert_ulps_eq!(BESSEL.polar_eccentricity(), 0.006719218741581313);
+        assert_ulps_eq!(BESSEL.eccentricity_squared(), 0.006674372174974933);
+        assert_ulps_eq!(BESSEL.second_eccentricity_squared(), 0.006719218741581313);
+    }
+
+    #[test]
+    fn wgs84() {
+        assert_abs_diff_eq!(WGS84.polar_radius(), 6356752.314245, epsilon = 1e-6);
+        assert_abs_diff_eq!(WGS84.inverse_flattening(), 298.257223563, epsilon = 1e-9);
+        assert_abs_diff_eq!(WGS84.mean_radius(), 6371008.7714, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn constructors() {
+        let e2 = BESSEL.eccentricity_squared();
+        let ret = Ellipsoid::from_eccentricity_squared(BESSEL.equatorial_radius(), e2);
+        assert_abs_diff_eq!(ret.polar_radius(), BESSEL.polar_radius(), epsilon = 1e-6);
+
+        let sphere = Ellipsoid::from_inverse_flattening(6371000.0, f64::INFINITY);
+        assert_eq!(sphere, Ellipsoid::from_radii(6371000.0, 6371000.0));
+        assert_eq!(sphere.eccentricity(), 0.0);
+    }
+
+    #[test]
+    fn radii_of_curvature() {
+        // 赤道では M = a(1 - e^2), N = a
+        assert_ulps_eq!(
+            GRS80.meridian_radius(0.0),
+            6378137.0 * (1.0 - GRS80.eccentricity_squared())
+        );
+        assert_ulps_eq!(GRS80.prime_vertical_radius(0.0), 6378137.0);
+        // 極では M = N = a^2 / b
+        assert_ulps_eq!(
+            GRS80.meridian_radius(90.0),
+            GRS80.prime_vertical_radius(90.0)
+        );
+    }
+
+    #[test]
+    fn meridian_arc_length() {
+        assert_eq!(GRS80.meridian_arc_length(0.0), 0.0);
+        // 子午線象限
+        assert_abs_diff_eq!(
+            GRS80.meridian_arc_length(90.0),
+            10001965.7293,
+            epsilon = 1e-4
+        );
+        assert_eq!(
+            GRS80.meridian_arc_length(-35.0),
+            -GRS80.meridian_arc_length(35.0)
+        );
+    }
+
+    #[test]
+    fn meridian_arc_length_exact() {
+        for ellipsoid in [GRS80, BESSEL] {
+            for lat in [-89.0, -35.0, 0.0, 0.001, 24.0, 45.0, 90.0] {
+                assert_abs_diff_eq!(
+                    ellipsoid.meridian_arc_length_exact(lat),
+                    ellipsoid.meridian_arc_length(lat),
+                    epsilon = 1e-6
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn meridian_arc_latitude() {
+        for lat in [-89.0, -35.0, 0.0, 0.001, 24.0, 45.0, 90.0] {
+            let length = GRS80.meridian_arc_length(lat);
+            assert_abs_diff_eq!(GRS80.meridian_arc_latitude(length), lat, epsilon = 1e-12);
+        }
+        assert_abs_diff_eq!(
+            GRS80.rectifying_radius() * core::f64::consts::FRAC_PI_2,
+            GRS80.meridian_arc_length(90.0),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn ecef_ops() {
+        let a = ECEF::new(1.0, 2.0, 3.0);
+        let b = ECEF::from([0.5, 0.5, 0.5]);
+        assert_eq!(a + b, ECEF::new(1.5, 2.5, 3.5));
+        assert_eq!(a - b, ECEF::new(0.5, 1.5, 2.5));
+        assert_eq!(-a, ECEF::new(-1.0, -2.0, -3.0));
+        assert_eq!(a * 2.0, ECEF::new(2.0, 4.0, 6.0));
+        assert_eq!(a / 2.0, ECEF::new(0.5, 1.0, 1.5));
+        assert_eq!(<[f64; 3]>::from(a), [1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn ecef_round_trip() {
+        let degrees = crate::LatLon(35.0, 135.0);
+        let ecef = GRS80.to_ecef(degrees);
+        let ret = GRS80.to_geodetic(ecef);
+        assert_ulps_eq!(ret.lat(), degrees.lat(), max_ulps = 4);
+        assert_ulps_eq!(ret.lon(), degrees.lon(), max_ulps = 4);
     }
 }
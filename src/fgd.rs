@@ -0,0 +1,929 @@
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+use encoding_rs::{Encoding, UTF_8};
+use quick_xml::{
+    escape::escape,
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use crate::{DatumKind, LatLon};
+#[cfg(feature = "patchjgd")]
+use crate::{DegreesError, Jgd2000};
+
+/// Metadata of a dataset of 基盤地図情報.
+///
+/// `Dataset` 直下の `gml:description` と `gml:name`、および XML 宣言の文字コード。
+/// 基盤地図情報のダウンロードデータは Shift_JIS であることが多い。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FgdHeader {
+    description: Option<String>,
+    name: Option<String>,
+    encoding: &'static Encoding,
+}
+impl FgdHeader {
+    /// Constructs a [`FgdHeader`] in UTF-8.
+    pub fn new(description: Option<String>, name: Option<String>) -> Self {
+        Self {
+            description,
+            name,
+            encoding: UTF_8,
+        }
+    }
+
+    /// Returns the header with the encoding like [`encoding_rs::SHIFT_JIS`].
+    pub fn with_encoding(mut self, encoding: &'static Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Returns `gml:description`, if any.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Returns `gml:name`, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the encoding of the XML declaration.
+    pub fn encoding(&self) -> &'static Encoding {
+        self.encoding
+    }
+}
+impl Default for FgdHeader {
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
+/// A feature of 基盤地図情報 like `BldL` or `RdEdg`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FgdFeature {
+    kind: String,
+    id: String,
+    properties: Vec<(String, FgdValue)>,
+}
+impl FgdFeature {
+    /// Constructs a [`FgdFeature`] of the element name, `gml:id` and properties in order.
+    pub fn new(
+        kind: impl Into<String>,
+        id: impl Into<String>,
+        properties: Vec<(String, FgdValue)>,
+    ) -> Self {
+        Self {
+            kind: kind.into(),
+            id: id.into(),
+            properties,
+        }
+    }
+
+    /// Returns the element name like `BldL`.
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Returns `gml:id`.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the properties in order like `fid`, `lfSpanFr` and `loc`.
+    pub fn properties(&self) -> &[(String, FgdValue)] {
+        &self.properties
+    }
+
+    /// Returns the first property of the name.
+    pub fn get(&self, name: &str) -> Option<&FgdValue> {
+        self.properties
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns an iterator over the geometries.
+    pub fn geometries(&self) -> impl Iterator<Item = &FgdGeometry> {
+        self.properties.iter().filter_map(|(_, value)| match value {
+            FgdValue::Geometry(geometry) => Some(geometry),
+            _ => None,
+        })
+    }
+
+    /// Transforms all geometries in JGD2000 to JGD2011 by [`Jgd2000::to_jgd2011`].
+    ///
+    /// JGD2011 のジオメトリはそのまま残す。
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DegreesError`] if a position is out of range in degrees.
+    #[cfg(feature = "patchjgd")]
+    pub fn to_jgd2011(&self) -> Result<Self, DegreesError> {
+        let mut ret = self.clone();
+        for (_, value) in &mut ret.properties {
+            let FgdValue::Geometry(geometry) = value else {
+                continue;
+            };
+            if geometry.datum != DatumKind::Jgd2000 {
+                continue;
+            }
+            geometry.shape = geometry
+                .shape
+                .try_map(|degrees| Ok(Jgd2000::new(degrees)?.to_jgd2011().degrees()))?;
+            geometry.datum = DatumKind::Jgd2011;
+        }
+        Ok(ret)
+    }
+}
+
+/// Value of a property of [`FgdFeature`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FgdValue {
+    /// Text like `<type>普通建物</type>`.
+    Text(String),
+    /// `gml:id` and `gml:timePosition` like `<lfSpanFr gml:id="K3_1-1"><gml:timePosition>2008-03-01</gml:timePosition></lfSpanFr>`.
+    Time(String, String),
+    /// `gml:Point`, `gml:Curve` or `gml:Surface`.
+    Geometry(FgdGeometry),
+}
+
+/// Geometry of [`FgdFeature`] in degrees.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FgdGeometry {
+    id: String,
+    datum: DatumKind,
+    shape: FgdShape,
+}
+impl FgdGeometry {
+    /// Constructs a [`FgdGeometry`] of `gml:id`, the datum and the shape.
+    pub fn new(id: impl Into<String>, datum: DatumKind, shape: FgdShape) -> Self {
+        Self {
+            id: id.into(),
+            datum,
+            shape,
+        }
+    }
+
+    /// Returns `gml:id`.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the datum of `srsName`.
+    pub fn datum(&self) -> DatumKind {
+        self.datum
+    }
+
+    /// Returns the shape.
+    pub fn shape(&self) -> &FgdShape {
+        &self.shape
+    }
+}
+
+/// Shape of [`FgdGeometry`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FgdShape {
+    /// `gml:Point`
+    Point(LatLon),
+    /// `gml:Curve`
+    Curve(Vec<LatLon>),
+    /// `gml:Surface` with the exterior and interior rings.
+    Surface(Vec<Vec<LatLon>>),
+}
+impl FgdShape {
+    #[cfg(feature = "patchjgd")]
+    fn try_map(
+        &self,
+        mut f: impl FnMut(LatLon) -> Result<LatLon, DegreesError>,
+    ) -> Result<Self, DegreesError> {
+        let mut line = |line: &[LatLon]| line.iter().map(|&p| f(p)).collect::<Result<Vec<_>, _>>();
+        Ok(match self {
+            FgdShape::Point(p) => FgdShape::Point(line(&[*p])?[0]),
+            FgdShape::Curve(curve) => FgdShape::Curve(line(curve)?),
+            FgdShape::Surface(rings) => {
+                FgdShape::Surface(rings.iter().map(|r| line(r)).collect::<Result<_, _>>()?)
+            }
+        })
+    }
+}
+
+/// Streaming reader of GML of 基盤地図情報, iterating over the features.
+///
+/// 国土地理院の基盤地図情報 (JPGIS 準拠の GML) を読み、`Dataset` 直下の地物を1つずつ返す。
+/// 座標は `srsName` が `fguuid:jgd2000.bl` または `fguuid:jgd2011.bl` の緯度、経度の順とする。
+/// 数値標高モデルなど、上記以外の形式には対応しない。
+/// 文字コードは XML 宣言に従い、[`FgdHeader::encoding`] で得られる。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{FgdReader, FgdWriter};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+/// <Dataset xmlns="http://fgd.gsi.go.jp/spec/2008/FGD_GMLSchema" xmlns:gml="http://www.opengis.net/gml/3.2" gml:id="Dataset1">
+/// <GCP gml:id="K1_1">
+/// <type>電子基準点</type>
+/// <pos><gml:Point gml:id="K1_1-g" srsName="fguuid:jgd2000.bl"><gml:pos>35.0 135.0</gml:pos></gml:Point></pos>
+/// </GCP>
+/// </Dataset>"#;
+/// let mut reader = FgdReader::new(input.as_bytes())?;
+/// let mut writer = FgdWriter::new(vec![], reader.header())?;
+/// for feature in &mut reader {
+///     writer.write(&feature?.to_jgd2011()?)?;
+/// }
+/// let output = writer.finish()?;
+/// #   Ok(())
+/// # }
+/// ```
+pub struct FgdReader<R> {
+    reader: Reader<R>,
+    buf: Vec<u8>,
+    header: FgdHeader,
+    pending: Option<BytesStart<'static>>,
+    done: bool,
+}
+impl<R: BufRead> FgdReader<R> {
+    /// Constructs a [`FgdReader`], reading up to the first feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FgdError`] if the input does not start with a `Dataset`.
+    pub fn new(reader: R) -> Result<Self, FgdError> {
+        let mut ret = Self {
+            reader: Reader::from_reader(reader),
+            buf: vec![],
+            header: FgdHeader::default(),
+            pending: None,
+            done: false,
+        };
+        match ret.event()? {
+            Event::Start(e) if local(&e) == "Dataset" => {
+                ret.header.encoding = ret.reader.decoder().encoding();
+            }
+            Event::Eof => return Err(ret.error(ErrorKind::Missing("Dataset"))),
+            e => return Err(ret.unexpected(&e)),
+        }
+        loop {
+            match ret.event()? {
+                Event::Start(e) if e.name().prefix().is_some_and(|p| p.as_ref() == b"gml") => {
+                    match local(&e).as_str() {
+                        "description" => ret.header.description = Some(ret.text()?),
+                        "name" => ret.header.name = Some(ret.text()?),
+                        // gml:boundedBy など
+                        _ => ret.skip_element()?,
+                    }
+                }
+                Event::Start(e) => {
+                    ret.pending = Some(e);
+                    break;
+                }
+                Event::Empty(_) => {}
+                Event::End(_) => {
+                    ret.done = true;
+                    break;
+                }
+                e => return Err(ret.unexpected(&e)),
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Returns the metadata of the dataset.
+    pub fn header(&self) -> &FgdHeader {
+        &self.header
+    }
+
+    fn error(&self, kind: ErrorKind) -> FgdError {
+        FgdError {
+            offset: self.reader.buffer_position(),
+            kind,
+        }
+    }
+
+    fn unexpected(&self, event: &Event) -> FgdError {
+        let name = match event {
+            Event::Start(e) | Event::Empty(e) => format!("<{}>", local(e)),
+            Event::End(e) => format!("</{}>", String::from_utf8_lossy(e.local_name().as_ref())),
+            Event::Text(_) => "text".to_owned(),
+            _ => "end of file".to_owned(),
+        };
+        self.error(ErrorKind::Unexpected(name))
+    }
+
+    /// Reads the next event except whitespace, comments and declarations.
+    fn event(&mut self) -> Result<Event<'static>, FgdError> {
+        loop {
+            self.buf.clear();
+            let event = self
+                .reader
+                .read_event_into(&mut self.buf)
+                .map_err(|e| FgdError {
+                    offset: self.reader.buffer_position(),
+                    kind: ErrorKind::Xml(e),
+                })?
+                .into_owned();
+            match &event {
+                Event::Text(text) if text.iter().all(u8::is_ascii_whitespace) => {}
+                Event::Start(_) | Event::Empty(_) | Event::End(_) | Event::Text(_) | Event::Eof => {
+                    return Ok(event)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads text up to the end of the current element.
+    fn text(&mut self) -> Result<String, FgdError> {
+        let mut ret = String::new();
+        loop {
+            match self.event()? {
+                Event::Text(text) => {
+                    let text = text.unescape().map_err(|e| self.error(ErrorKind::Xml(e)))?;
+                    ret.push_str(&text);
+                }
+                Event::End(_) => return Ok(ret),
+                e => return Err(self.unexpected(&e)),
+            }
+        }
+    }
+
+    /// Reads the end of the current element.
+    fn end(&mut self) -> Result<(), FgdError> {
+        match self.event()? {
+            Event::End(_) => Ok(()),
+            e => Err(self.unexpected(&e)),
+        }
+    }
+
+    /// Skips the rest of the current element.
+    fn skip_element(&mut self) -> Result<(), FgdError> {
+        let mut depth = 0;
+        loop {
+            match self.event()? {
+                Event::Start(_) => depth += 1,
+                Event::End(_) if depth == 0 => return Ok(()),
+                Event::End(_) => depth -= 1,
+                Event::Eof => return Err(self.unexpected(&Event::Eof)),
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the value of the attribute by the local name.
+    fn attribute(&self, e: &BytesStart, name: &str) -> Option<String> {
+        e.attributes()
+            .flatten()
+            .find(|a| a.key.local_name().as_ref() == name.as_bytes())
+            .and_then(|a| a.decode_and_unescape_value(self.reader.decoder()).ok())
+            .map(|v| v.into_owned())
+    }
+
+    fn next_feature(&mut self) -> Result<Option<FgdFeature>, FgdError> {
+        let start = match self.pending.take() {
+            Some(start) => start,
+            None => loop {
+                match self.event()? {
+                    Event::Start(e) => break e,
+                    Event::Empty(_) => {}
+                    Event::End(_) => return Ok(None),
+                    e => return Err(self.unexpected(&e)),
+                }
+            },
+        };
+        let mut properties = vec![];
+        loop {
+            match self.event()? {
+                Event::Start(e) => {
+                    let value = self.value(&e)?;
+                    properties.push((local(&e), value));
+                }
+                Event::Empty(e) => properties.push((local(&e), FgdValue::Text(String::new()))),
+                Event::End(_) => break,
+                e => return Err(self.unexpected(&e)),
+            }
+        }
+        Ok(Some(FgdFeature {
+            kind: local(&start),
+            id: self.attribute(&start, "id").unwrap_or_default(),
+            properties,
+        }))
+    }
+
+    fn value(&mut self, property: &BytesStart) -> Result<FgdValue, FgdError> {
+        let child = match self.event()? {
+            Event::Text(text) => {
+                let text = text.unescape().map_err(|e| self.error(ErrorKind::Xml(e)))?;
+                let text = text.into_owned();
+                self.end()?;
+                return Ok(FgdValue::Text(text));
+            }
+            Event::End(_) => return Ok(FgdValue::Text(String::new())),
+            Event::Start(child) => child,
+            e => return Err(self.unexpected(&e)),
+        };
+        let value = match local(&child).as_str() {
+            "timePosition" => {
+                let id = self.attribute(property, "id").unwrap_or_default();
+                FgdValue::Time(id, self.text()?)
+            }
+            "Point" | "Curve" | "Surface" => FgdValue::Geometry(self.geometry(&child)?),
+            _ => return Err(self.unexpected(&Event::Start(child))),
+        };
+        self.end()?;
+        Ok(value)
+    }
+
+    fn geometry(&mut self, start: &BytesStart) -> Result<FgdGeometry, FgdError> {
+        let srs = self
+            .attribute(start, "srsName")
+            .ok_or(self.error(ErrorKind::Missing("srsName")))?;
+        let datum = match srs.as_str() {
+            "fguuid:jgd2000.bl" => DatumKind::Jgd2000,
+            "fguuid:jgd2011.bl" => DatumKind::Jgd2011,
+            _ => return Err(self.error(ErrorKind::Srs(srs))),
+        };
+        let mut rings: Vec<Vec<LatLon>> = vec![];
+        let mut depth = 0;
+        let mut in_pos = false;
+        loop {
+            match self.event()? {
+                Event::Start(e) => {
+                    depth += 1;
+                    match local(&e).as_str() {
+                        "exterior" | "interior" => rings.push(vec![]),
+                        "pos" | "posList" => in_pos = true,
+                        _ => {}
+                    }
+                }
+                Event::End(_) if depth == 0 => break,
+                Event::End(_) => {
+                    depth -= 1;
+                    in_pos = false;
+                }
+                Event::Text(text) if in_pos => {
+                    let text = text.unescape().map_err(|e| self.error(ErrorKind::Xml(e)))?;
+                    let positions = parse_positions(&text).map_err(|kind| self.error(kind))?;
+                    if rings.is_empty() {
+                        rings.push(vec![]);
+                    }
+                    rings.last_mut().unwrap().extend(positions);
+                }
+                Event::Empty(_) => {}
+                e => return Err(self.unexpected(&e)),
+            }
+        }
+        let shape = match local(start).as_str() {
+            "Point" => match rings.concat()[..] {
+                [p] => FgdShape::Point(p),
+                _ => return Err(self.error(ErrorKind::Missing("gml:pos"))),
+            },
+            "Curve" => FgdShape::Curve(rings.concat()),
+            _ => FgdShape::Surface(rings),
+        };
+        Ok(FgdGeometry {
+            id: self.attribute(start, "id").unwrap_or_default(),
+            datum,
+            shape,
+        })
+    }
+}
+impl<R: BufRead> Iterator for FgdReader<R> {
+    type Item = Result<FgdFeature, FgdError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let ret = self.next_feature().transpose();
+        if !matches!(ret, Some(Ok(_))) {
+            self.done = true;
+        }
+        ret
+    }
+}
+
+fn local(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).into_owned()
+}
+
+/// Parses `lat lon lat lon ...`.
+fn parse_positions(s: &str) -> Result<Vec<LatLon>, ErrorKind> {
+    let numbers = s
+        .split_whitespace()
+        .map(|n| n.parse().map_err(|_| ErrorKind::Number(n.to_owned())))
+        .collect::<Result<Vec<f64>, _>>()?;
+    if numbers.len() % 2 != 0 {
+        return Err(ErrorKind::Number(s.trim().to_owned()));
+    }
+    Ok(numbers.chunks(2).map(|c| LatLon(c[0], c[1])).collect())
+}
+
+/// Writer of GML of 基盤地図情報.
+///
+/// [`FgdReader`] と同じスキーマで出力し、`srsName` はジオメトリの測地系に合わせる。
+/// 座標は小数点以下 9桁とする。
+/// 面を構成する `gml:Curve` の `gml:id` は、面の `gml:id` に連番を付けたものとする。
+/// 文字コードは [`FgdHeader::encoding`] とし、表せない文字は数値文字参照とする。
+pub struct FgdWriter<W: Write> {
+    writer: W,
+    encoding: &'static Encoding,
+    buf: Vec<u8>,
+}
+impl<W: Write> FgdWriter<W> {
+    /// Constructs a [`FgdWriter`], writing up to the header.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::Error`] if failed to write.
+    pub fn new(writer: W, header: &FgdHeader) -> io::Result<Self> {
+        let encoding = header.encoding.output_encoding();
+        let mut ret = Self {
+            writer,
+            encoding,
+            buf: vec![],
+        };
+        let writer = &mut ret.buf;
+        writeln!(
+            writer,
+            r#"<?xml version="1.0" encoding="{}"?>"#,
+            encoding.name()
+        )?;
+        writeln!(
+            writer,
+            r#"<Dataset xsi:schemaLocation="http://fgd.gsi.go.jp/spec/2008/FGD_GMLSchema FGD_GMLSchema.xsd" xmlns="http://fgd.gsi.go.jp/spec/2008/FGD_GMLSchema" xmlns:gml="http://www.opengis.net/gml/3.2" xmlns:xlink="http://www.w3.org/1999/xlink" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" gml:id="Dataset1">"#
+        )?;
+        if let Some(description) = &header.description {
+            writeln!(
+                writer,
+                "<gml:description>{}</gml:description>",
+                escape(description)
+            )?;
+        }
+        if let Some(name) = &header.name {
+            writeln!(writer, "<gml:name>{}</gml:name>", escape(name))?;
+        }
+        ret.emit()?;
+        Ok(ret)
+    }
+
+    /// Writes the buffer in the encoding.
+    fn emit(&mut self) -> io::Result<()> {
+        // buf は書式化した文字列のみから成る
+        let text = String::from_utf8_lossy(&self.buf);
+        let (bytes, _, _) = self.encoding.encode(&text);
+        self.writer.write_all(&bytes)?;
+        self.buf.clear();
+        Ok(())
+    }
+
+    /// Writes a feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::Error`] if failed to write, or with [`io::ErrorKind::InvalidInput`]
+    /// if a geometry is neither in JGD2000 nor JGD2011.
+    pub fn write(&mut self, feature: &FgdFeature) -> io::Result<()> {
+        self.buf.clear();
+        let w = &mut self.buf;
+        writeln!(w, r#"<{} gml:id="{}">"#, feature.kind, escape(&feature.id))?;
+        for (name, value) in &feature.properties {
+            match value {
+                FgdValue::Text(text) => writeln!(w, "<{}>{}</{}>", name, escape(text), name)?,
+                FgdValue::Time(id, position) => {
+                    let id = if id.is_empty() {
+                        String::new()
+                    } else {
+                        format!(r#" gml:id="{}""#, escape(id))
+                    };
+                    writeln!(
+                        w,
+                        "<{}{}><gml:timePosition>{}</gml:timePosition></{}>",
+                        name,
+                        id,
+                        escape(position),
+                        name
+                    )?;
+                }
+                FgdValue::Geometry(geometry) => {
+                    writeln!(w, "<{}>", name)?;
+                    geometry_gml(w, geometry)?;
+                    writeln!(w, "</{}>", name)?;
+                }
+            }
+        }
+        writeln!(w, "</{}>", feature.kind)?;
+        self.emit()
+    }
+
+    /// Writes the end of the dataset and returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::Error`] if failed to write.
+    pub fn finish(mut self) -> io::Result<W> {
+        writeln!(self.buf, "</Dataset>")?;
+        self.emit()?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+fn geometry_gml(w: &mut Vec<u8>, geometry: &FgdGeometry) -> io::Result<()> {
+    let srs = match geometry.datum {
+        DatumKind::Jgd2000 => "fguuid:jgd2000.bl",
+        DatumKind::Jgd2011 => "fguuid:jgd2011.bl",
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "geometry must be in JGD2000 or JGD2011",
+            ))
+        }
+    };
+    let id = escape(&geometry.id);
+    let pos_list = |w: &mut Vec<u8>, positions: &[LatLon]| -> io::Result<()> {
+        writeln!(w, "<gml:segments>\n<gml:LineStringSegment>\n<gml:posList>")?;
+        for LatLon(lat, lon) in positions {
+            writeln!(w, "{:.9} {:.9}", lat, lon)?;
+        }
+        writeln!(
+            w,
+            "</gml:posList>\n</gml:LineStringSegment>\n</gml:segments>"
+        )
+    };
+    match &geometry.shape {
+        FgdShape::Point(LatLon(lat, lon)) => writeln!(
+            w,
+            r#"<gml:Point gml:id="{}" srsName="{}"><gml:pos>{:.9} {:.9}</gml:pos></gml:Point>"#,
+            id, srs, lat, lon
+        ),
+        FgdShape::Curve(curve) => {
+            writeln!(w, r#"<gml:Curve gml:id="{}" srsName="{}">"#, id, srs)?;
+            pos_list(w, curve)?;
+            writeln!(w, "</gml:Curve>")
+        }
+        FgdShape::Surface(rings) => {
+            writeln!(w, r#"<gml:Surface gml:id="{}" srsName="{}">"#, id, srs)?;
+            writeln!(w, "<gml:patches>\n<gml:PolygonPatch>")?;
+            for (i, ring) in rings.iter().enumerate() {
+                let boundary = if i == 0 { "exterior" } else { "interior" };
+                writeln!(w, "<gml:{}>\n<gml:Ring>\n<gml:curveMember>", boundary)?;
+                writeln!(w, r#"<gml:Curve gml:id="{}-{}">"#, id, i)?;
+                pos_list(w, ring)?;
+                writeln!(w, "</gml:Curve>")?;
+                writeln!(w, "</gml:curveMember>\n</gml:Ring>\n</gml:{}>", boundary)?;
+            }
+            writeln!(w, "</gml:PolygonPatch>\n</gml:patches>\n</gml:Surface>")
+        }
+    }
+}
+
+/// Errors in reading GML of 基盤地図情報.
+#[derive(Debug)]
+pub struct FgdError {
+    offset: u64,
+    kind: ErrorKind,
+}
+impl FgdError {
+    /// Returns the byte offset in the input where the error occurred.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+impl fmt::Display for FgdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "byte {}: ", self.offset)?;
+        match &self.kind {
+            ErrorKind::Xml(e) => write!(f, "{}", e),
+            ErrorKind::Missing(name) => write!(f, "missing {}", name),
+            ErrorKind::Unexpected(name) => write!(f, "unexpected {}", name),
+            ErrorKind::Srs(srs) => write!(f, "unsupported srsName: {}", srs),
+            ErrorKind::Number(s) => write!(f, "invalid coordinates: {:?}", s),
+        }
+    }
+}
+impl std::error::Error for FgdError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Xml(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum ErrorKind {
+    Xml(quick_xml::Error),
+    Missing(&'static str),
+    Unexpected(String),
+    Srs(String),
+    Number(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::SHIFT_JIS;
+
+    use crate::{DatumKind, LatLon};
+
+    use super::{FgdFeature, FgdGeometry, FgdHeader, FgdReader, FgdShape, FgdValue, FgdWriter};
+
+    const INPUT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Dataset xsi:schemaLocation="http://fgd.gsi.go.jp/spec/2008/FGD_GMLSchema FGD_GMLSchema.xsd" xmlns="http://fgd.gsi.go.jp/spec/2008/FGD_GMLSchema" xmlns:gml="http://www.opengis.net/gml/3.2" xmlns:xlink="http://www.w3.org/1999/xlink" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" gml:id="Dataset1">
+<gml:description>基盤地図情報メタデータ ID=fmdid:15-3101</gml:description>
+<gml:name>基盤地図情報ダウンロードデータ（GML版）</gml:name>
+<BldL gml:id="K3_1">
+<fid>fgoid:10-00100-2-1</fid>
+<lfSpanFr gml:id="K3_1-1"><gml:timePosition>2008-03-01</gml:timePosition></lfSpanFr>
+<orgGILvl>2500</orgGILvl>
+<loc>
+<gml:Curve gml:id="K3_1-g" srsName="fguuid:jgd2000.bl">
+<gml:segments>
+<gml:LineStringSegment>
+<gml:posList>
+35.000000000 135.000000000
+35.000100000 135.000100000
+</gml:posList>
+</gml:LineStringSegment>
+</gml:segments>
+</gml:Curve>
+</loc>
+<type>普通建物 &amp; 堅ろう</type>
+</BldL>
+<GCP gml:id="K4_1">
+<pos><gml:Point gml:id="K4_1-g" srsName="fguuid:jgd2011.bl"><gml:pos>38.25 140.875</gml:pos></gml:Point></pos>
+</GCP>
+<BldA gml:id="K5_1">
+<area>
+<gml:Surface gml:id="K5_1-g" srsName="fguuid:jgd2000.bl">
+<gml:patches>
+<gml:PolygonPatch>
+<gml:exterior><gml:Ring><gml:curveMember><gml:Curve gml:id="K5_1-g-0"><gml:segments><gml:LineStringSegment><gml:posList>36.0 140.0 36.0 140.1 36.1 140.1 36.0 140.0</gml:posList></gml:LineStringSegment></gml:segments></gml:Curve></gml:curveMember></gml:Ring></gml:exterior>
+<gml:interior><gml:Ring><gml:curveMember><gml:Curve gml:id="K5_1-g-1"><gml:segments><gml:LineStringSegment><gml:posList>36.02 140.05 36.03 140.06 36.02 140.06 36.02 140.05</gml:posList></gml:LineStringSegment></gml:segments></gml:Curve></gml:curveMember></gml:Ring></gml:interior>
+</gml:PolygonPatch>
+</gml:patches>
+</gml:Surface>
+</area>
+</BldA>
+</Dataset>
+"#;
+
+    fn read(input: &str) -> Result<Vec<FgdFeature>, String> {
+        let reader = FgdReader::new(input.as_bytes()).map_err(|e| e.to_string())?;
+        reader.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn read_features() {
+        let reader = FgdReader::new(INPUT.as_bytes()).unwrap();
+        assert_eq!(
+            reader.header().description(),
+            Some("基盤地図情報メタデータ ID=fmdid:15-3101")
+        );
+        let features: Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+        assert_eq!(features.len(), 3);
+
+        let building = &features[0];
+        assert_eq!((building.kind(), building.id()), ("BldL", "K3_1"));
+        assert_eq!(
+            building.get("lfSpanFr"),
+            Some(&FgdValue::Time("K3_1-1".into(), "2008-03-01".into()))
+        );
+        assert_eq!(
+            building.get("type"),
+            Some(&FgdValue::Text("普通建物 & 堅ろう".into()))
+        );
+        let geometry = building.geometries().next().unwrap();
+        assert_eq!(geometry.datum(), DatumKind::Jgd2000);
+        assert_eq!(
+            *geometry.shape(),
+            FgdShape::Curve(vec![LatLon(35., 135.), LatLon(35.0001, 135.0001)])
+        );
+
+        assert_eq!(
+            features[1].get("pos"),
+            Some(&FgdValue::Geometry(FgdGeometry::new(
+                "K4_1-g",
+                DatumKind::Jgd2011,
+                FgdShape::Point(LatLon(38.25, 140.875))
+            )))
+        );
+        let FgdShape::Surface(rings) = features[2].geometries().next().unwrap().shape() else {
+            panic!()
+        };
+        assert_eq!(rings.len(), 2);
+        assert_eq!(rings[1][0], LatLon(36.02, 140.05));
+    }
+
+    #[test]
+    fn write_round_trip() {
+        let mut reader = FgdReader::new(INPUT.as_bytes()).unwrap();
+        let mut writer = FgdWriter::new(vec![], reader.header()).unwrap();
+        let features: Vec<_> = (&mut reader).collect::<Result<_, _>>().unwrap();
+        for feature in &features {
+            writer.write(feature).unwrap();
+        }
+        let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(read(&output).unwrap(), features);
+        assert!(output.contains(r#"<lfSpanFr gml:id="K3_1-1"><gml:timePosition>2008-03-01</gml:timePosition></lfSpanFr>"#));
+        assert!(output.contains("<type>普通建物 &amp; 堅ろう</type>"));
+
+        let header = FgdHeader::new(None, None);
+        let output = FgdWriter::new(vec![], &header).unwrap().finish().unwrap();
+        assert_eq!(read(std::str::from_utf8(&output).unwrap()).unwrap(), []);
+    }
+
+    #[test]
+    fn shift_jis() {
+        let text = INPUT.replace(r#"encoding="UTF-8""#, r#"encoding="Shift_JIS""#);
+        let (input, _, _) = SHIFT_JIS.encode(&text);
+        let mut reader = FgdReader::new(&input[..]).unwrap();
+        assert_eq!(reader.header().encoding(), SHIFT_JIS);
+        assert_eq!(
+            reader.header().name(),
+            Some("基盤地図情報ダウンロードデータ（GML版）")
+        );
+        let header = reader.header().clone();
+        let features: Vec<_> = (&mut reader).collect::<Result<_, _>>().unwrap();
+        assert_eq!(
+            features[0].get("type"),
+            Some(&FgdValue::Text("普通建物 & 堅ろう".into()))
+        );
+
+        let mut writer = FgdWriter::new(vec![], &header).unwrap();
+        for feature in &features {
+            writer.write(feature).unwrap();
+        }
+        let output = writer.finish().unwrap();
+        assert!(output.starts_with(br#"<?xml version="1.0" encoding="Shift_JIS"?>"#));
+        let (expected, _, _) = SHIFT_JIS.encode("<type>普通建物 &amp; 堅ろう</type>");
+        assert!(output.windows(expected.len()).any(|w| w == &expected[..]));
+        let reader = FgdReader::new(&output[..]).unwrap();
+        assert_eq!(reader.collect::<Result<Vec<_>, _>>().unwrap(), features);
+
+        // Shift_JIS にない文字は数値文字参照
+        let feature = FgdFeature::new(
+            "Cstline",
+            "K6_1",
+            vec![("name".into(), FgdValue::Text("𩸽".into()))],
+        );
+        let mut writer = FgdWriter::new(vec![], &header).unwrap();
+        writer.write(&feature).unwrap();
+        let output = writer.finish().unwrap();
+        let reader = FgdReader::new(&output[..]).unwrap();
+        assert_eq!(reader.collect::<Result<Vec<_>, _>>().unwrap(), [feature]);
+    }
+
+    #[cfg(feature = "patchjgd")]
+    #[test]
+    fn to_jgd2011() {
+        use crate::Jgd2000;
+
+        let features = read(INPUT).unwrap();
+        let building = features[0].to_jgd2011().unwrap();
+        let geometry = building.geometries().next().unwrap();
+        assert_eq!(geometry.datum(), DatumKind::Jgd2011);
+        let expected = Jgd2000::new(LatLon(35., 135.))
+            .unwrap()
+            .to_jgd2011()
+            .degrees();
+        let FgdShape::Curve(curve) = geometry.shape() else {
+            panic!()
+        };
+        assert_eq!(curve[0], expected);
+        assert_eq!(building.get("fid"), features[0].get("fid"));
+
+        // JGD2011 はそのまま
+        assert_eq!(features[1].to_jgd2011().unwrap(), features[1]);
+
+        let mut writer = FgdWriter::new(vec![], &FgdHeader::default()).unwrap();
+        writer.write(&building).unwrap();
+        let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert!(output.contains(r#"srsName="fguuid:jgd2011.bl""#));
+        assert!(!output.contains("jgd2000"));
+    }
+
+    #[test]
+    fn errors() {
+        let tokyo = INPUT.replace("fguuid:jgd2011.bl", "fguuid:tokyo.bl");
+        let err = read(&tokyo).unwrap_err();
+        assert!(
+            err.ends_with("unsupported srsName: fguuid:tokyo.bl"),
+            "{}",
+            err
+        );
+
+        let odd = INPUT.replace("38.25 140.875", "38.25");
+        let err = read(&odd).unwrap_err();
+        assert!(err.ends_with(r#"invalid coordinates: "38.25""#), "{}", err);
+
+        let err = read("<Other/>").unwrap_err();
+        assert!(err.ends_with("unexpected <Other>"), "{}", err);
+
+        let err = read(&INPUT.replace("</Dataset>", "")).unwrap_err();
+        assert!(err.ends_with("unexpected end of file"), "{}", err);
+    }
+}
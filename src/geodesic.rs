@@ -0,0 +1,1242 @@
+use core::f64::consts::{FRAC_1_SQRT_2, PI};
+
+use crate::{Ellipsoid, LatLon};
+
+#[cfg(not(any(feature = "std", test)))]
+use crate::math::Float;
+
+/// A geodesic, the shortest path between two points on an [`Ellipsoid`].
+///
+/// 測地線。[`Ellipsoid::geodesic_inverse`] または [`Ellipsoid::geodesic_direct`] によって求められる。
+///
+/// 方位角は真北を 0 度として時計回りに 0 以上 360 未満の度で表す。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{LatLon, GRS80};
+///
+/// let geodesic = GRS80.geodesic_inverse(LatLon(35.0, 135.0), LatLon(36.0, 136.0));
+/// let meters = geodesic.distance();
+/// let degrees = geodesic.forward_azimuth();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geodesic {
+    from: LatLon,
+    to: LatLon,
+    distance: f64,
+    azimuth1: f64,
+    azimuth2: f64,
+}
+impl Geodesic {
+    /// Returns the start point in degrees.
+    pub fn from(&self) -> LatLon {
+        self.from
+    }
+
+    /// Returns the end point in degrees.
+    pub fn to(&self) -> LatLon {
+        self.to
+    }
+
+    /// Returns the length in meters.
+    pub fn distance(&self) -> f64 {
+        self.distance
+    }
+
+    /// Returns the azimuth at the start point toward the end point in degrees.
+    ///
+    /// 始点から終点への方位角。
+    pub fn forward_azimuth(&self) -> f64 {
+        normalize_azimuth(self.azimuth1)
+    }
+
+    /// Returns the azimuth at the end point toward the start point in degrees.
+    ///
+    /// 終点から始点への方位角。
+    pub fn back_azimuth(&self) -> f64 {
+        normalize_azimuth(self.azimuth2 + 180.)
+    }
+}
+
+fn normalize_azimuth(degrees: f64) -> f64 {
+    let ret = degrees.rem_euclid(360.);
+    // rem_euclid may round up to 360
+    if ret >= 360. {
+        0.
+    } else {
+        ret + 0.
+    }
+}
+
+impl Ellipsoid {
+    /// Solves the inverse geodesic problem.
+    ///
+    /// 2点間の測地線の長さと方位角を求める (測地線の逆問題)。
+    /// Karney (2013) の方法により、地球上のどの2点でもナノメートル程度の精度で計算される。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jgd::{LatLon, GRS80};
+    ///
+    /// let geodesic = GRS80.geodesic_inverse(LatLon(35.0, 135.0), LatLon(36.0, 136.0));
+    /// let meters = geodesic.distance();
+    /// ```
+    pub fn geodesic_inverse(&self, from: LatLon, to: LatLon) -> Geodesic {
+        let inverse = Karney::new(self).inverse(from, to);
+        Geodesic {
+            from,
+            to,
+            distance: inverse.s12,
+            azimuth1: atan2d(inverse.salp1, inverse.calp1),
+            azimuth2: atan2d(inverse.salp2, inverse.calp2),
+        }
+    }
+
+    /// Solves the direct geodesic problem.
+    ///
+    /// 始点と方位角 (度) と距離 (メートル) から、終点を求める (測地線の順問題)。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jgd::{LatLon, GRS80};
+    ///
+    /// let geodesic = GRS80.geodesic_direct(LatLon(35.0, 135.0), 45.0, 1000.0);
+    /// let LatLon(lat, lon) = geodesic.to();
+    /// ```
+    pub fn geodesic_direct(&self, from: LatLon, azimuth: f64, distance: f64) -> Geodesic {
+        let (to, azimuth2) = Karney::new(self).direct(from, azimuth, distance);
+        Geodesic {
+            from,
+            to,
+            distance,
+            azimuth1: azimuth,
+            azimuth2,
+        }
+    }
+}
+
+// Order of the series expansions.
+const N_A1: usize = 6;
+const N_C1: usize = 6;
+const N_C1P: usize = 6;
+const N_A2: usize = 6;
+const N_C2: usize = 6;
+const N_A3: usize = 6;
+const N_C3: usize = 6;
+const N_C4: usize = 6;
+const N_C: usize = 7;
+
+const TOL0: f64 = f64::EPSILON;
+const TOL1: f64 = 200. * TOL0;
+const MAXIT1: u32 = 20;
+const MAXIT2: u32 = MAXIT1 + f64::MANTISSA_DIGITS + 10;
+const DEGREE: f64 = PI / 180.;
+
+fn tol2() -> f64 {
+    TOL0.sqrt()
+}
+
+fn tiny() -> f64 {
+    f64::MIN_POSITIVE.sqrt()
+}
+
+/// Algorithms for geodesics.
+///
+/// C. F. F. Karney, [Algorithms for geodesics](https://doi.org/10.1007/s00190-012-0578-z),
+/// J. Geodesy 87, 43-55 (2013) および [GeographicLib](https://geographiclib.sourceforge.io/) の実装に基づく。
+pub(crate) struct Karney {
+    a: f64,
+    f: f64,
+    f1: f64,
+    e2: f64,
+    ep2: f64,
+    n: f64,
+    b: f64,
+    c2: f64,
+    etol2: f64,
+    a3x: [f64; N_A3],
+    c3x: [f64; 15],
+    c4x: [f64; 21],
+}
+
+/// Solution of the inverse problem before conversion to degrees.
+pub(crate) struct Inverse {
+    pub(crate) s12: f64,
+    pub(crate) salp1: f64,
+    pub(crate) calp1: f64,
+    pub(crate) salp2: f64,
+    pub(crate) calp2: f64,
+    /// Area between the geodesic and the equator in square meters.
+    pub(crate) area: f64,
+}
+
+impl Karney {
+    pub(crate) fn new(ellipsoid: &Ellipsoid) -> Self {
+        let a = ellipsoid.equatorial_radius();
+        let f = ellipsoid.flattening();
+        let f1 = 1. - f;
+        let e2 = f * (2. - f);
+        let n = f / (2. - f);
+        let etol2 =
+            0.1 * tol2() / ((0.001f64).max(f.abs()) * (1.0f64).min(1. - f / 2.) / 2.).sqrt();
+        let b = a * f1;
+        // authalic radius squared
+        let c2 = (a * a
+            + b * b
+                * if e2 == 0. {
+                    1.
+                } else if e2 > 0. {
+                    e2.sqrt().atanh() / e2.sqrt()
+                } else {
+                    (-e2).sqrt().atan() / (-e2).sqrt()
+                })
+            / 2.;
+        Self {
+            a,
+            f,
+            f1,
+            e2,
+            ep2: e2 / (f1 * f1),
+            n,
+            b,
+            c2,
+            etol2,
+            a3x: a3_coeff(n),
+            c3x: c3_coeff(n),
+            c4x: c4_coeff(n),
+        }
+    }
+
+    fn a3f(&self, eps: f64) -> f64 {
+        polyval(&self.a3x, eps)
+    }
+
+    fn c3f(&self, eps: f64, c: &mut [f64; N_C]) {
+        let mut mult = 1.;
+        let mut o = 0;
+        for (l, c) in c.iter_mut().enumerate().take(N_C3).skip(1) {
+            let m = N_C3 - l - 1;
+            mult *= eps;
+            *c = mult * polyval(&self.c3x[o..=o + m], eps);
+            o += m + 1;
+        }
+    }
+
+    fn c4f(&self, eps: f64, c: &mut [f64; N_C]) {
+        let mut mult = 1.;
+        let mut o = 0;
+        for (l, c) in c.iter_mut().enumerate().take(N_C4) {
+            let m = N_C4 - l - 1;
+            *c = mult * polyval(&self.c4x[o..=o + m], eps);
+            o += m + 1;
+            mult *= eps;
+        }
+    }
+
+    /// Returns the total area of the ellipsoid in square meters.
+    pub(crate) fn area0(&self) -> f64 {
+        4. * PI * self.c2
+    }
+
+    pub(crate) fn inverse(&self, from: LatLon, to: LatLon) -> Inverse {
+        let LatLon(lat1, lon1) = from;
+        let LatLon(lat2, lon2) = to;
+        let mut ca = [0.; N_C];
+
+        let (mut lon12, mut lon12s) = ang_diff(lon1, lon2);
+        let mut lonsign = if lon12.is_sign_negative() { -1. } else { 1. };
+        lon12 *= lonsign;
+        lon12s *= lonsign;
+        let lam12 = lon12 * DEGREE;
+        let (slam12, clam12) = sincosde(lon12, lon12s);
+        // the supplementary longitude difference
+        let lon12s = (180. - lon12) - lon12s;
+
+        let mut lat1 = ang_round(lat_fix(lat1));
+        let mut lat2 = ang_round(lat_fix(lat2));
+        let swapp = if lat1.abs() < lat2.abs() || lat2.is_nan() {
+            -1.
+        } else {
+            1.
+        };
+        if swapp < 0. {
+            lonsign *= -1.;
+            core::mem::swap(&mut lat1, &mut lat2);
+        }
+        let latsign = if lat1.is_sign_negative() { 1. } else { -1. };
+        lat1 *= latsign;
+        lat2 *= latsign;
+
+        let (sbet1, cbet1) = sincosd(lat1);
+        let (sbet1, cbet1) = norm2(sbet1 * self.f1, cbet1);
+        let cbet1 = cbet1.max(tiny());
+
+        let (sbet2, cbet2) = sincosd(lat2);
+        let (mut sbet2, mut cbet2) = norm2(sbet2 * self.f1, cbet2);
+        cbet2 = cbet2.max(tiny());
+
+        if cbet1 < -sbet1 {
+            if cbet2 == cbet1 {
+                sbet2 = sbet1.copysign(sbet2);
+            }
+        } else if sbet2.abs() == -sbet1 {
+            cbet2 = cbet1;
+        }
+
+        let dn1 = (1. + self.ep2 * sbet1 * sbet1).sqrt();
+        let dn2 = (1. + self.ep2 * sbet2 * sbet2).sqrt();
+
+        let mut meridian = lat1 == -90. || slam12 == 0.;
+
+        let (mut salp1, mut calp1, mut salp2, mut calp2) = (0., 0., 0., 0.);
+        let mut s12x = 0.;
+        // omg12 for the area, not used for meridians
+        let mut omg12 = None;
+
+        if meridian {
+            // Head to the target longitude
+            calp1 = clam12;
+            salp1 = slam12;
+            // At the target we're heading north
+            calp2 = 1.;
+            salp2 = 0.;
+
+            let (ssig1, csig1) = (sbet1, calp1 * cbet1);
+            let (ssig2, csig2) = (sbet2, calp2 * cbet2);
+
+            let sig12 =
+                ((csig1 * ssig2 - ssig1 * csig2).max(0.) + 0.).atan2(csig1 * csig2 + ssig1 * ssig2);
+            let lengths =
+                self.lengths(self.n, sig12, ssig1, csig1, dn1, ssig2, csig2, dn2, &mut ca);
+            s12x = lengths.s12b;
+            let m12x = lengths.m12b;
+            if sig12 < tol2() || m12x >= 0. {
+                if sig12 < 3. * tiny() || (sig12 < TOL0 && (s12x < 0. || m12x < 0.)) {
+                    s12x = 0.;
+                }
+                s12x *= self.b;
+            } else {
+                meridian = false;
+            }
+        }
+
+        if !meridian && sbet1 == 0. && (self.f <= 0. || lon12s >= self.f * 180.) {
+            // Geodesic runs along equator
+            calp1 = 0.;
+            calp2 = 0.;
+            salp1 = 1.;
+            salp2 = 1.;
+            s12x = self.a * lam12;
+            omg12 = Some((lam12 / self.f1).sin_cos());
+        } else if !meridian {
+            let start = self.inverse_start(
+                sbet1, cbet1, dn1, sbet2, cbet2, dn2, lam12, slam12, clam12, &mut ca,
+            );
+            salp1 = start.salp1;
+            calp1 = start.calp1;
+
+            if start.sig12 >= 0. {
+                // Short lines
+                salp2 = start.salp2;
+                calp2 = start.calp2;
+                s12x = start.sig12 * self.b * start.dnm;
+                omg12 = Some((lam12 / (self.f1 * start.dnm)).sin_cos());
+            } else {
+                let mut numit = 0;
+                let (mut salp1a, mut calp1a, mut salp1b, mut calp1b) = (tiny(), 1., tiny(), -1.);
+                let mut tripn = false;
+                let mut tripb = false;
+                let mut lambda;
+                loop {
+                    lambda = self.lambda12(
+                        sbet1,
+                        cbet1,
+                        dn1,
+                        sbet2,
+                        cbet2,
+                        dn2,
+                        salp1,
+                        calp1,
+                        slam12,
+                        clam12,
+                        numit < MAXIT1,
+                        &mut ca,
+                    );
+                    let v = lambda.lam12;
+                    let dv = lambda.dlam12;
+                    if tripb
+                        || v.abs() < (if tripn { 8. } else { 1. }) * TOL0
+                        || v.is_nan()
+                        || numit == MAXIT2
+                    {
+                        break;
+                    }
+                    if v > 0. && (numit > MAXIT1 || calp1 / salp1 > calp1b / salp1b) {
+                        salp1b = salp1;
+                        calp1b = calp1;
+                    } else if v < 0. && (numit > MAXIT1 || calp1 / salp1 < calp1a / salp1a) {
+                        salp1a = salp1;
+                        calp1a = calp1;
+                    }
+                    numit += 1;
+                    if numit - 1 < MAXIT1 && dv > 0. {
+                        let dalp1 = -v / dv;
+                        if dalp1.abs() < PI {
+                            let (sdalp1, cdalp1) = dalp1.sin_cos();
+                            let nsalp1 = salp1 * cdalp1 + calp1 * sdalp1;
+                            if nsalp1 > 0. {
+                                calp1 = calp1 * cdalp1 - salp1 * sdalp1;
+                                salp1 = nsalp1;
+                                (salp1, calp1) = norm2(salp1, calp1);
+                                tripn = v.abs() <= 16. * TOL0;
+                                continue;
+                            }
+                        }
+                    }
+                    // bisection
+                    salp1 = (salp1a + salp1b) / 2.;
+                    calp1 = (calp1a + calp1b) / 2.;
+                    (salp1, calp1) = norm2(salp1, calp1);
+                    tripn = false;
+                    tripb = (salp1a - salp1).abs() + (calp1a - calp1) < TOL0
+                        || (salp1 - salp1b).abs() + (calp1 - calp1b) < TOL0;
+                }
+                salp2 = lambda.salp2;
+                calp2 = lambda.calp2;
+                let lengths = self.lengths(
+                    lambda.eps,
+                    lambda.sig12,
+                    lambda.ssig1,
+                    lambda.csig1,
+                    dn1,
+                    lambda.ssig2,
+                    lambda.csig2,
+                    dn2,
+                    &mut ca,
+                );
+                s12x = lengths.s12b * self.b;
+                // omg12 = lam12 - domg12
+                let (sdomg12, cdomg12) = lambda.domg12.sin_cos();
+                omg12 = Some((
+                    slam12 * cdomg12 - clam12 * sdomg12,
+                    clam12 * cdomg12 + slam12 * sdomg12,
+                ));
+            }
+        }
+
+        let area = self.area(
+            [sbet1, cbet1, sbet2, cbet2],
+            [salp1, calp1, salp2, calp2],
+            omg12,
+            &mut ca,
+        ) * swapp
+            * lonsign
+            * latsign;
+
+        if swapp < 0. {
+            core::mem::swap(&mut salp1, &mut salp2);
+            core::mem::swap(&mut calp1, &mut calp2);
+        }
+
+        Inverse {
+            // Convert -0 to 0
+            s12: s12x + 0.,
+            salp1: salp1 * swapp * lonsign,
+            calp1: calp1 * swapp * latsign,
+            salp2: salp2 * swapp * lonsign,
+            calp2: calp2 * swapp * latsign,
+            // Convert -0 to 0
+            area: area + 0.,
+        }
+    }
+
+    /// Area between the geodesic and the equator, before the symmetry is restored.
+    fn area(
+        &self,
+        [sbet1, cbet1, sbet2, cbet2]: [f64; 4],
+        [salp1, calp1, salp2, calp2]: [f64; 4],
+        omg12: Option<(f64, f64)>,
+        ca: &mut [f64; N_C],
+    ) -> f64 {
+        // From Lambda12: sin(alp1) * cos(bet1) = sin(alp0)
+        let salp0 = salp1 * cbet1;
+        let calp0 = calp1.hypot(salp1 * sbet1);
+        let mut ret = if calp0 != 0. && salp0 != 0. {
+            let (ssig1, csig1) = norm2(sbet1, calp1 * cbet1);
+            let (ssig2, csig2) = norm2(sbet2, calp2 * cbet2);
+            let k2 = calp0 * calp0 * self.ep2;
+            let eps = k2 / (2. * (1. + (1. + k2).sqrt()) + k2);
+            let a4 = self.a * self.a * calp0 * salp0 * self.e2;
+            self.c4f(eps, ca);
+            let b41 = sin_cos_series(false, ssig1, csig1, &ca[..N_C4]);
+            let b42 = sin_cos_series(false, ssig2, csig2, &ca[..N_C4]);
+            a4 * (b42 - b41)
+        } else {
+            // Avoid problems with indeterminate sig1, sig2 on equator
+            0.
+        };
+
+        let alp12 = match omg12 {
+            Some((somg12, comg12)) if comg12 > -FRAC_1_SQRT_2 && sbet2 - sbet1 < 1.75 => {
+                // Use tan(Gamma/2) = tan(omg12/2) * (tan(bet1/2)+tan(bet2/2))/(1+tan(bet1/2)*tan(bet2/2))
+                let domg12 = 1. + comg12;
+                let dbet1 = 1. + cbet1;
+                let dbet2 = 1. + cbet2;
+                2. * (somg12 * (sbet1 * dbet2 + sbet2 * dbet1))
+                    .atan2(domg12 * (sbet1 * sbet2 + dbet1 * dbet2))
+            }
+            _ => {
+                // alp12 = alp2 - alp1, used in atan2 so no need to normalize
+                let mut salp12 = salp2 * calp1 - calp2 * salp1;
+                let mut calp12 = calp2 * calp1 + salp2 * salp1;
+                if salp12 == 0. && calp12 < 0. {
+                    salp12 = tiny() * calp1;
+                    calp12 = -1.;
+                }
+                salp12.atan2(calp12)
+            }
+        };
+        ret += self.c2 * alp12;
+        ret
+    }
+
+    /// Returns the end point and the azimuth there.
+    pub(crate) fn direct(&self, from: LatLon, azimuth: f64, distance: f64) -> (LatLon, f64) {
+        let LatLon(lat1, lon1) = from;
+        let azi1 = ang_normalize(azimuth);
+        let (salp1, calp1) = sincosd(ang_round(azi1));
+
+        let (sbet1, cbet1) = sincosd(ang_round(lat_fix(lat1)));
+        let (sbet1, cbet1) = norm2(sbet1 * self.f1, cbet1);
+        let cbet1 = cbet1.max(tiny());
+
+        // alp0 in [0, pi/2 - |bet1|]
+        let salp0 = salp1 * cbet1;
+        let calp0 = calp1.hypot(salp1 * sbet1);
+        let somg1 = salp0 * sbet1;
+        let comg1 = if sbet1 != 0. || calp1 != 0. {
+            cbet1 * calp1
+        } else {
+            1.
+        };
+        // sig1 in (-pi, pi]
+        let (ssig1, csig1) = norm2(sbet1, comg1);
+
+        let k2 = calp0 * calp0 * self.ep2;
+        let eps = k2 / (2. * (1. + (1. + k2).sqrt()) + k2);
+
+        let a1m1 = a1m1f(eps);
+        let mut c1a = [0.; N_C];
+        c1f(eps, &mut c1a);
+        let b11 = sin_cos_series(true, ssig1, csig1, &c1a[..=N_C1]);
+        let (s, c) = b11.sin_cos();
+        // tau1 = sig1 + B11
+        let stau1 = ssig1 * c + csig1 * s;
+        let ctau1 = csig1 * c - ssig1 * s;
+
+        let mut c1pa = [0.; N_C];
+        c1pf(eps, &mut c1pa);
+
+        let a3c = -self.f * salp0 * self.a3f(eps);
+        let mut c3a = [0.; N_C];
+        self.c3f(eps, &mut c3a);
+        let b31 = sin_cos_series(true, ssig1, csig1, &c3a[..N_C3]);
+
+        let tau12 = distance / (self.b * (1. + a1m1));
+        let (s, c) = tau12.sin_cos();
+        let b12 = -sin_cos_series(
+            true,
+            stau1 * c + ctau1 * s,
+            ctau1 * c - stau1 * s,
+            &c1pa[..=N_C1P],
+        );
+        let mut sig12 = tau12 - (b12 - b11);
+        let (mut ssig12, mut csig12) = sig12.sin_cos();
+        if self.f.abs() > 0.01 {
+            let ssig2 = ssig1 * csig12 + csig1 * ssig12;
+            let csig2 = csig1 * csig12 - ssig1 * ssig12;
+            let b12 = sin_cos_series(true, ssig2, csig2, &c1a[..=N_C1]);
+            let serr = (1. + a1m1) * (sig12 + (b12 - b11)) - distance / self.b;
+            sig12 -= serr / (1. + k2 * ssig2 * ssig2).sqrt();
+            (ssig12, csig12) = sig12.sin_cos();
+        }
+
+        let ssig2 = ssig1 * csig12 + csig1 * ssig12;
+        let mut csig2 = csig1 * csig12 - ssig1 * ssig12;
+        let sbet2 = calp0 * ssig2;
+        let mut cbet2 = salp0.hypot(calp0 * csig2);
+        if cbet2 == 0. {
+            cbet2 = tiny();
+            csig2 = tiny();
+        }
+        // No need to normalize
+        let salp2 = salp0;
+        let calp2 = calp0 * csig2;
+
+        let somg2 = salp0 * ssig2;
+        let comg2 = csig2;
+        let omg12 = (somg2 * comg1 - comg2 * somg1).atan2(comg2 * comg1 + somg2 * somg1);
+        let lam12 =
+            omg12 + a3c * (sig12 + (sin_cos_series(true, ssig2, csig2, &c3a[..N_C3]) - b31));
+        let lon12 = lam12 / DEGREE;
+        let lon2 = ang_normalize(ang_normalize(lon1) + ang_normalize(lon12));
+        let lat2 = atan2d(sbet2, self.f1 * cbet2);
+        let azi2 = atan2d(salp2, calp2);
+
+        (LatLon(lat2, lon2), azi2)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn lengths(
+        &self,
+        eps: f64,
+        sig12: f64,
+        ssig1: f64,
+        csig1: f64,
+        dn1: f64,
+        ssig2: f64,
+        csig2: f64,
+        dn2: f64,
+        ca: &mut [f64; N_C],
+    ) -> Lengths {
+        let mut cb = [0.; N_C];
+        let a1 = a1m1f(eps);
+        c1f(eps, ca);
+        let a2 = a2m1f(eps);
+        c2f(eps, &mut cb);
+        let m0 = a1 - a2;
+        let a1 = 1. + a1;
+        let a2 = 1. + a2;
+
+        let b1 = sin_cos_series(true, ssig2, csig2, &ca[..=N_C1])
+            - sin_cos_series(true, ssig1, csig1, &ca[..=N_C1]);
+        let s12b = a1 * (sig12 + b1);
+        let b2 = sin_cos_series(true, ssig2, csig2, &cb[..=N_C2])
+            - sin_cos_series(true, ssig1, csig1, &cb[..=N_C2]);
+        let j12 = m0 * sig12 + (a1 * b1 - a2 * b2);
+        let m12b = dn2 * (csig1 * ssig2) - dn1 * (ssig1 * csig2) - csig1 * csig2 * j12;
+        Lengths { s12b, m12b, m0 }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn inverse_start(
+        &self,
+        sbet1: f64,
+        cbet1: f64,
+        dn1: f64,
+        sbet2: f64,
+        cbet2: f64,
+        dn2: f64,
+        lam12: f64,
+        slam12: f64,
+        clam12: f64,
+        ca: &mut [f64; N_C],
+    ) -> InverseStart {
+        let mut ret = InverseStart {
+            sig12: -1.,
+            salp1: 0.,
+            calp1: 0.,
+            salp2: 0.,
+            calp2: 0.,
+            dnm: 0.,
+        };
+        let sbet12 = sbet2 * cbet1 - cbet2 * sbet1;
+        let cbet12 = cbet2 * cbet1 + sbet2 * sbet1;
+        let sbet12a = sbet2 * cbet1 + cbet2 * sbet1;
+        let shortline = cbet12 >= 0. && sbet12 < 0.5 && cbet2 * lam12 < 0.5;
+        let (mut somg12, mut comg12);
+        if shortline {
+            let mut sbetm2 = (sbet1 + sbet2).powi(2);
+            sbetm2 /= sbetm2 + (cbet1 + cbet2).powi(2);
+            ret.dnm = (1. + self.ep2 * sbetm2).sqrt();
+            let omg12 = lam12 / (self.f1 * ret.dnm);
+            (somg12, comg12) = omg12.sin_cos();
+        } else {
+            somg12 = slam12;
+            comg12 = clam12;
+        }
+
+        let mut salp1 = cbet2 * somg12;
+        let mut calp1 = if comg12 >= 0. {
+            sbet12 + cbet2 * sbet1 * somg12 * somg12 / (1. + comg12)
+        } else {
+            sbet12a - cbet2 * sbet1 * somg12 * somg12 / (1. - comg12)
+        };
+
+        let ssig12 = salp1.hypot(calp1);
+        let csig12 = sbet1 * sbet2 + cbet1 * cbet2 * comg12;
+
+        if shortline && ssig12 < self.etol2 {
+            // really short lines
+            let salp2 = cbet1 * somg12;
+            let calp2 = sbet12
+                - cbet1
+                    * sbet2
+                    * if comg12 >= 0. {
+                        somg12 * somg12 / (1. + comg12)
+                    } else {
+                        1. - comg12
+                    };
+            (ret.salp2, ret.calp2) = norm2(salp2, calp2);
+            ret.sig12 = ssig12.atan2(csig12);
+        } else if self.n.abs() > 0.1
+            || csig12 >= 0.
+            || ssig12 >= 6. * self.n.abs() * PI * cbet1 * cbet1
+        {
+            // Nothing to do, zeroth order spherical approximation is OK
+        } else {
+            // Scale lam12 and bet2 to x, y coordinate system where antipodal point is at origin
+            let lam12x = (-slam12).atan2(-clam12); // lam12 - pi
+            let (x, y, lamscale);
+            if self.f >= 0. {
+                let k2 = sbet1 * sbet1 * self.ep2;
+                let eps = k2 / (2. * (1. + (1. + k2).sqrt()) + k2);
+                lamscale = self.f * cbet1 * self.a3f(eps) * PI;
+                let betscale = lamscale * cbet1;
+                x = lam12x / lamscale;
+                y = sbet12a / betscale;
+            } else {
+                let cbet12a = cbet2 * cbet1 - sbet2 * sbet1;
+                let bet12a = sbet12a.atan2(cbet12a);
+                let lengths = self.lengths(
+                    self.n,
+                    PI + bet12a,
+                    sbet1,
+                    -cbet1,
+                    dn1,
+                    sbet2,
+                    cbet2,
+                    dn2,
+                    ca,
+                );
+                x = -1. + lengths.m12b / (cbet1 * cbet2 * lengths.m0 * PI);
+                let betscale = if x < -0.01 {
+                    sbet12a / x
+                } else {
+                    -self.f * cbet1 * cbet1 * PI
+                };
+                lamscale = betscale / cbet1;
+                y = lam12x / lamscale;
+            }
+
+            if y > -TOL1 && x > -1. - 1000. * tol2() {
+                if self.f >= 0. {
+                    salp1 = (1.0f64).min(-x);
+                    calp1 = -(1. - salp1 * salp1).sqrt();
+                } else {
+                    calp1 = x.max(if x > -TOL1 { 0. } else { -1. });
+                    salp1 = (1. - calp1 * calp1).sqrt();
+                }
+            } else {
+                let k = astroid(x, y);
+                let omg12a = lamscale
+                    * if self.f >= 0. {
+                        -x * k / (1. + k)
+                    } else {
+                        -y * (1. + k) / k
+                    };
+                (somg12, comg12) = omg12a.sin_cos();
+                comg12 = -comg12;
+                salp1 = cbet2 * somg12;
+                calp1 = sbet12a - cbet2 * sbet1 * somg12 * somg12 / (1. - comg12);
+            }
+        }
+        if salp1 > 0. {
+            (ret.salp1, ret.calp1) = norm2(salp1, calp1);
+        } else {
+            ret.salp1 = 1.;
+            ret.calp1 = 0.;
+        }
+        ret
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn lambda12(
+        &self,
+        sbet1: f64,
+        cbet1: f64,
+        dn1: f64,
+        sbet2: f64,
+        cbet2: f64,
+        dn2: f64,
+        salp1: f64,
+        calp1: f64,
+        slam120: f64,
+        clam120: f64,
+        diffp: bool,
+        ca: &mut [f64; N_C],
+    ) -> Lambda12 {
+        let calp1 = if sbet1 == 0. && calp1 == 0. {
+            // Break degeneracy of equatorial line
+            -tiny()
+        } else {
+            calp1
+        };
+
+        // alp0 in [0, pi/2 - |bet1|]
+        let salp0 = salp1 * cbet1;
+        let calp0 = calp1.hypot(salp1 * sbet1);
+
+        let somg1 = salp0 * sbet1;
+        let comg1 = calp1 * cbet1;
+        let (ssig1, csig1) = norm2(sbet1, comg1);
+
+        let salp2 = if cbet2 != cbet1 { salp0 / cbet2 } else { salp1 };
+        let calp2 = if cbet2 != cbet1 || sbet2.abs() != -sbet1 {
+            ((calp1 * cbet1).powi(2)
+                + if cbet1 < -sbet1 {
+                    (cbet2 - cbet1) * (cbet1 + cbet2)
+                } else {
+                    (sbet1 - sbet2) * (sbet1 + sbet2)
+                })
+            .sqrt()
+                / cbet2
+        } else {
+            calp1.abs()
+        };
+        let somg2 = salp0 * sbet2;
+        let comg2 = calp2 * cbet2;
+        let (ssig2, csig2) = norm2(sbet2, comg2);
+
+        // sig12 = sig2 - sig1, limit to [0, pi]
+        let sig12 =
+            ((csig1 * ssig2 - ssig1 * csig2).max(0.) + 0.).atan2(csig1 * csig2 + ssig1 * ssig2);
+        // omg12 = omg2 - omg1, limit to [0, pi]
+        let somg12 = (comg1 * somg2 - somg1 * comg2).max(0.) + 0.;
+        let comg12 = comg1 * comg2 + somg1 * somg2;
+        // eta = omg12 - lam120
+        let eta = (somg12 * clam120 - comg12 * slam120).atan2(comg12 * clam120 + somg12 * slam120);
+        let k2 = calp0 * calp0 * self.ep2;
+        let eps = k2 / (2. * (1. + (1. + k2).sqrt()) + k2);
+        self.c3f(eps, ca);
+        let b312 = sin_cos_series(true, ssig2, csig2, &ca[..N_C3])
+            - sin_cos_series(true, ssig1, csig1, &ca[..N_C3]);
+        let domg12 = -self.f * self.a3f(eps) * salp0 * (sig12 + b312);
+        let lam12 = eta + domg12;
+
+        let dlam12 = if !diffp {
+            0.
+        } else if calp2 == 0. {
+            -2. * self.f1 * dn1 / sbet1
+        } else {
+            let lengths = self.lengths(eps, sig12, ssig1, csig1, dn1, ssig2, csig2, dn2, ca);
+            lengths.m12b * self.f1 / (calp2 * cbet2)
+        };
+
+        Lambda12 {
+            lam12,
+            salp2,
+            calp2,
+            sig12,
+            ssig1,
+            csig1,
+            ssig2,
+            csig2,
+            eps,
+            domg12,
+            dlam12,
+        }
+    }
+}
+
+struct Lengths {
+    s12b: f64,
+    m12b: f64,
+    m0: f64,
+}
+
+struct InverseStart {
+    sig12: f64,
+    salp1: f64,
+    calp1: f64,
+    salp2: f64,
+    calp2: f64,
+    dnm: f64,
+}
+
+struct Lambda12 {
+    lam12: f64,
+    salp2: f64,
+    calp2: f64,
+    sig12: f64,
+    ssig1: f64,
+    csig1: f64,
+    ssig2: f64,
+    csig2: f64,
+    eps: f64,
+    domg12: f64,
+    dlam12: f64,
+}
+
+/// Evaluates a polynomial with coefficients in descending order.
+pub(crate) fn polyval(p: &[f64], x: f64) -> f64 {
+    p.iter().fold(0., |y, &c| y * x + c)
+}
+
+/// Evaluates `sum(c[i] * sin(2*i*x), i, 1, n)` if `sinp`, otherwise `sum(c[i] * cos((2*i+1)*x), i, 0, n-1)`.
+pub(crate) fn sin_cos_series(sinp: bool, sinx: f64, cosx: f64, c: &[f64]) -> f64 {
+    let mut n = c.len() - usize::from(sinp);
+    let mut i = c.len();
+    // 2 * cos(2 * x)
+    let ar = 2. * (cosx - sinx) * (cosx + sinx);
+    let mut y0 = if n % 2 == 1 {
+        i -= 1;
+        c[i]
+    } else {
+        0.
+    };
+    let mut y1 = 0.;
+    n /= 2;
+    while n > 0 {
+        n -= 1;
+        i -= 1;
+        y1 = ar * y0 - y1 + c[i];
+        i -= 1;
+        y0 = ar * y1 - y0 + c[i];
+    }
+    if sinp {
+        // sin(2 * x) * y0
+        2. * sinx * cosx * y0
+    } else {
+        // cos(x) * (y0 - y1)
+        cosx * (y0 - y1)
+    }
+}
+
+fn astroid(x: f64, y: f64) -> f64 {
+    let p = x * x;
+    let q = y * y;
+    let r = (p + q - 1.) / 6.;
+    if q == 0. && r <= 0. {
+        return 0.;
+    }
+    let s = p * q / 4.;
+    let r2 = r * r;
+    let r3 = r * r2;
+    let disc = s * (s + 2. * r3);
+    let mut u = r;
+    if disc >= 0. {
+        let mut t3 = s + r3;
+        t3 += if t3 < 0. { -disc.sqrt() } else { disc.sqrt() };
+        let t = t3.cbrt();
+        u += t + if t != 0. { r2 / t } else { 0. };
+    } else {
+        let ang = (-disc).sqrt().atan2(-(s + r3));
+        u += 2. * r * (ang / 3.).cos();
+    }
+    let v = (u * u + q).sqrt();
+    let uv = if u < 0. { q / (v - u) } else { u + v };
+    let w = (uv - q) / (2. * v);
+    uv / ((uv + w * w).sqrt() + w)
+}
+
+fn a1m1f(eps: f64) -> f64 {
+    const COEFF: [f64; 5] = [1., 4., 64., 0., 256.];
+    let m = N_A1 / 2;
+    let t = polyval(&COEFF[..=m], eps * eps) / COEFF[m + 1];
+    (t + eps) / (1. - eps)
+}
+
+fn c1f(eps: f64, c: &mut [f64; N_C]) {
+    #[rustfmt::skip]
+    const COEFF: [f64; 18] = [
+        -1., 6., -16., 32.,
+        -9., 64., -128., 2048.,
+        9., -16., 768.,
+        3., -5., 512.,
+        -7., 1280.,
+        -7., 2048.,
+    ];
+    series(eps, &COEFF, N_C1, c);
+}
+
+fn c1pf(eps: f64, c: &mut [f64; N_C]) {
+    #[rustfmt::skip]
+    const COEFF: [f64; 18] = [
+        205., -432., 768., 1536.,
+        4005., -4736., 3840., 12288.,
+        -225., 116., 384.,
+        -7173., 2695., 7680.,
+        3467., 7680.,
+        38081., 61440.,
+    ];
+    series(eps, &COEFF, N_C1P, c);
+}
+
+fn a2m1f(eps: f64) -> f64 {
+    const COEFF: [f64; 5] = [-11., -28., -192., 0., 256.];
+    let m = N_A2 / 2;
+    let t = polyval(&COEFF[..=m], eps * eps) / COEFF[m + 1];
+    (t - eps) / (1. + eps)
+}
+
+fn c2f(eps: f64, c: &mut [f64; N_C]) {
+    #[rustfmt::skip]
+    const COEFF: [f64; 18] = [
+        1., 2., 16., 32.,
+        35., 64., 384., 2048.,
+        15., 80., 768.,
+        7., 35., 512.,
+        63., 1280.,
+        77., 2048.,
+    ];
+    series(eps, &COEFF, N_C2, c);
+}
+
+/// Evaluates `c[l]` for `l` in `1..=n` from polynomials in `eps^2`.
+fn series(eps: f64, coeff: &[f64], n: usize, c: &mut [f64; N_C]) {
+    let eps2 = eps * eps;
+    let mut d = eps;
+    let mut o = 0;
+    for (l, c) in c.iter_mut().enumerate().take(n + 1).skip(1) {
+        let m = (n - l) / 2;
+        *c = d * polyval(&coeff[o..=o + m], eps2) / coeff[o + m + 1];
+        o += m + 2;
+        d *= eps;
+    }
+}
+
+fn c4_coeff(n: f64) -> [f64; 21] {
+    #[rustfmt::skip]
+    const COEFF: [f64; 77] = [
+        97., 15015.,
+        1088., 156., 45045.,
+        -224., -4784., 1573., 45045.,
+        -10656., 14144., -4576., -858., 45045.,
+        64., 624., -4576., 6864., -3003., 15015.,
+        100., 208., 572., 3432., -12012., 30030., 45045.,
+        1., 9009.,
+        -2944., 468., 135135.,
+        5792., 1040., -1287., 135135.,
+        5952., -11648., 9152., -2574., 135135.,
+        -64., -624., 4576., -6864., 3003., 135135.,
+        8., 10725.,
+        1856., -936., 225225.,
+        -8448., 4992., -1144., 225225.,
+        -1440., 4160., -4576., 1716., 225225.,
+        -136., 63063.,
+        1024., -208., 105105.,
+        3584., -3328., 1144., 315315.,
+        -128., 135135.,
+        -2560., 832., 405405.,
+        128., 99099.,
+    ];
+    let mut c4x = [0.; 21];
+    let mut o = 0;
+    let mut k = 0;
+    for l in 0..N_C4 {
+        for j in (l..N_C4).rev() {
+            let m = N_C4 - j - 1;
+            c4x[k] = polyval(&COEFF[o..=o + m], n) / COEFF[o + m + 1];
+            k += 1;
+            o += m + 2;
+        }
+    }
+    c4x
+}
+
+fn a3_coeff(n: f64) -> [f64; N_A3] {
+    #[rustfmt::skip]
+    const COEFF: [f64; 18] = [
+        -3., 128.,
+        -2., -3., 64.,
+        -1., -3., -1., 16.,
+        3., -1., -2., 8.,
+        1., -1., 2.,
+        1., 1.,
+    ];
+    let mut a3x = [0.; N_A3];
+    let mut o = 0;
+    for (k, j) in (0..N_A3).rev().enumerate() {
+        let m = (N_A3 - j - 1).min(j);
+        a3x[k] = polyval(&COEFF[o..=o + m], n) / COEFF[o + m + 1];
+        o += m + 2;
+    }
+    a3x
+}
+
+fn c3_coeff(n: f64) -> [f64; 15] {
+    #[rustfmt::skip]
+    const COEFF: [f64; 45] = [
+        3., 128.,
+        2., 5., 128.,
+        -1., 3., 3., 64.,
+        -1., 0., 1., 8.,
+        -1., 1., 4.,
+        5., 256.,
+        1., 3., 128.,
+        -3., -2., 3., 64.,
+        1., -3., 2., 32.,
+        7., 512.,
+        -10., 9., 384.,
+        5., -9., 5., 192.,
+        7., 512.,
+        -14., 7., 512.,
+        21., 2560.,
+    ];
+    let mut c3x = [0.; 15];
+    let mut o = 0;
+    let mut k = 0;
+    for l in 1..N_C3 {
+        for j in (l..N_C3).rev() {
+            let m = (N_C3 - j - 1).min(j);
+            c3x[k] = polyval(&COEFF[o..=o + m], n) / COEFF[o + m + 1];
+            k += 1;
+            o += m + 2;
+        }
+    }
+    c3x
+}
+
+/// Remainder of `x / y` in `[-y/2, y/2]`.
+pub(crate) fn remainder(x: f64, y: f64) -> f64 {
+    let r = x % y;
+    if r < -y / 2. {
+        r + y
+    } else if r > y / 2. {
+        r - y
+    } else {
+        r
+    }
+}
+
+pub(crate) fn sum_x(u: f64, v: f64) -> (f64, f64) {
+    let s = u + v;
+    let up = s - v;
+    let vpp = s - up;
+    let t = if s != 0. {
+        0. - ((up - u) + (vpp - v))
+    } else {
+        s
+    };
+    (s, t)
+}
+
+pub(crate) fn ang_normalize(x: f64) -> f64 {
+    let y = remainder(x, 360.);
+    if y.abs() == 180. {
+        180f64.copysign(x)
+    } else {
+        y
+    }
+}
+
+/// Returns `y - x` in `[-180, 180]` and its error.
+pub(crate) fn ang_diff(x: f64, y: f64) -> (f64, f64) {
+    let (d, t) = sum_x(remainder(-x, 360.), remainder(y, 360.));
+    let (mut d, t) = sum_x(remainder(d, 360.), t);
+    if d == 0. || d.abs() == 180. {
+        d = d.copysign(if t == 0. { y - x } else { -t });
+    }
+    (d, t)
+}
+
+fn ang_round(x: f64) -> f64 {
+    const Z: f64 = 1. / 16.;
+    let y = x.abs();
+    let w = Z - y;
+    let y = if w > 0. { Z - w } else { y };
+    y.copysign(x)
+}
+
+fn lat_fix(x: f64) -> f64 {
+    if x.abs() > 90. {
+        f64::NAN
+    } else {
+        x
+    }
+}
+
+fn norm2(sinx: f64, cosx: f64) -> (f64, f64) {
+    let r = sinx.hypot(cosx);
+    (sinx / r, cosx / r)
+}
+
+/// Sine and cosine of `x` in degrees, exact at multiples of 90 degrees.
+pub(crate) fn sincosd(x: f64) -> (f64, f64) {
+    sincosde(x, 0.)
+}
+
+/// Sine and cosine of `x + t` in degrees where `t` is a small correction.
+fn sincosde(x: f64, t: f64) -> (f64, f64) {
+    let r = x % 360.;
+    let q = (r / 90.).round();
+    let r = ang_round(r - 90. * q + t) * DEGREE;
+    let (s, c) = r.sin_cos();
+    let (sinx, cosx) = match (q as i32).rem_euclid(4) {
+        0 => (s, c),
+        1 => (c, -s),
+        2 => (-s, -c),
+        _ => (-c, s),
+    };
+    let sinx = if sinx == 0. { sinx.copysign(x) } else { sinx };
+    (sinx, cosx + 0.)
+}
+
+/// `atan2(y, x)` in degrees, exact at multiples of 45 degrees.
+pub(crate) fn atan2d(y: f64, x: f64) -> f64 {
+    let (mut x, mut y) = (x, y);
+    let mut q = 0;
+    if y.abs() > x.abs() {
+        core::mem::swap(&mut x, &mut y);
+        q = 2;
+    }
+    if x.is_sign_negative() {
+        x = -x;
+        q += 1;
+    }
+    let ang = y.atan2(x) / DEGREE;
+    match q {
+        1 => 180f64.copysign(y) - ang,
+        2 => 90. - ang,
+        3 => -90. + ang,
+        _ => ang,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{LatLon, WGS84};
+
+    #[test]
+    fn inverse() {
+        // GeographicLib: echo 40.6 -73.8 51.6 -0.5 | GeodSolve -i
+        let ret = WGS84.geodesic_inverse(LatLon(40.6, -73.8), LatLon(51.6, -0.5));
+        assert_abs_diff_eq!(ret.forward_azimuth(), 51.198882845579, epsilon = 1e-6);
+        assert_abs_diff_eq!(ret.back_azimuth(), 107.821776735514 + 180., epsilon = 1e-6);
+        assert_abs_diff_eq!(ret.distance(), 5551759.400319, epsilon = 0.001);
+    }
+
+    #[test]
+    fn direct() {
+        let ret = WGS84.geodesic_direct(LatLon(40.6, -73.8), 51.198882845579, 5551759.400319);
+        assert_abs_diff_eq!(*ret.to().lat(), 51.6, epsilon = 1e-9);
+        assert_abs_diff_eq!(*ret.to().lon(), -0.5, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn same_point() {
+        let ret = WGS84.geodesic_inverse(LatLon(35.0, 135.0), LatLon(35.0, 135.0));
+        assert_eq!(ret.distance(), 0.0);
+    }
+
+    #[test]
+    fn meridian() {
+        let ret = WGS84.geodesic_inverse(LatLon(35.0, 135.0), LatLon(36.0, 135.0));
+        assert_eq!(ret.forward_azimuth(), 0.0);
+        assert_eq!(ret.back_azimuth(), 180.0);
+    }
+
+    #[test]
+    fn antipodal() {
+        let ret = WGS84.geodesic_inverse(LatLon(0.5, 0.0), LatLon(-0.5, 179.7));
+        let back = WGS84.geodesic_direct(ret.from(), ret.forward_azimuth(), ret.distance());
+        assert_abs_diff_eq!(*back.to().lat(), -0.5, epsilon = 1e-9);
+        assert_abs_diff_eq!(*back.to().lon(), 179.7, epsilon = 1e-9);
+    }
+}
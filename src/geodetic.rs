@@ -1,8 +1,12 @@
-use std::{
+use core::{
+    cmp::Ordering,
     fmt,
-    ops::{Add, Div, Mul, Sub},
+    ops::{Add, Div, Mul, Neg, Sub},
 };
 
+#[cfg(not(any(feature = "std", test)))]
+use crate::math::Float;
+
 pub const DEGREES: f64 = 1.;
 pub const MINUTES: f64 = DEGREES * 60.;
 pub const SECS: f64 = MINUTES * 60.;
@@ -24,9 +28,12 @@ pub const MICRO_SECS: f64 = MILLI_SECS * 1_000.;
 /// ```
 /// use jgd::{Dms, LatLon};
 ///
-/// let dms = LatLon(Dms(35, 0, 0.0), Dms(135, 0, 0.0));
+/// # fn main() -> anyhow::Result<()> {
+/// let dms = LatLon(Dms::new(35, 0, 0.0)?, Dms::new(135, 0, 0.0)?);
 /// let degrees = dms.to_degrees();
 /// # assert_eq!(degrees, LatLon(35., 135.));
+/// #   Ok(())
+/// # }
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
 pub struct LatLon<T = f64>(
@@ -83,12 +90,17 @@ impl LatLon<f64> {
     /// use jgd::LatLon;
     ///
     /// # let degrees = LatLon(35.0, 135.0);
-    /// let LatLon(lat, lon) = degrees.to_dms();
-    /// # assert_eq!(lat, jgd::Dms(35, 0, 0.0));
-    /// # assert_eq!(lon, jgd::Dms(135, 0, 0.0));
+    /// let LatLon(lat, lon) = degrees.to_dms().unwrap();
+    /// # assert_eq!(lat, jgd::Dms::new(35, 0, 0.0).unwrap());
+    /// # assert_eq!(lon, jgd::Dms::new(135, 0, 0.0).unwrap());
     /// ```
-    pub fn to_dms(self) -> LatLon<Dms> {
-        self.map(Dms::from_degrees)
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DmsError`] if latitude or longitude is not finite, as [`Dms::from_degrees`].
+    pub fn to_dms(self) -> Result<LatLon<Dms>, DmsError> {
+        let LatLon(lat, lon) = self;
+        Ok(LatLon(Dms::from_degrees(lat)?, Dms::from_degrees(lon)?))
     }
 
     pub(crate) fn validate_degrees(self) -> Result<(), DegreesError> {
@@ -112,9 +124,12 @@ impl LatLon<Dms> {
     /// ```
     /// use jgd::{Dms, LatLon};
     ///
-    /// let dms = LatLon(Dms(35, 0, 0.0), Dms(135, 0, 0.0));
+    /// # fn main() -> anyhow::Result<()> {
+    /// let dms = LatLon(Dms::new(35, 0, 0.0)?, Dms::new(135, 0, 0.0)?);
     /// let degrees = dms.to_degrees();
     /// # assert_eq!(degrees, LatLon(35., 135.0));
+    /// #   Ok(())
+    /// # }
     /// ```
     pub fn to_degrees(self) -> LatLon<f64> {
         self.map(Dms::to_degrees)
@@ -149,56 +164,231 @@ impl Div<f64> for LatLon {
     }
 }
 
+/// Formats latitude and longitude separated by a comma.
+///
+/// # Examples
+///
+/// ```
+/// use jgd::LatLon;
+///
+/// assert_eq!(format!("{:.3}", LatLon(35.0, 135.0)), "35.000, 135.000");
+/// ```
+impl<T: fmt::Display> fmt::Display for LatLon<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let LatLon(lat, lon) = self;
+        match f.precision() {
+            Some(p) => write!(f, "{:.*}, {:.*}", p, lat, p, lon),
+            None => write!(f, "{}, {}", lat, lon),
+        }
+    }
+}
+
 /// Degrees minutes seconds.
 ///
+/// 度分秒。符号は各成分ではなく全体に対して1つ持つため、`-0°30'` のような値も表せる。
+/// 分・秒は常に 0 以上 60 未満に正規化されている。
+/// 比較は [`Dms::to_degrees`] の値で行うため、`-0°` と `0°` は等しい。
+///
 /// # Examples
 ///
 /// ```
-/// # use jgd::Dms;
-/// let lat = Dms(35, 0, 0.0);
+/// use jgd::{Dms, Hemisphere};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let lat = Dms::new(35, 0, 0.0)?;
+/// // 昭和基地
+/// let syowa = Dms::new(69, 0, 25.0)?.with_hemisphere(Hemisphere::South);
+/// # assert_eq!(syowa.to_degrees(), -(69.0 + 25.0 / 3600.0));
+/// let minus_half = -Dms::new(0, 30, 0.0)?;
+/// # assert_eq!(minus_half.to_degrees(), -0.5);
+/// # assert_eq!(-Dms::new(0, 0, 0.0)?, Dms::new(0, 0, 0.0)?);
+/// # assert!(minus_half < Dms::new(0, 0, 0.0)?);
+/// #   Ok(())
+/// # }
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
-pub struct Dms(
-    /// Degrees.
-    pub i32,
-    /// Minutes.
-    pub i32,
-    /// Seconds.
-    pub f64,
-);
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Dms {
+    negative: bool,
+    d: u32,
+    m: u32,
+    s: f64,
+}
 impl Dms {
-    /// Returns degrees.
-    pub fn d(self) -> i32 {
-        self.0
+    /// Constructs a non-negative [`Dms`].
+    ///
+    /// 負の値は [`Neg`] または [`Dms::with_hemisphere`] で得られる。
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DmsError`] if minutes or seconds are not in the range of 0 to less than 60.
+    pub fn new(d: u32, m: u32, s: f64) -> Result<Self, DmsError> {
+        if m >= 60 {
+            return Err(DmsError::Minutes(m));
+        }
+        if !(0. ..60.).contains(&s) {
+            return Err(DmsError::Seconds(s));
+        }
+        Ok(Self::new_unchecked(false, d, m, s))
     }
 
-    /// Returns minutes.
-    pub fn m(self) -> i32 {
-        self.1
+    pub(crate) const fn new_unchecked(negative: bool, d: u32, m: u32, s: f64) -> Self {
+        let negative = negative && (d != 0 || m != 0 || s != 0.);
+        // -0.0 を 0.0 にする
+        let s = s + 0.;
+        Self { negative, d, m, s }
     }
 
-    /// Returns seconds.
+    /// Returns degrees without the sign.
+    pub fn d(self) -> u32 {
+        self.d
+    }
+
+    /// Returns minutes without the sign.
+    pub fn m(self) -> u32 {
+        self.m
+    }
+
+    /// Returns seconds without the sign.
     pub fn s(self) -> f64 {
-        self.2
+        self.s
+    }
+
+    /// Returns `true` if south latitude or west longitude.
+    pub fn is_negative(self) -> bool {
+        self.negative
+    }
+
+    /// Returns self with the sign of the hemisphere.
+    pub fn with_hemisphere(self, hemisphere: Hemisphere) -> Self {
+        let Self { d, m, s, .. } = self;
+        Self::new_unchecked(hemisphere.is_negative(), d, m, s)
+    }
+
+    /// Returns [`Hemisphere::North`] or [`Hemisphere::South`] as a latitude.
+    pub fn lat_hemisphere(self) -> Hemisphere {
+        if self.negative {
+            Hemisphere::South
+        } else {
+            Hemisphere::North
+        }
+    }
+
+    /// Returns [`Hemisphere::East`] or [`Hemisphere::West`] as a longitude.
+    pub fn lon_hemisphere(self) -> Hemisphere {
+        if self.negative {
+            Hemisphere::West
+        } else {
+            Hemisphere::East
+        }
     }
 
     /// Constructs from decimal degrees.
-    fn from_degrees(deg: f64) -> Self {
-        let d = deg as i32;
-        let m = (deg * 60. % 60.) as i32;
-        let s = (deg * 3600.) % 60.;
-        Dms(d, m, s)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jgd::Dms;
+    ///
+    /// let dms = Dms::from_degrees(-0.5).unwrap();
+    /// # assert!(dms.is_negative());
+    /// # assert_eq!((dms.d(), dms.m(), dms.s()), (0, 30, 0.0));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DmsError::Degrees`] if `deg` is NaN, infinite or too large for degrees of `u32`.
+    pub fn from_degrees(deg: f64) -> Result<Self, DmsError> {
+        if !deg.is_finite() || deg.abs() >= f64::from(u32::MAX) {
+            return Err(DmsError::Degrees(deg));
+        }
+        let secs = deg.abs() * SECS;
+        let mut d = (secs / SECS).floor();
+        let mut m = ((secs - d * SECS) / MINUTES).floor();
+        let mut s = (secs - d * SECS - m * MINUTES).max(0.);
+        // 丸め誤差による繰り上がり
+        if s >= 60. {
+            s -= 60.;
+            m += 1.;
+        }
+        if m >= 60. {
+            m -= 60.;
+            d += 1.;
+        }
+        Ok(Self::new_unchecked(deg < 0., d as u32, m as u32, s))
     }
 
     /// Converts to decimal degrees.
-    fn to_degrees(self) -> f64 {
-        let Dms(d, m, s) = self;
-        f64::from(d) + f64::from(m) / 60. + s / 3_600.
+    pub fn to_degrees(self) -> f64 {
+        let Self { negative, d, m, s } = self;
+        let abs = f64::from(d) + f64::from(m) / MINUTES + s / SECS;
+        if negative {
+            -abs
+        } else {
+            abs
+        }
+    }
+}
+impl Neg for Dms {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        let Self { negative, d, m, s } = self;
+        Self::new_unchecked(!negative, d, m, s)
+    }
+}
+impl PartialEq for Dms {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_degrees() == other.to_degrees()
+    }
+}
+impl PartialOrd for Dms {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.to_degrees().partial_cmp(&other.to_degrees())
+    }
+}
+
+/// Hemisphere, which determines the sign of [`Dms`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Hemisphere {
+    /// North latitude, positive.
+    North,
+    /// South latitude, negative.
+    South,
+    /// East longitude, positive.
+    East,
+    /// West longitude, negative.
+    West,
+}
+impl Hemisphere {
+    /// Returns `true` if south or west.
+    pub fn is_negative(self) -> bool {
+        matches!(self, Hemisphere::South | Hemisphere::West)
+    }
+}
+
+/// Errors in constructing [`Dms`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DmsError {
+    /// Minutes out of range.
+    Minutes(u32),
+    /// Seconds out of range.
+    Seconds(f64),
+    /// Degrees not finite or out of range.
+    Degrees(f64),
+}
+impl fmt::Display for DmsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DmsError::Minutes(m) => write!(f, "minutes out of range: {}", m),
+            DmsError::Seconds(s) => write!(f, "seconds out of range: {}", s),
+            DmsError::Degrees(deg) => write!(f, "degrees out of range: {}", deg),
+        }
     }
 }
+impl core::error::Error for DmsError {}
 
 /// Errors in input [LatLon].
 #[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DegreesError {
     /// The [LatLon] may be in the order of lon, lat.
     possibly_reversed: bool,
@@ -214,4 +404,4 @@ impl fmt::Display for DegreesError {
         Ok(())
     }
 }
-impl std::error::Error for DegreesError {}
+impl core::error::Error for DegreesError {}
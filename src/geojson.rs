@@ -0,0 +1,516 @@
+use std::{
+    fmt,
+    io::{self, BufReader, Read, Write},
+};
+
+use serde::{
+    de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    Deserializer, Serialize,
+};
+use serde_json::{Map, Value};
+
+use crate::{Crs, DatumKind, DegreesError, Transformer, TransformerError};
+
+/// Transformation of GeoJSON between datums.
+///
+/// 座標の軸順序は RFC 7946 に従い経度、緯度の順とする。3番目以降の要素 (高さ) はそのまま残す。
+/// `properties` などその他のメンバーは保持するが、変換後に合わなくなる `bbox` は取り除く。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{DatumKind, GeoJsonTransformer};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let input = r#"{"type": "FeatureCollection", "features": [{"type": "Feature",
+///     "geometry": {"type": "Point", "coordinates": [135.0, 35.0]}, "properties": {"name": "A"}}]}"#;
+/// let geojson = GeoJsonTransformer::new(DatumKind::Tokyo97, DatumKind::Jgd2000)?
+///     .with_method("method");
+/// let mut output = vec![];
+/// geojson.transform_collection(input.as_bytes(), &mut output)?;
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct GeoJsonTransformer {
+    transformer: Transformer,
+    method_key: Option<String>,
+}
+impl GeoJsonTransformer {
+    /// Constructs a [`GeoJsonTransformer`] from datum `from` to datum `to`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransformerError`] if there is no path between the datums.
+    pub fn new(from: DatumKind, to: DatumKind) -> Result<Self, TransformerError> {
+        let transformer = Transformer::new(Crs::Geographic(from), Crs::Geographic(to))?;
+        Ok(Self {
+            transformer,
+            method_key: None,
+        })
+    }
+
+    /// Returns self annotating each feature with the transformation methods used for its coordinates.
+    ///
+    /// `properties` の `key` に `"3-parameter+PatchJGD"` のような文字列を設定する。
+    /// 頂点によって方法が異なる場合は、現れた順に `", "` で区切って並べる。
+    /// 変換されなかった頂点の方法は空文字列となる。
+    pub fn with_method(mut self, key: impl Into<String>) -> Self {
+        self.method_key = Some(key.into());
+        self
+    }
+
+    /// Transforms a FeatureCollection from `reader` to `writer` feature by feature.
+    ///
+    /// 地物を1つずつ読み書きするため、全体をメモリに載せる必要はない。
+    /// 出力は改行や空白を含まない JSON となる。`writer` はバッファリングされていることが望ましい。
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GeoJsonError`] if the input is not a valid FeatureCollection,
+    /// a coordinate is out of range in degrees, or reading or writing fails.
+    /// 途中で失敗した場合、それまでに書き込んだ出力は不完全な JSON となる。
+    pub fn transform_collection<R: Read, W: Write>(
+        &self,
+        reader: R,
+        writer: W,
+    ) -> Result<(), GeoJsonError> {
+        let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+        let mut stream = Stream {
+            geojson: self,
+            writer,
+            error: None,
+        };
+        let ret = deserializer
+            .deserialize_map(&mut stream)
+            .and_then(|()| deserializer.end());
+        if let Some(error) = stream.error {
+            return Err(error);
+        }
+        ret.map_err(|e| GeoJsonError::new(None, ErrorKind::Json(e)))?;
+        stream
+            .writer
+            .flush()
+            .map_err(|e| GeoJsonError::new(None, ErrorKind::Io(e)))
+    }
+
+    /// Transforms a Feature in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GeoJsonError`] if the value is not a valid Feature or a coordinate is out of range in degrees.
+    pub fn transform_feature(&self, feature: &mut Value) -> Result<(), GeoJsonError> {
+        self.feature(feature)
+            .map_err(|kind| GeoJsonError::new(None, kind))
+    }
+
+    /// Transforms a Geometry in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GeoJsonError`] if the value is not a valid Geometry or a coordinate is out of range in degrees.
+    pub fn transform_geometry(&self, geometry: &mut Value) -> Result<(), GeoJsonError> {
+        self.geometry(geometry, &mut vec![])
+            .map_err(|kind| GeoJsonError::new(None, kind))
+    }
+
+    fn feature(&self, feature: &mut Value) -> Result<(), ErrorKind> {
+        let object = feature
+            .as_object_mut()
+            .filter(|object| object.get("type").and_then(Value::as_str) == Some("Feature"))
+            .ok_or(ErrorKind::Invalid("not a Feature"))?;
+        object.shift_remove("bbox");
+        let geometry = object
+            .get_mut("geometry")
+            .ok_or(ErrorKind::Invalid("missing geometry"))?;
+        let mut methods = vec![];
+        // 位置を持たない地物の geometry は null となる
+        if !geometry.is_null() {
+            self.geometry(geometry, &mut methods)?;
+        }
+        if let Some(key) = &self.method_key {
+            let properties = object.entry("properties").or_insert(Value::Null);
+            if properties.is_null() {
+                *properties = Value::Object(Map::new());
+            }
+            properties
+                .as_object_mut()
+                .ok_or(ErrorKind::Invalid("properties must be an object"))?
+                .insert(key.clone(), Value::from(methods.join(", ")));
+        }
+        Ok(())
+    }
+
+    /// Transforms a geometry, collecting the distinct methods used.
+    fn geometry(&self, geometry: &mut Value, methods: &mut Vec<String>) -> Result<(), ErrorKind> {
+        let object = geometry
+            .as_object_mut()
+            .ok_or(ErrorKind::Invalid("geometry must be an object"))?;
+        object.shift_remove("bbox");
+        // 座標の配列の入れ子の深さ
+        let depth = match object.get("type").and_then(Value::as_str) {
+            Some("Point") => 0,
+            Some("MultiPoint" | "LineString") => 1,
+            Some("MultiLineString" | "Polygon") => 2,
+            Some("MultiPolygon") => 3,
+            Some("GeometryCollection") => {
+                let geometries = object
+                    .get_mut("geometries")
+                    .and_then(Value::as_array_mut)
+                    .ok_or(ErrorKind::Invalid("missing geometries"))?;
+                return geometries
+                    .iter_mut()
+                    .try_for_each(|g| self.geometry(g, methods));
+            }
+            _ => return Err(ErrorKind::Invalid("unknown geometry type")),
+        };
+        let coordinates = object
+            .get_mut("coordinates")
+            .ok_or(ErrorKind::Invalid("missing coordinates"))?;
+        self.coordinates(coordinates, depth, methods)
+    }
+
+    fn coordinates(
+        &self,
+        coordinates: &mut Value,
+        depth: usize,
+        methods: &mut Vec<String>,
+    ) -> Result<(), ErrorKind> {
+        let array = coordinates
+            .as_array_mut()
+            .ok_or(ErrorKind::Invalid("coordinates must be arrays"))?;
+        if depth > 0 {
+            return array
+                .iter_mut()
+                .try_for_each(|c| self.coordinates(c, depth - 1, methods));
+        }
+        let (Some(lon), Some(lat)) = (
+            array.first().and_then(Value::as_f64),
+            array.get(1).and_then(Value::as_f64),
+        ) else {
+            return Err(ErrorKind::Invalid(
+                "position must have longitude and latitude",
+            ));
+        };
+        let ([lat, lon], used) = self
+            .transformer
+            .transform_with_methods([lat, lon])
+            .map_err(ErrorKind::Degrees)?;
+        let used = used.join("+");
+        if !methods.contains(&used) {
+            methods.push(used);
+        }
+        array[0] = Value::from(lon);
+        array[1] = Value::from(lat);
+        Ok(())
+    }
+}
+
+/// Errors in transforming GeoJSON.
+#[derive(Debug)]
+pub struct GeoJsonError {
+    feature: Option<usize>,
+    kind: ErrorKind,
+}
+impl GeoJsonError {
+    fn new(feature: Option<usize>, kind: ErrorKind) -> Self {
+        Self { feature, kind }
+    }
+
+    /// Returns the index of the offending feature in the FeatureCollection, if any.
+    pub fn feature(&self) -> Option<usize> {
+        self.feature
+    }
+}
+impl fmt::Display for GeoJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(index) = self.feature {
+            write!(f, "feature {}: ", index)?;
+        }
+        match &self.kind {
+            ErrorKind::Json(e) => write!(f, "{}", e),
+            ErrorKind::Io(e) => write!(f, "{}", e),
+            ErrorKind::Invalid(message) => f.write_str(message),
+            ErrorKind::Degrees(e) => write!(f, "{}", e),
+        }
+    }
+}
+impl std::error::Error for GeoJsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Json(e) => Some(e),
+            ErrorKind::Io(e) => Some(e),
+            ErrorKind::Invalid(_) => None,
+            ErrorKind::Degrees(e) => Some(e),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum ErrorKind {
+    Json(serde_json::Error),
+    Io(io::Error),
+    Invalid(&'static str),
+    Degrees(DegreesError),
+}
+
+/// Writes the FeatureCollection while deserializing it.
+///
+/// 失敗の原因は `error` に保持し、デシリアライザには空のエラーを返して中断させる。
+struct Stream<'a, W> {
+    geojson: &'a GeoJsonTransformer,
+    writer: W,
+    error: Option<GeoJsonError>,
+}
+impl<W: Write> Stream<'_, W> {
+    fn fail<E: de::Error>(&mut self, feature: Option<usize>, kind: ErrorKind) -> E {
+        self.error = Some(GeoJsonError::new(feature, kind));
+        E::custom("aborted")
+    }
+
+    fn write<E: de::Error>(&mut self, bytes: &[u8]) -> Result<(), E> {
+        let ret = self.writer.write_all(bytes);
+        ret.map_err(|e| self.fail(None, ErrorKind::Io(e)))
+    }
+
+    fn write_json<E: de::Error>(&mut self, value: &impl Serialize) -> Result<(), E> {
+        let ret = serde_json::to_writer(&mut self.writer, value);
+        ret.map_err(|e| self.fail(None, ErrorKind::Json(e)))
+    }
+}
+impl<'de, W: Write> Visitor<'de> for &mut Stream<'_, W> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a GeoJSON FeatureCollection")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        self.write(b"{")?;
+        let mut first = true;
+        let mut collection = false;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "bbox" {
+                map.next_value::<de::IgnoredAny>()?;
+                continue;
+            }
+            if !first {
+                self.write(b",")?;
+            }
+            first = false;
+            self.write_json(&key)?;
+            self.write(b":")?;
+            match key.as_str() {
+                "features" => map.next_value_seed(Features(&mut *self))?,
+                "type" => {
+                    let value: Value = map.next_value()?;
+                    if value != "FeatureCollection" {
+                        return Err(self.fail(None, ErrorKind::Invalid("not a FeatureCollection")));
+                    }
+                    collection = true;
+                    self.write_json(&value)?;
+                }
+                _ => {
+                    let value: Value = map.next_value()?;
+                    self.write_json(&value)?;
+                }
+            }
+        }
+        if !collection {
+            return Err(self.fail(None, ErrorKind::Invalid("not a FeatureCollection")));
+        }
+        self.write(b"}")
+    }
+}
+
+/// Streams the `features` array.
+struct Features<'a, 'b, W>(&'a mut Stream<'b, W>);
+impl<'de, W: Write> DeserializeSeed<'de> for Features<'_, '_, W> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+impl<'de, W: Write> Visitor<'de> for Features<'_, '_, W> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of features")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let stream = self.0;
+        stream.write(b"[")?;
+        let mut index = 0;
+        while let Some(mut feature) = seq.next_element::<Value>()? {
+            if index > 0 {
+                stream.write(b",")?;
+            }
+            if let Err(kind) = stream.geojson.feature(&mut feature) {
+                return Err(stream.fail(Some(index), kind));
+            }
+            stream.write_json(&feature)?;
+            index += 1;
+        }
+        stream.write(b"]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::{DatumKind, LatLon, Tokyo97};
+
+    use super::GeoJsonTransformer;
+
+    fn sut() -> GeoJsonTransformer {
+        GeoJsonTransformer::new(DatumKind::Tokyo97, DatumKind::Jgd2000).unwrap()
+    }
+
+    fn expected(lon: f64, lat: f64) -> [f64; 2] {
+        let LatLon(lat, lon) = Tokyo97::new(LatLon(lat, lon))
+            .unwrap()
+            .to_jgd2000()
+            .degrees();
+        [lon, lat]
+    }
+
+    #[test]
+    fn collection() {
+        let input = json!({
+            "type": "FeatureCollection",
+            "name": "sample",
+            "bbox": [135., 35., 135.1, 35.1],
+            "features": [
+                {
+                    "type": "Feature",
+                    "id": 1,
+                    "geometry": {"type": "Point", "coordinates": [135., 35., 12.5]},
+                    "properties": {"name": "A"}
+                },
+                {
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "Polygon",
+                        "coordinates": [[[135., 35.], [135.1, 35.], [135.1, 35.1], [135., 35.]]]
+                    },
+                    "properties": null
+                },
+                {"type": "Feature", "geometry": null, "properties": {}}
+            ]
+        });
+        let mut output = vec![];
+        sut()
+            .with_method("method")
+            .transform_collection(input.to_string().as_bytes(), &mut output)
+            .unwrap();
+        let output: Value = serde_json::from_slice(&output).unwrap();
+
+        assert_eq!(output["name"], "sample");
+        assert!(output.get("bbox").is_none());
+        let features = output["features"].as_array().unwrap();
+        assert_eq!(features.len(), 3);
+
+        let [lon, lat] = expected(135., 35.);
+        let keys: Vec<_> = features[0].as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["type", "id", "geometry", "properties"]);
+        assert_eq!(
+            features[0]["geometry"]["coordinates"],
+            json!([lon, lat, 12.5])
+        );
+        assert_eq!(
+            features[0]["properties"],
+            json!({"name": "A", "method": "3-parameter"})
+        );
+
+        let ring = &features[1]["geometry"]["coordinates"][0];
+        assert_eq!(ring[2], json!(expected(135.1, 35.1)));
+        assert_eq!(ring[3], json!([lon, lat]));
+        assert_eq!(features[1]["properties"], json!({"method": "3-parameter"}));
+        assert_eq!(features[2]["geometry"], Value::Null);
+    }
+
+    #[cfg(feature = "patchjgd")]
+    #[test]
+    fn method_per_feature() {
+        let sut = GeoJsonTransformer::new(DatumKind::Jgd2000, DatumKind::Jgd2011)
+            .unwrap()
+            .with_method("method");
+        let feature = |coordinates: Value| {
+            let mut feature = json!({
+                "type": "Feature",
+                "geometry": {"type": "LineString", "coordinates": coordinates},
+            });
+            sut.transform_feature(&mut feature).unwrap();
+            feature["properties"]["method"].take()
+        };
+        assert_eq!(
+            feature(json!([[140.87, 38.26], [140.88, 38.27]])),
+            "PatchJGD"
+        );
+        // パラメータのない頂点は変換されない
+        assert_eq!(feature(json!([[135., 35.]])), "");
+        assert_eq!(feature(json!([[140.87, 38.26], [135., 35.]])), "PatchJGD, ");
+    }
+
+    #[test]
+    fn geometry_collection() {
+        let mut geometry = json!({
+            "type": "GeometryCollection",
+            "geometries": [
+                {"type": "MultiPoint", "coordinates": [[135., 35.]]},
+                {"type": "MultiPolygon", "coordinates": [[[[135.1, 35.1]]]]}
+            ]
+        });
+        sut().transform_geometry(&mut geometry).unwrap();
+        assert_eq!(
+            geometry["geometries"][0]["coordinates"][0],
+            json!(expected(135., 35.))
+        );
+        assert_eq!(
+            geometry["geometries"][1]["coordinates"][0][0][0],
+            json!(expected(135.1, 35.1))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let transform = |input: Value| {
+            let mut output = vec![];
+            sut()
+                .transform_collection(input.to_string().as_bytes(), &mut output)
+                .unwrap_err()
+        };
+
+        let point = |lon, lat| json!({"type": "Feature", "geometry": {"type": "Point", "coordinates": [lon, lat]}});
+        let err = transform(json!({
+            "type": "FeatureCollection",
+            "features": [point(135., 35.), point(35., 135.)]
+        }));
+        assert_eq!(err.feature(), Some(1));
+        assert_eq!(
+            err.to_string(),
+            "feature 1: degrees out of range; may be lat and lon reversed?"
+        );
+
+        let err = transform(point(135., 35.));
+        assert_eq!(err.to_string(), "not a FeatureCollection");
+        let err = transform(json!({"features": []}));
+        assert_eq!(err.to_string(), "not a FeatureCollection");
+
+        let err =
+            transform(json!({"type": "FeatureCollection", "features": [{"type": "Feature"}]}));
+        assert_eq!(err.to_string(), "feature 0: missing geometry");
+
+        let mut output = vec![];
+        let err = sut()
+            .transform_collection(
+                &b"{\"type\": \"FeatureCollection\", \"features\": ["[..],
+                &mut output,
+            )
+            .unwrap_err();
+        assert!(err.to_string().starts_with("EOF while parsing"));
+    }
+}
@@ -0,0 +1,267 @@
+use std::fmt;
+
+use geo_types::{
+    Coord, Geometry, GeometryCollection, Line, LineString, MultiLineString, MultiPoint,
+    MultiPolygon, Point, Polygon, Rect, Triangle,
+};
+
+use crate::{Datum, DegreesError, LatLon, TransformTo};
+
+/// Converts to [`Coord`] with longitude as `x` and latitude as `y`.
+impl From<LatLon> for Coord {
+    fn from(degrees: LatLon) -> Self {
+        let LatLon(lat, lon) = degrees;
+        Coord { x: lon, y: lat }
+    }
+}
+
+/// Converts from [`Coord`] with longitude as `x` and latitude as `y`.
+impl From<Coord> for LatLon {
+    fn from(coord: Coord) -> Self {
+        LatLon(coord.y, coord.x)
+    }
+}
+
+/// Converts to [`Point`] with longitude as `x` and latitude as `y`.
+impl From<LatLon> for Point {
+    fn from(degrees: LatLon) -> Self {
+        Point(degrees.into())
+    }
+}
+
+/// Converts from [`Point`] with longitude as `x` and latitude as `y`.
+impl From<Point> for LatLon {
+    fn from(point: Point) -> Self {
+        point.0.into()
+    }
+}
+
+/// Transformation of `geo-types` geometries between datums.
+///
+/// 座標は `x` が経度、`y` が緯度。全ての頂点を変換し、最初に範囲外だった頂点で失敗する。
+///
+/// [`Rect`] は変換後に軸に沿った長方形とならないため、[`Geometry::Rect`] は [`Geometry::Polygon`] となる。
+///
+/// # Examples
+///
+/// ```
+/// use geo_types::{line_string, LineString};
+/// use jgd::{Jgd2000, TransformGeometry, Tokyo97};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let tokyo97: LineString = line_string![(x: 135.0, y: 35.0), (x: 135.1, y: 35.1)];
+/// let jgd2000 = tokyo97.transform_datum::<Tokyo97, Jgd2000>()?;
+/// #   Ok(())
+/// # }
+/// ```
+pub trait TransformGeometry: Sized {
+    /// Transforms all vertices from datum `S` to datum `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GeometryError`] with the index of the first vertex out of range in degrees.
+    fn transform_datum<S, T>(&self) -> Result<Self, GeometryError>
+    where
+        S: Datum + TransformTo<T>,
+        T: Datum;
+}
+
+/// Errors in transforming a geometry.
+#[derive(Debug, PartialEq)]
+pub struct GeometryError {
+    index: usize,
+    source: DegreesError,
+}
+impl GeometryError {
+    /// Returns the index of the vertex in the order of traversal.
+    ///
+    /// ポリゴンでは外周、内周の順に数える。閉じた環の終点も1つの頂点として数える。
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+impl fmt::Display for GeometryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "vertex {}: {}", self.index, self.source)
+    }
+}
+impl std::error::Error for GeometryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Applies a function to each vertex counting the index.
+struct Vertices<F> {
+    index: usize,
+    f: F,
+}
+impl<F: FnMut(LatLon) -> Result<LatLon, DegreesError>> Vertices<F> {
+    fn coord(&mut self, coord: Coord) -> Result<Coord, GeometryError> {
+        let index = self.index;
+        self.index += 1;
+        let degrees = (self.f)(coord.into()).map_err(|source| GeometryError { index, source })?;
+        Ok(degrees.into())
+    }
+
+    fn geometry(&mut self, geometry: &Geometry) -> Result<Geometry, GeometryError> {
+        Ok(match geometry {
+            Geometry::Point(g) => Geometry::Point(self.point(g)?),
+            Geometry::Line(g) => Geometry::Line(self.line(g)?),
+            Geometry::LineString(g) => Geometry::LineString(self.line_string(g)?),
+            Geometry::Polygon(g) => Geometry::Polygon(self.polygon(g)?),
+            Geometry::MultiPoint(g) => Geometry::MultiPoint(self.multi_point(g)?),
+            Geometry::MultiLineString(g) => Geometry::MultiLineString(self.multi_line_string(g)?),
+            Geometry::MultiPolygon(g) => Geometry::MultiPolygon(self.multi_polygon(g)?),
+            Geometry::GeometryCollection(g) => {
+                Geometry::GeometryCollection(self.geometry_collection(g)?)
+            }
+            Geometry::Rect(g) => Geometry::Polygon(self.rect(g)?),
+            Geometry::Triangle(g) => Geometry::Triangle(self.triangle(g)?),
+        })
+    }
+
+    fn point(&mut self, point: &Point) -> Result<Point, GeometryError> {
+        Ok(Point(self.coord(point.0)?))
+    }
+
+    fn line(&mut self, line: &Line) -> Result<Line, GeometryError> {
+        Ok(Line::new(self.coord(line.start)?, self.coord(line.end)?))
+    }
+
+    fn line_string(&mut self, line_string: &LineString) -> Result<LineString, GeometryError> {
+        let coords = line_string.0.iter().map(|c| self.coord(*c));
+        Ok(LineString(coords.collect::<Result<_, _>>()?))
+    }
+
+    fn polygon(&mut self, polygon: &Polygon) -> Result<Polygon, GeometryError> {
+        let exterior = self.line_string(polygon.exterior())?;
+        let interiors = polygon
+            .interiors()
+            .iter()
+            .map(|ring| self.line_string(ring));
+        Ok(Polygon::new(exterior, interiors.collect::<Result<_, _>>()?))
+    }
+
+    fn multi_point(&mut self, multi: &MultiPoint) -> Result<MultiPoint, GeometryError> {
+        let points = multi.0.iter().map(|g| self.point(g));
+        Ok(MultiPoint(points.collect::<Result<_, _>>()?))
+    }
+
+    fn multi_line_string(
+        &mut self,
+        multi: &MultiLineString,
+    ) -> Result<MultiLineString, GeometryError> {
+        let line_strings = multi.0.iter().map(|g| self.line_string(g));
+        Ok(MultiLineString(line_strings.collect::<Result<_, _>>()?))
+    }
+
+    fn multi_polygon(&mut self, multi: &MultiPolygon) -> Result<MultiPolygon, GeometryError> {
+        let polygons = multi.0.iter().map(|g| self.polygon(g));
+        Ok(MultiPolygon(polygons.collect::<Result<_, _>>()?))
+    }
+
+    fn geometry_collection(
+        &mut self,
+        collection: &GeometryCollection,
+    ) -> Result<GeometryCollection, GeometryError> {
+        let geometries = collection.0.iter().map(|g| self.geometry(g));
+        Ok(GeometryCollection(geometries.collect::<Result<_, _>>()?))
+    }
+
+    fn rect(&mut self, rect: &Rect) -> Result<Polygon, GeometryError> {
+        self.polygon(&rect.to_polygon())
+    }
+
+    fn triangle(&mut self, triangle: &Triangle) -> Result<Triangle, GeometryError> {
+        let [a, b, c] = triangle.to_array();
+        Ok(Triangle::new(
+            self.coord(a)?,
+            self.coord(b)?,
+            self.coord(c)?,
+        ))
+    }
+}
+
+macro_rules! impl_transform_geometry {
+    ($($ty:ident => $method:ident,)*) => {
+        $(
+            impl TransformGeometry for $ty {
+                fn transform_datum<S, T>(&self) -> Result<Self, GeometryError>
+                where
+                    S: Datum + TransformTo<T>,
+                    T: Datum,
+                {
+                    let mut vertices = Vertices {
+                        index: 0,
+                        f: |degrees: LatLon| -> Result<LatLon, DegreesError> {
+                            Ok(S::new(degrees)?.transform_to::<T>().degrees())
+                        },
+                    };
+                    vertices.$method(self)
+                }
+            }
+        )*
+    };
+}
+impl_transform_geometry! {
+    Geometry => geometry,
+    Point => point,
+    Line => line,
+    LineString => line_string,
+    Polygon => polygon,
+    MultiPoint => multi_point,
+    MultiLineString => multi_line_string,
+    MultiPolygon => multi_polygon,
+    GeometryCollection => geometry_collection,
+    Triangle => triangle,
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{coord, line_string, polygon, Coord, Geometry, LineString, Point, Rect};
+
+    use crate::{Jgd2000, LatLon, Tokyo97, TransformGeometry};
+
+    #[test]
+    fn axis_order() {
+        let coord: Coord = LatLon(35., 135.).into();
+        assert_eq!(coord, coord! { x: 135., y: 35. });
+        assert_eq!(LatLon::from(coord), LatLon(35., 135.));
+        assert_eq!(LatLon::from(Point::new(135., 35.)), LatLon(35., 135.));
+    }
+
+    #[test]
+    fn transform() {
+        let line: LineString = line_string![(x: 135., y: 35.), (x: 135.1, y: 35.1)];
+        let ret = line.transform_datum::<Tokyo97, Jgd2000>().unwrap();
+        for (ret, src) in ret.0.iter().zip(&line.0) {
+            let expected = Tokyo97::new((*src).into()).unwrap().to_jgd2000().degrees();
+            assert_eq!(LatLon::from(*ret), expected);
+        }
+    }
+
+    #[test]
+    fn offending_vertex() {
+        let polygon = polygon![
+            exterior: [(x: 135., y: 35.), (x: 135.1, y: 35.), (x: 135.1, y: 35.1)],
+            interiors: [[(x: 135.05, y: 35.05), (x: 35.06, y: 135.05), (x: 135.06, y: 35.06)]],
+        ];
+        let err = polygon.transform_datum::<Tokyo97, Jgd2000>().unwrap_err();
+        // 外周は閉じられて4頂点となる
+        assert_eq!(err.index(), 5);
+        assert_eq!(
+            err.to_string(),
+            "vertex 5: degrees out of range; may be lat and lon reversed?"
+        );
+    }
+
+    #[test]
+    fn rect_to_polygon() {
+        let rect = Rect::new(coord! { x: 135., y: 35. }, coord! { x: 135.1, y: 35.1 });
+        let ret = Geometry::Rect(rect)
+            .transform_datum::<Tokyo97, Jgd2000>()
+            .unwrap();
+        assert!(matches!(ret, Geometry::Polygon(_)));
+    }
+}
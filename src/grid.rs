@@ -3,6 +3,9 @@ use crate::{
     LatLon,
 };
 
+#[cfg(not(any(feature = "std", test)))]
+use crate::math::Float;
+
 /// 日本測地系から世界測地系への座標変換パラメータ。
 ///
 /// たとえ陸地であっても、無人島や、後年に埋め立てられた沿岸部などには、パラメータが存在しない。
@@ -22,11 +25,62 @@ pub const TOUHOKUTAIHEIYOUOKI2011: Grid = crate::par::TOUHOKUTAIHEIYOUOKI2011.to
 /// Parameters grid.
 pub struct Grid<'a> {
     dots: &'a [Dot],
+    spacing: Spacing,
 }
 impl<'a> Grid<'a> {
+    /// Constructs a [`Grid`] with 3rd mesh spacing.
     #[allow(dead_code)]
     pub(crate) const fn new(dots: &'a [Dot]) -> Self {
-        Self { dots }
+        Self::with_spacing(dots, Spacing::MESH3)
+    }
+
+    /// Constructs a [`Grid`] of the dots sorted by the node, latitude first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jgd::{Dot, Grid, LatLon, Spacing};
+    ///
+    /// const DOTS: &[Dot] = &[
+    ///     Dot::new([428, 1117], [1000, 2000]),
+    ///     Dot::new([428, 1118], [1000, 2000]),
+    ///     Dot::new([429, 1117], [1000, 2000]),
+    ///     Dot::new([429, 1118], [1000, 2000]),
+    /// ];
+    /// let grid = Grid::with_spacing(DOTS, Spacing::MESH2);
+    /// let shift = grid.bilinear(LatLon(35.7, 139.7));
+    /// # assert!(shift.is_some());
+    /// ```
+    pub const fn with_spacing(dots: &'a [Dot], spacing: Spacing) -> Self {
+        Self { dots, spacing }
+    }
+
+    /// Returns the southwest corner of the node `(0, 0)` in degrees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jgd::{LatLon, TOUHOKUTAIHEIYOUOKI2011};
+    ///
+    /// # assert_eq!(TOUHOKUTAIHEIYOUOKI2011.origin(), LatLon(0.0, 0.0));
+    /// let LatLon(lat, lon) = TOUHOKUTAIHEIYOUOKI2011.origin();
+    /// ```
+    pub fn origin(&self) -> LatLon {
+        self.spacing.origin
+    }
+
+    /// Returns the intervals between nodes in seconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jgd::{LatLon, TOUHOKUTAIHEIYOUOKI2011};
+    ///
+    /// # assert_eq!(TOUHOKUTAIHEIYOUOKI2011.spacing(), LatLon(30.0, 45.0));
+    /// let LatLon(lat, lon) = TOUHOKUTAIHEIYOUOKI2011.spacing();
+    /// ```
+    pub fn spacing(&self) -> LatLon {
+        self.spacing.secs
     }
 
     /// Get a shift parameter for coordinate in degrees with bilinear interpolation.
@@ -44,33 +98,39 @@ impl<'a> Grid<'a> {
     /// # assert!(shift.is_some());
     /// ```
     pub fn bilinear(&self, degrees: LatLon) -> Option<LatLon> {
-        // > 地域毎の変換パラメータの格子点は, 3 次メッシュの中央ではなく, 南西隅に対応する (飛田, 2001)
-        let mesh = Mesh3::floor(degrees);
-        let i = self.search_after(0, mesh)?;
-        let sw_shift = self.dots[i].shift;
-
-        let i = self.search_at(i + 1, mesh.east())?;
-        let se_shift = self.dots[i].shift;
+        let [sw, se, nw, ne] = self.corners(degrees)?;
+        let weighted = |(i, LatLon(lat_weight, lon_weight)): (usize, LatLon)| {
+            self.dots[i].shift.to_degree() * lat_weight * lon_weight
+        };
 
-        let i = self.search_after(i + 1, mesh.north())?;
-        let nw_shift = self.dots[i].shift;
+        // weighted mean
+        let shift = weighted(sw) + weighted(se) + weighted(nw) + weighted(ne);
 
-        let i = self.search_at(i + 1, mesh.north().east())?;
-        let ne_shift = self.dots[i].shift;
+        Some(shift)
+    }
 
-        let LatLon(n_weight, e_weight) = mesh.diagonal_weight(degrees);
-        let LatLon(s_weight, w_weight) = mesh.north().east().diagonal_weight(degrees);
+    /// Returns the indices of the dots at the southwest, southeast, northwest and northeast
+    /// corners of the mesh that `degrees` belongs, with the weights in latitude and longitude.
+    pub(crate) fn corners(&self, degrees: LatLon) -> Option<[(usize, LatLon); 4]> {
+        // > 地域毎の変換パラメータの格子点は, 3 次メッシュの中央ではなく, 南西隅に対応する (飛田, 2001)
+        let mesh = self.spacing.floor(degrees);
+        let sw = self.search_after(0, mesh)?;
+        let se = self.search_at(sw + 1, mesh.east())?;
+        let nw = self.search_after(se + 1, mesh.north())?;
+        let ne = self.search_at(nw + 1, mesh.north().east())?;
 
-        // weighted mean
-        let shift = sw_shift.to_degree() * s_weight * w_weight
-            + se_shift.to_degree() * s_weight * e_weight
-            + nw_shift.to_degree() * n_weight * w_weight
-            + ne_shift.to_degree() * n_weight * e_weight;
+        let LatLon(n_weight, e_weight) = self.spacing.diagonal_weight(mesh, degrees);
+        let LatLon(s_weight, w_weight) = self.spacing.diagonal_weight(mesh.north().east(), degrees);
 
-        Some(shift)
+        Some([
+            (sw, LatLon(s_weight, w_weight)),
+            (se, LatLon(s_weight, e_weight)),
+            (nw, LatLon(n_weight, w_weight)),
+            (ne, LatLon(n_weight, e_weight)),
+        ])
     }
 
-    fn search_after(&self, first: usize, query: Mesh3) -> Option<usize> {
+    fn search_after(&self, first: usize, query: Node) -> Option<usize> {
         self.dots
             .get(first..)?
             .binary_search_by_key(&query, |dot| dot.mesh)
@@ -78,7 +138,7 @@ impl<'a> Grid<'a> {
             .map(|i| i + first)
     }
 
-    fn search_at(&self, index: usize, query: Mesh3) -> Option<usize> {
+    fn search_at(&self, index: usize, query: Node) -> Option<usize> {
         (self.dots.get(index)?.mesh == query).then_some(index)
     }
 
@@ -88,50 +148,130 @@ impl<'a> Grid<'a> {
     }
 }
 
+/// A node of [`Grid`] and its shift parameter.
 #[derive(Debug, Clone, Copy)]
 #[repr(C)]
 pub struct Dot {
-    mesh: Mesh3,
+    mesh: Node,
     shift: MicroSecond,
 }
+impl Dot {
+    /// Constructs a [`Dot`] of the node `[lat, lon]` counted from the origin,
+    /// with the shift `[lat, lon]` in microseconds.
+    pub const fn new(node: [i16; 2], micro_secs: [i32; 2]) -> Self {
+        Self {
+            mesh: Node {
+                lat: node[0],
+                lon: node[1],
+            },
+            shift: MicroSecond {
+                lat: micro_secs[0],
+                lon: micro_secs[1],
+            },
+        }
+    }
+}
 
-/// Serial number of Japanese MESH3 grids starting from 0 degree.
+/// Serial number of grid nodes starting from the origin.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(C)]
-struct Mesh3 {
+pub(crate) struct Node {
     lat: i16,
     lon: i16,
 }
-impl Mesh3 {
-    const LAT_SEC: f64 = 30.;
-    const LON_SEC: f64 = 45.;
-
-    /// Evaluate the southwest of the mesh containing `p`.
-    fn floor(degrees: LatLon) -> Self {
-        // "saturating cast" since Rust 1.45.0
-        // https://blog.rust-lang.org/2020/07/16/Rust-1.45.0.html#fixing-unsoundness-in-casts
-        let lat = (degrees.lat() * 120.) as i16;
-        let lon = (degrees.lon() * 80.) as i16;
-        Self { lat, lon }
-    }
-    fn diagonal_weight(self, p: LatLon) -> LatLon {
-        let diff_secs = (p - self.to_degree()).map(|x| x.abs() * SECS);
-        let weight_lat = diff_secs.lat() / Self::LAT_SEC;
-        let weight_lon = diff_secs.lon() / Self::LON_SEC;
-        LatLon(weight_lat, weight_lon)
+impl Node {
+    #[cfg(feature = "std")]
+    pub(crate) fn to_array(self) -> [i16; 2] {
+        [self.lat, self.lon]
     }
-    fn north(mut self) -> Self {
+    pub(crate) fn north(mut self) -> Self {
         self.lat += 1;
         self
     }
-    fn east(mut self) -> Self {
+    pub(crate) fn east(mut self) -> Self {
         self.lon += 1;
         self
     }
-    fn to_degree(self) -> LatLon {
-        let lat = f64::from(self.lat) * Self::LAT_SEC;
-        let lon = f64::from(self.lon) * Self::LON_SEC;
-        LatLon(lat, lon) / SECS
+}
+
+/// Position and intervals of grid nodes.
+///
+/// 格子点 `(0, 0)` の南西隅の経緯度と、格子点の間隔 (秒)。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spacing {
+    /// Southwest corner of the node `(0, 0)` in degrees.
+    origin: LatLon,
+    /// Intervals in seconds.
+    secs: LatLon,
+}
+impl Spacing {
+    /// 2nd mesh, 5' x 7.5'.
+    pub const MESH2: Self = Self::new(LatLon(0., 0.), LatLon(300., 450.));
+
+    /// 3rd mesh, 30" x 45".
+    pub const MESH3: Self = Self::new(LatLon(0., 0.), LatLon(30., 45.));
+
+    /// Constructs a [`Spacing`] of the southwest corner of the node `(0, 0)` in degrees
+    /// and the intervals in seconds.
+    pub const fn new(origin: LatLon, secs: LatLon) -> Self {
+        Self { origin, secs }
+    }
+
+    /// Evaluate the southwest of the node containing `p`.
+    pub(crate) fn floor(&self, degrees: LatLon) -> Node {
+        let per_degree = self.secs.map(|x| SECS / x);
+        // "saturating cast" since Rust 1.45.0
+        // https://blog.rust-lang.org/2020/07/16/Rust-1.45.0.html#fixing-unsoundness-in-casts
+        let lat = ((degrees.lat() - self.origin.lat()) * per_degree.lat()).floor() as i16;
+        let lon = ((degrees.lon() - self.origin.lon()) * per_degree.lon()).floor() as i16;
+        Node { lat, lon }
+    }
+
+    pub(crate) fn diagonal_weight(&self, node: Node, p: LatLon) -> LatLon {
+        let diff_secs = (p - self.node_degrees(node)).map(|x| x.abs() * SECS);
+        let weight_lat = diff_secs.lat() / self.secs.lat();
+        let weight_lon = diff_secs.lon() / self.secs.lon();
+        LatLon(weight_lat, weight_lon)
+    }
+
+    pub(crate) fn node_degrees(&self, node: Node) -> LatLon {
+        let lat = f64::from(node.lat) * self.secs.lat();
+        let lon = f64::from(node.lon) * self.secs.lon();
+        self.origin + LatLon(lat, lon) / SECS
+    }
+
+    /// Parses 6-digit (2nd) or 8-digit (3rd) mesh code of the southwest corner of a node.
+    #[cfg(feature = "std")]
+    pub(crate) fn parse_mesh_code(&self, code: &str) -> Option<Node> {
+        let mut digits = [0; 8];
+        if code.len() != 6 && code.len() != 8 {
+            return None;
+        }
+        for (digit, b) in digits.iter_mut().zip(code.bytes()) {
+            *digit = b.is_ascii_digit().then(|| i32::from(b - b'0'))?;
+        }
+        let (lat3, lon3) = (digits[6], digits[7]);
+        let (lat2, lon2) = (digits[4], digits[5]);
+        if lat2 > 7 || lon2 > 7 {
+            return None;
+        }
+        let lat1 = digits[0] * 10 + digits[1];
+        let lon1 = digits[2] * 10 + digits[3] + 100;
+
+        // 1st mesh 40' x 1°, 2nd mesh 5' x 7.5', 3rd mesh 30" x 45"
+        let secs = LatLon(
+            lat1 * 2400 + lat2 * 300 + lat3 * 30,
+            lon1 * 3600 + lon2 * 450 + lon3 * 45,
+        );
+        let LatLon(lat, lon) = secs.map(f64::from) - self.origin * SECS;
+        let LatLon(lat, lon) = LatLon(lat / self.secs.lat(), lon / self.secs.lon());
+        if lat.fract() != 0. || lon.fract() != 0. {
+            return None;
+        }
+        Some(Node {
+            lat: i16::try_from(lat as i64).ok()?,
+            lon: i16::try_from(lon as i64).ok()?,
+        })
     }
 }
 
@@ -157,7 +297,7 @@ mod tests {
         Grid, LatLon,
     };
 
-    use super::{Dot, Mesh3, MicroSecond};
+    use super::{Dot, MicroSecond, Node, Spacing};
 
     #[cfg(feature = "tky2jgd")]
     #[test]
@@ -190,33 +330,33 @@ mod tests {
     //  (0,-6) -- (6, 0)
     const SMALLEST: &[Dot] = &[
         Dot {
-            mesh: Mesh3 { lon: 0, lat: 0 },
+            mesh: Node { lon: 0, lat: 0 },
             shift: MicroSecond { lon: 0, lat: -6 },
         },
         Dot {
-            mesh: Mesh3 { lon: 1, lat: 0 },
+            mesh: Node { lon: 1, lat: 0 },
             shift: MicroSecond { lon: 6, lat: 0 },
         },
         Dot {
-            mesh: Mesh3 { lon: 0, lat: 1 },
+            mesh: Node { lon: 0, lat: 1 },
             shift: MicroSecond { lon: 0, lat: 0 },
         },
         Dot {
-            mesh: Mesh3 { lon: 1, lat: 1 },
+            mesh: Node { lon: 1, lat: 1 },
             shift: MicroSecond { lon: 6, lat: 6 },
         },
     ];
 
     #[test]
     fn interpolate_corner() {
-        let sut = Grid::new(&SMALLEST);
+        let sut = Grid::new(SMALLEST);
         let ret = sut.bilinear(LatLon(0.0, 0.0)).unwrap();
         assert_eq!(ret, LatLon(-6. / MICRO_SECS, 0.0));
     }
 
     #[test]
     fn interpolate_middle() {
-        let sut = Grid::new(&SMALLEST);
+        let sut = Grid::new(SMALLEST);
         let exp = LatLon(-2., 2.) / MICRO_SECS;
         let ret = sut.bilinear(LatLon(10., 15.) / SECS).unwrap();
         assert_ulps_eq!(exp.lat(), ret.lat());
@@ -225,15 +365,46 @@ mod tests {
 
     #[test]
     fn interpolate_out_of_grid() {
-        let sut = Grid::new(&SMALLEST);
+        let sut = Grid::new(SMALLEST);
         let ret = sut.bilinear(LatLon(30.001, 45.001) / SECS);
         assert_eq!(ret, None);
     }
 
     #[test]
     fn interpolate_almost_out_of_grid() {
-        let sut = Grid::new(&SMALLEST);
+        let sut = Grid::new(SMALLEST);
         let ret = sut.bilinear(LatLon(29.999, 44.999) / SECS);
         assert_ne!(ret, None);
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn mesh_code() {
+        let parse = |spacing: Spacing, code| {
+            let node = spacing.parse_mesh_code(code)?;
+            Some((node.lat, node.lon))
+        };
+        assert_eq!(parse(Spacing::MESH3, "53394611"), Some((4281, 11181)));
+        assert_eq!(parse(Spacing::MESH3, "533946"), Some((4280, 11180)));
+        assert_eq!(parse(Spacing::MESH2, "53394600"), Some((428, 1118)));
+        assert_eq!(parse(Spacing::MESH2, "53394611"), None);
+        assert_eq!(parse(Spacing::MESH3, "53398811"), None);
+    }
+
+    #[test]
+    fn interpolate_coarse_spacing() {
+        let sut = Grid {
+            dots: SMALLEST,
+            spacing: Spacing::new(LatLon(-1., -1.), LatLon(300., 450.)),
+        };
+        let exp = LatLon(-2., 2.) / MICRO_SECS;
+        let ret = sut
+            .bilinear(LatLon(-1., -1.) + LatLon(100., 150.) / SECS)
+            .unwrap();
+        assert_ulps_eq!(exp.lat(), ret.lat());
+        assert_ulps_eq!(exp.lon(), ret.lon());
+        assert_eq!(sut.bilinear(LatLon(0.0, 0.0)), None);
+        // 原点の南西は格子点 (-1, -1) に属する
+        assert_eq!(sut.bilinear(LatLon(-1., -1.) - LatLon(1., 1.) / SECS), None);
+    }
 }
@@ -0,0 +1,242 @@
+use crate::ECEF;
+
+const ARC_SECS: f64 = 180. * 3600. / core::f64::consts::PI;
+const PPM: f64 = 1_000_000.;
+
+/// Sign convention of the rotation parameters in [`Helmert`].
+///
+/// 同じ回転パラメータでも、規約によって回転の向きが逆になる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RotationConvention {
+    /// Position vector rotation, used by IERS and ISO 19111 (EPSG: 9606).
+    ///
+    /// 座標値ではなく位置ベクトルを回転させる規約。
+    #[default]
+    PositionVector,
+
+    /// Coordinate frame rotation, used by IOGP (EPSG: 9607).
+    ///
+    /// 位置ベクトルではなく座標軸を回転させる規約。
+    CoordinateFrame,
+}
+
+/// 7-parameter Helmert transformation between [`ECEF`] coordinates.
+///
+/// 3つの平行移動、3つの回転、1つの縮尺の各パラメータによる相似変換。
+/// 回転は微小角として線形化される。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{Helmert, RotationConvention, ECEF};
+///
+/// // WGS 72 to WGS 84 (EPSG: 1238)
+/// let helmert = Helmert::new(ECEF::new(0.0, 0.0, 4.5))
+///     .with_rotation([0.0, 0.0, 0.554], RotationConvention::PositionVector)
+///     .with_scale(0.219);
+/// let wgs84 = helmert.transform(ECEF::new(3657660.66, 255768.55, 5201382.11));
+/// let wgs72 = helmert.inverse(wgs84);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Helmert {
+    translation: ECEF,
+    rotation: [f64; 3],
+    convention: RotationConvention,
+    scale: f64,
+}
+impl Helmert {
+    /// Constructs a [`Helmert`] with translation in meters and without rotation and scale.
+    ///
+    /// いわゆる3パラメータ変換。
+    pub const fn new(translation: ECEF) -> Self {
+        Self {
+            translation,
+            rotation: [0., 0., 0.],
+            convention: RotationConvention::PositionVector,
+            scale: 0.,
+        }
+    }
+
+    /// Returns self with rotation around X, Y and Z axes in arc seconds.
+    pub const fn with_rotation(
+        mut self,
+        arc_secs: [f64; 3],
+        convention: RotationConvention,
+    ) -> Self {
+        self.rotation = arc_secs;
+        self.convention = convention;
+        self
+    }
+
+    /// Returns self with scale difference in ppm.
+    pub const fn with_scale(mut self, ppm: f64) -> Self {
+        self.scale = ppm;
+        self
+    }
+
+    /// Returns translation in meters.
+    pub fn translation(&self) -> ECEF {
+        self.translation
+    }
+
+    /// Returns rotation around X, Y and Z axes in arc seconds.
+    pub fn rotation(&self) -> [f64; 3] {
+        self.rotation
+    }
+
+    /// Returns the sign convention of [`Helmert::rotation`].
+    pub fn convention(&self) -> RotationConvention {
+        self.convention
+    }
+
+    /// Returns scale difference in ppm.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Transforms a coordinate from the source frame to the target frame.
+    pub fn transform(&self, ecef: ECEF) -> ECEF {
+        let m = self.matrix();
+        let [x, y, z] = m.map(|row| ECEF::from(row).dot(ecef));
+        self.translation + ECEF::new(x, y, z)
+    }
+
+    /// Transforms a coordinate from the target frame to the source frame.
+    ///
+    /// [`Helmert::transform`] の厳密な逆変換。
+    /// パラメータの符号を反転させた近似ではなく、変換行列の逆行列が用いられる。
+    pub fn inverse(&self, ecef: ECEF) -> ECEF {
+        let m = inverse_matrix(self.matrix());
+        let v = ecef - self.translation;
+        let [x, y, z] = m.map(|row| ECEF::from(row).dot(v));
+        ECEF::new(x, y, z)
+    }
+
+    /// Matrix of rotation and scale in the position vector convention.
+    fn matrix(&self) -> [[f64; 3]; 3] {
+        let sign = match self.convention {
+            RotationConvention::PositionVector => 1.,
+            RotationConvention::CoordinateFrame => -1.,
+        };
+        let [rx, ry, rz] = self.rotation.map(|r| sign * r / ARC_SECS);
+        let m = [[1., -rz, ry], [rz, 1., -rx], [-ry, rx, 1.]];
+        let s = 1. + self.scale / PPM;
+        m.map(|row| row.map(|x| x * s))
+    }
+}
+
+/// 14-parameter time-dependent Helmert transformation.
+///
+/// 7つのパラメータと、それぞれの1年あたりの変化率からなる。
+/// 元期 `epoch` から `t` 年後のパラメータは `helmert + rate * (t - epoch)` となる。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{Helmert, RotationConvention, TimeDependentHelmert, ECEF};
+///
+/// let helmert = TimeDependentHelmert::new(
+///     Helmert::new(ECEF::new(0.0016, 0.0019, 0.0024)).with_scale(-0.00002),
+///     Helmert::new(ECEF::new(0.0, 0.0, -0.0001)).with_scale(0.00003),
+///     2010.0,
+/// );
+/// let ecef = helmert.at(2024.5).transform(ECEF::new(-3959340.203, 3352854.274, 3697471.413));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TimeDependentHelmert {
+    helmert: Helmert,
+    rate: Helmert,
+    epoch: f64,
+}
+impl TimeDependentHelmert {
+    /// Constructs a [`TimeDependentHelmert`] with parameters at `epoch` and their rates per year.
+    ///
+    /// `rate` の回転の規約は無視され、`helmert` の規約に従う。
+    pub const fn new(helmert: Helmert, rate: Helmert, epoch: f64) -> Self {
+        Self {
+            helmert,
+            rate,
+            epoch,
+        }
+    }
+
+    /// Returns the reference epoch in decimal years.
+    pub fn epoch(&self) -> f64 {
+        self.epoch
+    }
+
+    /// Returns parameters at `epoch` in decimal years.
+    pub fn at(&self, epoch: f64) -> Helmert {
+        let dt = epoch - self.epoch;
+        let Helmert {
+            translation,
+            rotation,
+            convention,
+            scale,
+        } = self.helmert;
+        let mut r = rotation;
+        for (r, rate) in r.iter_mut().zip(self.rate.rotation) {
+            *r += rate * dt;
+        }
+        Helmert {
+            translation: translation + self.rate.translation * dt,
+            rotation: r,
+            convention,
+            scale: scale + self.rate.scale * dt,
+        }
+    }
+}
+
+fn inverse_matrix(m: [[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let [[a, b, c], [d, e, f], [g, h, i]] = m;
+    let cofactor = [
+        [e * i - f * h, c * h - b * i, b * f - c * e],
+        [f * g - d * i, a * i - c * g, c * d - a * f],
+        [d * h - e * g, b * g - a * h, a * e - b * d],
+    ];
+    let det = a * cofactor[0][0] + b * cofactor[1][0] + c * cofactor[2][0];
+    cofactor.map(|row| row.map(|x| x / det))
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::ECEF;
+
+    use super::{Helmert, RotationConvention};
+
+    // IOGP Guidance Note 7-2, 2.4.3.3
+    const WGS72: ECEF = ECEF::new(3657660.66, 255768.55, 5201382.11);
+    const WGS84: ECEF = ECEF::new(3657660.78, 255778.43, 5201387.75);
+
+    fn assert_ecef(left: ECEF, right: ECEF, epsilon: f64) {
+        assert_abs_diff_eq!(left.x(), right.x(), epsilon = epsilon);
+        assert_abs_diff_eq!(left.y(), right.y(), epsilon = epsilon);
+        assert_abs_diff_eq!(left.z(), right.z(), epsilon = epsilon);
+    }
+
+    #[test]
+    fn position_vector() {
+        let sut = Helmert::new(ECEF::new(0.0, 0.0, 4.5))
+            .with_rotation([0.0, 0.0, 0.554], RotationConvention::PositionVector)
+            .with_scale(0.219);
+        assert_ecef(sut.transform(WGS72), WGS84, 0.01);
+    }
+
+    #[test]
+    fn coordinate_frame() {
+        let sut = Helmert::new(ECEF::new(0.0, 0.0, 4.5))
+            .with_rotation([0.0, 0.0, -0.554], RotationConvention::CoordinateFrame)
+            .with_scale(0.219);
+        assert_ecef(sut.transform(WGS72), WGS84, 0.01);
+    }
+
+    #[test]
+    fn exact_inverse() {
+        let sut = Helmert::new(ECEF::new(-146.414, 507.337, 680.507))
+            .with_rotation([1.0, -2.0, 3.0], RotationConvention::CoordinateFrame)
+            .with_scale(-5.0);
+        assert_ecef(sut.inverse(sut.transform(WGS72)), WGS72, 1e-8);
+    }
+}
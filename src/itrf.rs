@@ -0,0 +1,130 @@
+use crate::{Helmert, RotationConvention, TimeDependentHelmert, ECEF};
+
+/// Realizations of International Terrestrial Reference Frame.
+///
+/// 国際地球基準座標系の各実現。
+/// [`Jgd2000`](crate::Jgd2000) は ITRF94、[`Jgd2011`](crate::Jgd2011) は ITRF2008 に基づく。
+///
+/// 変換パラメータは IERS が公開する ITRF2020 から各実現への14パラメータ (元期 2015.0) を用いる。
+/// ITRF2020 以外の実現同士は ITRF2020 を経由して変換される。
+///
+/// 座標系の変換のみが行われ、プレート運動などによる地点の移動は考慮されない。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{Itrf, ECEF};
+///
+/// let itrf2014 = ECEF::new(-3959340.203, 3352854.274, 3697471.413);
+/// let itrf2008 = Itrf::Itrf2014.transform(Itrf::Itrf2008, itrf2014, 2024.5);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Itrf {
+    /// ITRF2020
+    Itrf2020,
+    /// ITRF2014
+    Itrf2014,
+    /// ITRF2008
+    Itrf2008,
+    /// ITRF2005
+    Itrf2005,
+    /// ITRF2000
+    Itrf2000,
+    /// ITRF97
+    Itrf97,
+    /// ITRF96
+    Itrf96,
+    /// ITRF94
+    Itrf94,
+}
+impl Itrf {
+    /// All supported realizations.
+    pub const ALL: [Itrf; 8] = [
+        Itrf::Itrf2020,
+        Itrf::Itrf2014,
+        Itrf::Itrf2008,
+        Itrf::Itrf2005,
+        Itrf::Itrf2000,
+        Itrf::Itrf97,
+        Itrf::Itrf96,
+        Itrf::Itrf94,
+    ];
+
+    /// Transforms a coordinate at `epoch` in decimal years from `self` to `to`.
+    pub fn transform(self, to: Itrf, ecef: ECEF, epoch: f64) -> ECEF {
+        if self == to {
+            return ecef;
+        }
+        let itrf2020 = self.from_itrf2020().at(epoch).inverse(ecef);
+        to.from_itrf2020().at(epoch).transform(itrf2020)
+    }
+
+    /// Returns the transformation parameters from ITRF2020 to `self`.
+    ///
+    /// 出典: IERS [Transfo-ITRF2020_TRFs.txt](https://itrf.ign.fr/docs/solutions/itrf2020/Transfo-ITRF2020_TRFs.txt)
+    pub fn from_itrf2020(self) -> TimeDependentHelmert {
+        #[rustfmt::skip]
+        let (t, d, r, t_rate, d_rate, r_rate) = match self {
+            Itrf::Itrf2020 => return TimeDependentHelmert::new(Helmert::default(), Helmert::default(), 2015.0),
+            Itrf::Itrf2014 => ([-1.4, -0.9, 1.4], -0.42, [0.0, 0.0, 0.0], [0.0, -0.1, 0.2], 0.00, [0.0, 0.0, 0.0]),
+            Itrf::Itrf2008 => ([0.2, 1.0, 3.3], -0.29, [0.0, 0.0, 0.0], [0.0, -0.1, 0.1], 0.03, [0.0, 0.0, 0.0]),
+            Itrf::Itrf2005 => ([2.7, 0.1, -1.4], 0.65, [0.0, 0.0, 0.0], [0.3, -0.1, 0.1], 0.03, [0.0, 0.0, 0.0]),
+            Itrf::Itrf2000 => ([-0.2, 0.8, -34.2], 2.25, [0.0, 0.0, 0.0], [0.1, 0.0, -1.7], 0.11, [0.0, 0.0, 0.0]),
+            Itrf::Itrf97 | Itrf::Itrf96 | Itrf::Itrf94 => {
+                ([6.5, -3.9, -77.9], 3.98, [0.0, 0.0, 0.36], [0.1, -0.6, -3.1], 0.12, [0.0, 0.0, 0.02])
+            }
+        };
+        TimeDependentHelmert::new(iers(t, d, r), iers(t_rate, d_rate, r_rate), 2015.0)
+    }
+}
+
+/// Constructs a [`Helmert`] in the units of IERS: millimeters, ppb and milliarcseconds.
+fn iers(t: [f64; 3], d: f64, r: [f64; 3]) -> Helmert {
+    let [x, y, z] = t.map(|x| x / 1_000.);
+    Helmert::new(ECEF::new(x, y, z))
+        .with_rotation(r.map(|x| x / 1_000.), RotationConvention::PositionVector)
+        .with_scale(d / 1_000.)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::ECEF;
+
+    use super::{iers, Itrf};
+
+    const TSUKUBA: ECEF = ECEF::new(-3957199.0, 3310199.0, 3737711.0);
+
+    #[test]
+    fn identity() {
+        let ret = Itrf::Itrf2008.transform(Itrf::Itrf2008, TSUKUBA, 2011.4);
+        assert_eq!(ret, TSUKUBA);
+    }
+
+    #[test]
+    fn round_trip() {
+        for from in Itrf::ALL {
+            for to in Itrf::ALL {
+                let ecef = from.transform(to, TSUKUBA, 2024.5);
+                let ret = to.transform(from, ecef, 2024.5);
+                assert_abs_diff_eq!(ret.distance(TSUKUBA), 0.0, epsilon = 1e-8);
+            }
+        }
+    }
+
+    #[test]
+    fn itrf2014_to_itrf2008() {
+        // IERS: ITRF2014 から ITRF2008 への直接の変換パラメータ (元期 2010.0)
+        let direct = crate::TimeDependentHelmert::new(
+            iers([1.6, 1.9, 2.4], -0.02, [0.0, 0.0, 0.0]),
+            iers([0.0, 0.0, -0.1], 0.03, [0.0, 0.0, 0.0]),
+            2010.0,
+        );
+        for epoch in [2000.0, 2011.4, 2024.5] {
+            let ret = Itrf::Itrf2014.transform(Itrf::Itrf2008, TSUKUBA, epoch);
+            let exp = direct.at(epoch).transform(TSUKUBA);
+            assert_abs_diff_eq!(ret.distance(exp), 0.0, epsilon = 0.0001);
+        }
+    }
+}
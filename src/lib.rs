@@ -2,6 +2,7 @@
 #![cfg_attr(not(debug_assertions), deny(warnings))]
 // doc requres nightly
 #![cfg_attr(all(doc, not(doctest)), feature(doc_auto_cfg))]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 //! Transform geodetic datums used in Japan.
 //!
@@ -38,12 +39,32 @@
 //! # }
 //! ```
 //!
+//! `geo-types` フィーチャーを有効にすると、`TransformGeometry` によって一度に変換できる。
+//!
 //! # Features
 //!
 //! Each feature increases the size of the build binary.
 //!
+//! - `std` - Parsing, formatting and [`Transformer`] which require the standard library. Enabled by default.
+//! - `libm` - Floating point functions by [`libm`](https://docs.rs/libm) for `no_std`.
 //! - `tky2jgd` - [TKY2JGD] is used. Enabled by default.
 //! - `patchjgd` - [TOUHOKUTAIHEIYOUOKI2011] is used. Enabled by default.
+//! - `geo-types` - Conversions from/to [`geo-types`](https://docs.rs/geo-types) and `TransformGeometry`.
+//! - `serde` - `Serialize` and `Deserialize` for coordinates, [`Dms`] and datums.
+//! - `geojson` - Streaming transformation of GeoJSON by `GeoJsonTransformer`.
+//! - `sima` - Reading and writing SIMA survey data by `Sima`.
+//! - `mapxml` - Streaming transformation of 地図XML of the Ministry of Justice by `MapXmlTransformer`.
+//! - `fgd` - Streaming reading and writing GML of 基盤地図情報 by `FgdReader` and `FgdWriter`.
+//!
+//! # `no_std`
+//!
+//! `std` を無効にし `libm` を有効にすると、`no_std` 環境で使える。
+//! 測地系の変換、パラメータグリッド、楕円体上の計算、平面直角座標への投影は利用でき、
+//! 度分秒の解析や書式、[`Transformer`]、セミ・ダイナミック補正は利用できない。
+//!
+//! ```toml
+//! jgd = { version = "0.4", default-features = false, features = ["libm", "tky2jgd", "patchjgd"] }
+//! ```
 //!
 //! # Limitations
 //!
@@ -53,7 +74,9 @@
 //! 異なる測地系で整備された座標同士のズレを低減できても、ズレが消滅することはない。
 //! 変換方法によって精度や制約が異なり、詳細はメソッド毎のドキュメントに記載されている。
 //!
-//! 緯度経度で表される地理座標のみが対応されている。平面直角座標系などの投影座標は対応されていない。
+//! 緯度経度で表される地理座標のほか、[`Zone`] による平面直角座標系に対応している。
+//! EPSG コードで指定された座標参照系の間は [`Transformer`] で変換できる。
+//! PostGIS などの WKT / WKB は [`WktGeometry`] で読み書きでき、SRID に従って変換できる。
 //!
 //! # Compatibility
 //!
@@ -69,18 +92,80 @@
 //! - 飛田幹男 [世界測地系移行のための座標変換ソフトウェア "TKY2JGD"](https://www.gsi.go.jp/common/000063173.pdf) (国土地理院時報 97集 (2001) pp31-51)
 //! - 飛田幹男 [地震時地殻変動に伴う座標値の変化を補正するソフトウェア "PatchJGD"](https://www.jstage.jst.go.jp/article/sokuchi/55/4/55_4_355/_pdf/-char/ja) (測地学会誌 55巻 4号 (2009) pp355-367)
 
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("either `std` or `libm` feature is required");
+
+#[cfg(feature = "std")]
+mod batch;
 mod crs;
+mod datum;
+#[cfg(feature = "std")]
+mod dms;
 mod ecef;
+#[cfg(feature = "fgd")]
+mod fgd;
+mod geodesic;
 mod geodetic;
+#[cfg(feature = "geojson")]
+mod geojson;
+#[cfg(feature = "geo-types")]
+mod geometry;
 mod grid;
+mod helmert;
+mod itrf;
+mod local;
+#[cfg(feature = "mapxml")]
+mod mapxml;
+#[cfg(not(any(feature = "std", test)))]
+mod math;
 #[cfg(any(feature = "tky2jgd", feature = "patchjgd"))]
 mod par;
+mod plane;
+mod polygon;
+#[cfg(feature = "std")]
+mod semidyna;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "sima")]
+mod sima;
+#[cfg(feature = "std")]
+mod transformer;
+#[cfg(feature = "std")]
+mod wkt;
 
+#[cfg(feature = "std")]
+pub use batch::{BatchError, BatchLayout, BatchPoint, BatchReader, BatchRecord, BatchWriter};
 pub use crs::{Jgd2000, Jgd2011, Tokyo, Tokyo97};
-pub use ecef::{Ellipsoid, BESSEL, ECEF, GRS80};
-pub use geodetic::{DegreesError, Dms, LatLon};
-pub use grid::Grid;
+pub use datum::{Datum, DatumKind, TransformTo};
+#[cfg(feature = "std")]
+pub use dms::{AxisOrder, DmsFormat, DmsStyle, ParseDmsError};
+pub use ecef::{Ellipsoid, BESSEL, ECEF, GRS80, WGS84};
+#[cfg(feature = "fgd")]
+pub use fgd::{
+    FgdError, FgdFeature, FgdGeometry, FgdHeader, FgdReader, FgdShape, FgdValue, FgdWriter,
+};
+pub use geodesic::Geodesic;
+pub use geodetic::{DegreesError, Dms, DmsError, Hemisphere, LatLon};
+#[cfg(feature = "geojson")]
+pub use geojson::{GeoJsonError, GeoJsonTransformer};
+#[cfg(feature = "geo-types")]
+pub use geometry::{GeometryError, TransformGeometry};
 #[cfg(feature = "tky2jgd")]
 pub use grid::TKY2JGD;
 #[cfg(feature = "patchjgd")]
 pub use grid::TOUHOKUTAIHEIYOUOKI2011;
+pub use grid::{Dot, Grid, Spacing};
+pub use helmert::{Helmert, RotationConvention, TimeDependentHelmert};
+pub use itrf::Itrf;
+pub use local::{Enu, LocalFrame, Ned};
+#[cfg(feature = "mapxml")]
+pub use mapxml::{MapXmlError, MapXmlTransformer};
+pub use plane::{TransverseMercator, Xy, Zone};
+#[cfg(feature = "std")]
+pub use semidyna::{ParError, SemiDynamic};
+#[cfg(feature = "sima")]
+pub use sima::{Sima, SimaError, SimaParcel, SimaPoint, SimaRecord};
+#[cfg(feature = "std")]
+pub use transformer::{Crs, ParseCrsError, Transformer, TransformerError};
+#[cfg(feature = "std")]
+pub use wkt::{transform_ewkb, transform_ewkt, Position, WktError, WktGeometry, WktGeometryType};
@@ -0,0 +1,242 @@
+use crate::{Ellipsoid, LatLon, ECEF};
+
+#[cfg(not(any(feature = "std", test)))]
+use crate::math::Float;
+
+/// East-North-Up coordinate in a [`LocalFrame`].
+///
+/// 局所水平座標 (東, 北, 上)。単位はメートル。
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Enu {
+    e: f64,
+    n: f64,
+    u: f64,
+}
+impl Enu {
+    /// Constructs a [`Enu`] with east, north and up in meters.
+    pub const fn new(e: f64, n: f64, u: f64) -> Self {
+        Self { e, n, u }
+    }
+
+    /// Returns east in meters.
+    pub fn e(&self) -> f64 {
+        self.e
+    }
+
+    /// Returns north in meters.
+    pub fn n(&self) -> f64 {
+        self.n
+    }
+
+    /// Returns up in meters.
+    pub fn u(&self) -> f64 {
+        self.u
+    }
+
+    /// Converts to [`Ned`].
+    pub fn to_ned(self) -> Ned {
+        Ned::new(self.n, self.e, -self.u)
+    }
+}
+
+/// North-East-Down coordinate in a [`LocalFrame`].
+///
+/// 局所水平座標 (北, 東, 下)。単位はメートル。
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Ned {
+    n: f64,
+    e: f64,
+    d: f64,
+}
+impl Ned {
+    /// Constructs a [`Ned`] with north, east and down in meters.
+    pub const fn new(n: f64, e: f64, d: f64) -> Self {
+        Self { n, e, d }
+    }
+
+    /// Returns north in meters.
+    pub fn n(&self) -> f64 {
+        self.n
+    }
+
+    /// Returns east in meters.
+    pub fn e(&self) -> f64 {
+        self.e
+    }
+
+    /// Returns down in meters.
+    pub fn d(&self) -> f64 {
+        self.d
+    }
+
+    /// Converts to [`Enu`].
+    pub fn to_enu(self) -> Enu {
+        Enu::new(self.e, self.n, -self.d)
+    }
+}
+
+/// Local tangent plane around an origin on an [`Ellipsoid`].
+///
+/// 原点で楕円体に接する平面を基準とした局所座標系。
+/// 原点の楕円体高は 0 とみなされる。
+///
+/// 点の変換 ([`LocalFrame::to_enu`] など) は原点からの相対位置を、
+/// ベクトルの変換 ([`LocalFrame::vector_to_enu`] など) は回転のみを行う。
+/// 速度などは後者で変換する。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{LatLon, LocalFrame, GRS80};
+///
+/// let frame = LocalFrame::new(&GRS80, LatLon(35.0, 135.0));
+/// let enu = frame.to_enu(LatLon(35.001, 135.001));
+/// # assert!(enu.e() > 0.0 && enu.n() > 0.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LocalFrame {
+    ellipsoid: Ellipsoid,
+    origin: ECEF,
+    rotation: [[f64; 3]; 3],
+}
+impl LocalFrame {
+    /// Constructs a [`LocalFrame`] with the origin in degrees.
+    pub fn new(ellipsoid: &Ellipsoid, origin: LatLon) -> Self {
+        let LatLon(lat, lon) = origin.map(f64::to_radians);
+        let (sin_lat, cos_lat) = lat.sin_cos();
+        let (sin_lon, cos_lon) = lon.sin_cos();
+        let rotation = [
+            [-sin_lon, cos_lon, 0.],
+            [-sin_lat * cos_lon, -sin_lat * sin_lon, cos_lat],
+            [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat],
+        ];
+        Self {
+            ellipsoid: *ellipsoid,
+            origin: ellipsoid.to_ecef(origin),
+            rotation,
+        }
+    }
+
+    /// Returns the origin as [`ECEF`].
+    pub fn origin(&self) -> ECEF {
+        self.origin
+    }
+
+    /// Returns the rotation matrix from [`ECEF`] to [`Enu`].
+    ///
+    /// 各行が東, 北, 上の単位ベクトル。[`Enu`] から [`ECEF`] への回転はこの転置行列。
+    pub fn rotation(&self) -> [[f64; 3]; 3] {
+        self.rotation
+    }
+
+    /// Converts a point in [`ECEF`] to [`Enu`].
+    pub fn ecef_to_enu(&self, ecef: ECEF) -> Enu {
+        self.vector_to_enu(ecef - self.origin)
+    }
+
+    /// Converts a point in [`Enu`] to [`ECEF`].
+    pub fn enu_to_ecef(&self, enu: Enu) -> ECEF {
+        self.vector_from_enu(enu) + self.origin
+    }
+
+    /// Converts a point in degrees on the ellipsoid to [`Enu`].
+    pub fn to_enu(&self, degrees: LatLon) -> Enu {
+        self.ecef_to_enu(self.ellipsoid.to_ecef(degrees))
+    }
+
+    /// Converts a point in [`Enu`] to degrees.
+    ///
+    /// 楕円体高は捨てられる。
+    pub fn to_geodetic(&self, enu: Enu) -> LatLon {
+        self.ellipsoid.to_geodetic(self.enu_to_ecef(enu))
+    }
+
+    /// Converts a point in [`ECEF`] to [`Ned`].
+    pub fn ecef_to_ned(&self, ecef: ECEF) -> Ned {
+        self.ecef_to_enu(ecef).to_ned()
+    }
+
+    /// Converts a point in [`Ned`] to [`ECEF`].
+    pub fn ned_to_ecef(&self, ned: Ned) -> ECEF {
+        self.enu_to_ecef(ned.to_enu())
+    }
+
+    /// Converts a point in degrees on the ellipsoid to [`Ned`].
+    pub fn to_ned(&self, degrees: LatLon) -> Ned {
+        self.to_enu(degrees).to_ned()
+    }
+
+    /// Rotates a vector such as velocity from [`ECEF`] to [`Enu`].
+    pub fn vector_to_enu(&self, vector: ECEF) -> Enu {
+        let [e, n, u] = self.rotation.map(|row| vector.dot(ECEF::from(row)));
+        Enu::new(e, n, u)
+    }
+
+    /// Rotates a vector such as velocity from [`Enu`] to [`ECEF`].
+    pub fn vector_from_enu(&self, vector: Enu) -> ECEF {
+        let [e, n, u] = self.rotation.map(ECEF::from);
+        e * vector.e + n * vector.n + u * vector.u
+    }
+
+    /// Rotates a vector such as velocity from [`ECEF`] to [`Ned`].
+    pub fn vector_to_ned(&self, vector: ECEF) -> Ned {
+        self.vector_to_enu(vector).to_ned()
+    }
+
+    /// Rotates a vector such as velocity from [`Ned`] to [`ECEF`].
+    pub fn vector_from_ned(&self, vector: Ned) -> ECEF {
+        self.vector_from_enu(vector.to_enu())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{LatLon, ECEF, GRS80};
+
+    use super::{Enu, LocalFrame};
+
+    #[test]
+    fn origin_is_zero() {
+        let sut = LocalFrame::new(&GRS80, LatLon(35.0, 135.0));
+        let enu = sut.to_enu(LatLon(35.0, 135.0));
+        assert_eq!(enu, Enu::default());
+    }
+
+    #[test]
+    fn up_at_equator() {
+        let sut = LocalFrame::new(&GRS80, LatLon(0.0, 0.0));
+        let enu = sut.ecef_to_enu(ECEF::new(6378138.0, 1.0, 2.0));
+        assert_abs_diff_eq!(enu.e(), 1.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(enu.n(), 2.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(enu.u(), 1.0, epsilon = 1e-9);
+
+        let ned = sut.ecef_to_ned(ECEF::new(6378138.0, 1.0, 2.0));
+        assert_abs_diff_eq!(ned.d(), -1.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn round_trip() {
+        let sut = LocalFrame::new(&GRS80, LatLon(35.0, 135.0));
+        let enu = Enu::new(120.0, -80.0, 3.0);
+        let ret = sut.ecef_to_enu(sut.enu_to_ecef(enu));
+        assert_abs_diff_eq!(ret.e(), enu.e(), epsilon = 1e-9);
+        assert_abs_diff_eq!(ret.n(), enu.n(), epsilon = 1e-9);
+        assert_abs_diff_eq!(ret.u(), enu.u(), epsilon = 1e-9);
+
+        let degrees = LatLon(35.01, 134.99);
+        let ret = sut.to_geodetic(sut.to_enu(degrees));
+        assert_abs_diff_eq!(ret.lat(), degrees.lat(), epsilon = 1e-11);
+        assert_abs_diff_eq!(ret.lon(), degrees.lon(), epsilon = 1e-11);
+    }
+
+    #[test]
+    fn vector_is_not_translated() {
+        let sut = LocalFrame::new(&GRS80, LatLon(35.0, 135.0));
+        let velocity = sut.vector_from_enu(Enu::new(0.0, 0.0, 1.0));
+        assert_abs_diff_eq!(velocity.norm(), 1.0, epsilon = 1e-15);
+        let ned = sut.vector_to_ned(velocity);
+        assert_abs_diff_eq!(ned.d(), -1.0, epsilon = 1e-15);
+    }
+}
@@ -0,0 +1,430 @@
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+use quick_xml::{
+    events::{BytesEnd, BytesStart, BytesText, Event},
+    Reader, Writer,
+};
+
+use crate::{Crs, DatumKind, DegreesError, Transformer, TransformerError, Zone};
+
+/// Transformation of 地図XML of the Ministry of Justice between datums.
+///
+/// 法務省の地図XML (登記所備付地図データ) を1要素ずつ読み書きし、
+/// 点、線、面を構成するすべての座標 (`DirectPosition` と `GM_Position.direct` の `X`, `Y`) を変換する。
+/// 系番号は `座標系` 要素 (`公共座標9系` など) から読み取り、`任意座標系` は変換できない。
+///
+/// 変換先が世界測地系 (JGD2000, JGD2011) の場合、`測地系判別` 要素を `変換` とし、なければ `座標系` の直後に追加する。
+/// 座標の小数点以下の桁数、その他の要素、属性、空白はそのまま保持する。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{DatumKind, MapXmlTransformer};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+/// <地図 xmlns="http://www.moj.go.jp/MINJI/tizuxml" xmlns:zmn="http://www.moj.go.jp/MINJI/tizuzumen">
+///   <座標系>公共座標9系</座標系>
+///   <空間属性>
+///     <zmn:GM_Point id="P1"><zmn:GM_Point.position><zmn:DirectPosition>
+///       <zmn:X>-37934.23</zmn:X><zmn:Y>-8119.12</zmn:Y>
+///     </zmn:DirectPosition></zmn:GM_Point.position></zmn:GM_Point>
+///   </空間属性>
+/// </地図>"#;
+/// let mapxml = MapXmlTransformer::new(DatumKind::Tokyo, DatumKind::Jgd2011)?;
+/// let mut output = vec![];
+/// mapxml.transform(input.as_bytes(), &mut output)?;
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MapXmlTransformer {
+    from: DatumKind,
+    to: DatumKind,
+}
+impl MapXmlTransformer {
+    /// Constructs a [`MapXmlTransformer`] from datum `from` to datum `to`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransformerError`] if there is no path between the datums.
+    pub fn new(from: DatumKind, to: DatumKind) -> Result<Self, TransformerError> {
+        Transformer::new(Crs::Geographic(from), Crs::Geographic(to))?;
+        Ok(Self { from, to })
+    }
+
+    /// Transforms 地図XML from `reader` to `writer`.
+    ///
+    /// 全体をメモリに載せる必要はない。`writer` はバッファリングされていることが望ましい。
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MapXmlError`] if the input is not a valid XML, the coordinate system is missing or unsupported,
+    /// a coordinate is invalid or out of range in degrees, or reading or writing fails.
+    /// 途中で失敗した場合、それまでに書き込んだ出力は不完全な XML となる。
+    pub fn transform<R: BufRead, W: Write>(&self, reader: R, writer: W) -> Result<(), MapXmlError> {
+        let mut reader = Reader::from_reader(reader);
+        let mut writer = Writer::new(writer);
+        let mut buf = vec![];
+        let mut transformer = None;
+        let mut path: Vec<Vec<u8>> = vec![];
+        // 座標を含む要素のイベント
+        let mut position: Option<Vec<Event<'static>>> = None;
+        // 直前の空白
+        let mut indent = None;
+        // `測地系判別` を追加する場合の名前空間接頭辞
+        let mut pending: Option<Vec<u8>> = None;
+        let world = matches!(self.to, DatumKind::Jgd2000 | DatumKind::Jgd2011);
+
+        loop {
+            let offset = reader.buffer_position();
+            let error = |kind| MapXmlError::new(offset, kind);
+            let event = reader
+                .read_event_into(&mut buf)
+                .map_err(|e| error(ErrorKind::Xml(e)))?
+                .into_owned();
+            buf.clear();
+
+            if let Some(events) = &mut position {
+                let end = matches!(&event, Event::End(e) if is_position(e.local_name().as_ref()));
+                events.push(event);
+                if end {
+                    let mut events = position.take().unwrap();
+                    let transformer = transformer
+                        .as_ref()
+                        .ok_or(error(ErrorKind::Missing("座標系")))?;
+                    transform_position(transformer, &mut events).map_err(error)?;
+                    for event in events {
+                        writer
+                            .write_event(event)
+                            .map_err(|e| error(ErrorKind::Io(e)))?;
+                    }
+                }
+                continue;
+            }
+
+            // `座標系` の次の要素、または親要素の終わりの前に `測地系判別` を追加する
+            // 親要素の終わりの前では、空白を加えない
+            let next = match &event {
+                Event::Start(e) | Event::Empty(e) => {
+                    Some((e.local_name().as_ref() != DATUM.as_bytes(), indent.clone()))
+                }
+                Event::End(_) => Some((true, None)),
+                _ => None,
+            };
+            if let Some((insert, indent)) = next {
+                if let Some(prefix) = pending.take().filter(|_| insert) {
+                    let name = [prefix, DATUM.as_bytes().to_vec()].concat();
+                    let name = String::from_utf8(name).unwrap();
+                    let events = [
+                        Event::Start(BytesStart::new(name.as_str())),
+                        Event::Text(BytesText::new(CONVERTED)),
+                        Event::End(BytesEnd::new(name.as_str())),
+                    ];
+                    for event in events.into_iter().chain(indent) {
+                        writer
+                            .write_event(event)
+                            .map_err(|e| error(ErrorKind::Io(e)))?;
+                    }
+                }
+            }
+
+            let mut event = event;
+            match &event {
+                Event::Start(e) if is_position(e.local_name().as_ref()) => {
+                    position = Some(vec![event]);
+                    continue;
+                }
+                Event::Start(e) => path.push(e.local_name().as_ref().to_vec()),
+                Event::End(e) => {
+                    path.pop();
+                    if world && e.local_name().as_ref() == CRS.as_bytes() {
+                        let prefix = e.name().prefix().map(|p| [p.as_ref(), b":"].concat());
+                        pending = Some(prefix.unwrap_or_default());
+                    }
+                }
+                Event::Text(text) => {
+                    let content = text.unescape().map_err(|e| error(ErrorKind::Xml(e)))?;
+                    match path.last().map(Vec::as_slice) {
+                        Some(name) if name == CRS.as_bytes() => {
+                            let zone = parse_zone(&content)
+                                .ok_or_else(|| error(ErrorKind::Crs(content.trim().to_owned())))?;
+                            let t = Transformer::new(
+                                Crs::Plane(self.from, zone),
+                                Crs::Plane(self.to, zone),
+                            )
+                            .map_err(|e| error(ErrorKind::Transformer(e)))?;
+                            transformer = Some(t);
+                        }
+                        Some(name) if world && name == DATUM.as_bytes() => {
+                            event = Event::Text(BytesText::new(CONVERTED));
+                        }
+                        _ if content.trim().is_empty() => indent = Some(event.clone()),
+                        _ => {}
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            writer
+                .write_event(event)
+                .map_err(|e| error(ErrorKind::Io(e)))?;
+        }
+        let offset = reader.buffer_position();
+        writer
+            .into_inner()
+            .flush()
+            .map_err(|e| MapXmlError::new(offset, ErrorKind::Io(e)))
+    }
+}
+
+const CRS: &str = "座標系";
+const DATUM: &str = "測地系判別";
+const CONVERTED: &str = "変換";
+
+fn is_position(local_name: &[u8]) -> bool {
+    matches!(local_name, b"DirectPosition" | b"GM_Position.direct")
+}
+
+/// Parses `公共座標9系` etc.
+fn parse_zone(s: &str) -> Option<Zone> {
+    let number = s.trim().strip_prefix("公共座標")?.strip_suffix('系')?;
+    Zone::new(number.parse().ok()?)
+}
+
+/// Transforms `X` and `Y` in the events of a position, keeping the decimal places.
+fn transform_position(
+    transformer: &Transformer,
+    events: &mut [Event<'static>],
+) -> Result<(), ErrorKind> {
+    let mut axis = None;
+    let mut coord = [None, None];
+    for (i, event) in events.iter().enumerate() {
+        match event {
+            Event::Start(e) => {
+                axis = match e.local_name().as_ref() {
+                    b"X" => Some(0),
+                    b"Y" => Some(1),
+                    _ => None,
+                }
+            }
+            Event::End(_) => axis = None,
+            Event::Text(text) => {
+                if let Some(axis) = axis {
+                    let content = text.unescape().map_err(ErrorKind::Xml)?;
+                    coord[axis] = Some((i, content.trim().to_owned()));
+                }
+            }
+            _ => {}
+        }
+    }
+    let [Some(x), Some(y)] = coord else {
+        return Err(ErrorKind::Missing(if coord[0].is_none() {
+            "X"
+        } else {
+            "Y"
+        }));
+    };
+    let parse = |s: &str| {
+        s.parse::<f64>()
+            .map_err(|_| ErrorKind::Number(s.to_owned()))
+    };
+    let ret = transformer
+        .transform([parse(&x.1)?, parse(&y.1)?])
+        .map_err(ErrorKind::Degrees)?;
+    for ((i, s), value) in [x, y].into_iter().zip(ret) {
+        let decimals = s.split_once('.').map_or(0, |(_, d)| d.len());
+        let text = format!("{:.*}", decimals, value);
+        events[i] = Event::Text(BytesText::new(&text).into_owned());
+    }
+    Ok(())
+}
+
+/// Errors in transforming 地図XML.
+#[derive(Debug)]
+pub struct MapXmlError {
+    offset: u64,
+    kind: ErrorKind,
+}
+impl MapXmlError {
+    fn new(offset: u64, kind: ErrorKind) -> Self {
+        Self { offset, kind }
+    }
+
+    /// Returns the byte offset in the input where the error occurred.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+impl fmt::Display for MapXmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "byte {}: ", self.offset)?;
+        match &self.kind {
+            ErrorKind::Xml(e) => write!(f, "{}", e),
+            ErrorKind::Io(e) => write!(f, "{}", e),
+            ErrorKind::Missing(name) => write!(f, "missing {}", name),
+            ErrorKind::Crs(s) => write!(f, "unsupported coordinate system: {}", s),
+            ErrorKind::Number(s) => write!(f, "invalid coordinate: {:?}", s),
+            ErrorKind::Transformer(e) => write!(f, "{}", e),
+            ErrorKind::Degrees(e) => write!(f, "{}", e),
+        }
+    }
+}
+impl std::error::Error for MapXmlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Xml(e) => Some(e),
+            ErrorKind::Io(e) => Some(e),
+            ErrorKind::Transformer(e) => Some(e),
+            ErrorKind::Degrees(e) => Some(e),
+            ErrorKind::Missing(_) | ErrorKind::Crs(_) | ErrorKind::Number(_) => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum ErrorKind {
+    Xml(quick_xml::Error),
+    Io(io::Error),
+    Missing(&'static str),
+    Crs(String),
+    Number(String),
+    Transformer(TransformerError),
+    Degrees(DegreesError),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Crs, DatumKind, Transformer, Zone};
+
+    use super::MapXmlTransformer;
+
+    const INPUT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<地図 xmlns="http://www.moj.go.jp/MINJI/tizuxml" xmlns:zmn="http://www.moj.go.jp/MINJI/tizuzumen">
+  <地図名>テスト</地図名>
+  <座標系>公共座標9系</座標系>
+  <空間属性>
+    <zmn:GM_Point id="P1">
+      <zmn:GM_Point.position>
+        <zmn:DirectPosition>
+          <zmn:X>-37934.23</zmn:X>
+          <zmn:Y>-8119.12</zmn:Y>
+        </zmn:DirectPosition>
+      </zmn:GM_Point.position>
+    </zmn:GM_Point>
+    <zmn:GM_Curve id="C1">
+      <zmn:GM_Curve.segment>
+        <zmn:GM_LineString>
+          <zmn:GM_LineString.controlPoint>
+            <zmn:GM_PointArray>
+              <zmn:GM_PointArray.column>
+                <zmn:GM_Position.indirect><zmn:GM_PointRef.point idref="P1"/></zmn:GM_Position.indirect>
+              </zmn:GM_PointArray.column>
+              <zmn:GM_PointArray.column>
+                <zmn:GM_Position.direct><zmn:X>-37900.5</zmn:X><zmn:Y>-8100.125</zmn:Y></zmn:GM_Position.direct>
+              </zmn:GM_PointArray.column>
+            </zmn:GM_PointArray>
+          </zmn:GM_LineString.controlPoint>
+        </zmn:GM_LineString>
+      </zmn:GM_Curve.segment>
+    </zmn:GM_Curve>
+  </空間属性>
+</地図>"#;
+
+    fn transform(input: &str) -> Result<String, String> {
+        let mapxml = MapXmlTransformer::new(DatumKind::Tokyo97, DatumKind::Jgd2000).unwrap();
+        let mut output = vec![];
+        match mapxml.transform(input.as_bytes(), &mut output) {
+            Ok(()) => Ok(String::from_utf8(output).unwrap()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    #[test]
+    fn transform_positions() {
+        let zone = Zone::new(9).unwrap();
+        let transformer = Transformer::new(
+            Crs::Plane(DatumKind::Tokyo97, zone),
+            Crs::Plane(DatumKind::Jgd2000, zone),
+        )
+        .unwrap();
+        let [x1, y1] = transformer.transform([-37934.23, -8119.12]).unwrap();
+        let [x2, y2] = transformer.transform([-37900.5, -8100.125]).unwrap();
+        let expected = INPUT
+            .replace(
+                "<座標系>公共座標9系</座標系>\n",
+                "<座標系>公共座標9系</座標系>\n  <測地系判別>変換</測地系判別>\n",
+            )
+            .replace("-37934.23", &format!("{:.2}", x1))
+            .replace("-8119.12", &format!("{:.2}", y1))
+            .replace("-37900.5", &format!("{:.1}", x2))
+            .replace("-8100.125", &format!("{:.3}", y2));
+        assert_eq!(transform(INPUT).unwrap(), expected);
+
+        // 既存の測地系判別は置き換える
+        let input = INPUT.replace(
+            "<座標系>公共座標9系</座標系>",
+            "<座標系>公共座標9系</座標系><測地系判別>測量</測地系判別>",
+        );
+        let ret = transform(&input).unwrap();
+        assert!(ret.contains("<座標系>公共座標9系</座標系><測地系判別>変換</測地系判別>\n"));
+        assert_eq!(ret.matches("測地系判別>").count(), 2);
+
+        // 座標系が最後の子要素
+        let input = INPUT.replace(
+            "<座標系>公共座標9系</座標系>",
+            "<図郭><座標系>公共座標9系</座標系></図郭>",
+        );
+        let ret = transform(&input).unwrap();
+        assert!(ret
+            .contains("<図郭><座標系>公共座標9系</座標系><測地系判別>変換</測地系判別></図郭>\n"));
+        assert_eq!(ret.matches("測地系判別>").count(), 2);
+    }
+
+    #[cfg(all(feature = "tky2jgd", feature = "patchjgd"))]
+    #[test]
+    fn tokyo_to_jgd2011() {
+        let zone = Zone::new(9).unwrap();
+        let transformer = Transformer::new(
+            Crs::Plane(DatumKind::Tokyo, zone),
+            Crs::Plane(DatumKind::Jgd2011, zone),
+        )
+        .unwrap();
+        let [x, y] = transformer.transform([-37934.23, -8119.12]).unwrap();
+        // 旧日本測地系から世界測地系へは数百 m 移動する
+        assert!((x + 37934.23).abs() > 100.);
+
+        let mapxml = MapXmlTransformer::new(DatumKind::Tokyo, DatumKind::Jgd2011).unwrap();
+        let mut output = vec![];
+        mapxml.transform(INPUT.as_bytes(), &mut output).unwrap();
+        let ret = String::from_utf8(output).unwrap();
+        assert!(ret.contains(&format!("<zmn:X>{:.2}</zmn:X>", x)), "{}", ret);
+        assert!(ret.contains(&format!("<zmn:Y>{:.2}</zmn:Y>", y)), "{}", ret);
+        assert!(ret.contains("<測地系判別>変換</測地系判別>"));
+    }
+
+    #[test]
+    fn errors() {
+        let arbitrary = INPUT.replace("公共座標9系", "任意座標系");
+        let err = transform(&arbitrary).unwrap_err();
+        assert!(
+            err.ends_with("unsupported coordinate system: 任意座標系"),
+            "{}",
+            err
+        );
+
+        let missing = INPUT.replace("<座標系>公共座標9系</座標系>", "");
+        let err = transform(&missing).unwrap_err();
+        assert!(err.ends_with("missing 座標系"), "{}", err);
+
+        let invalid = INPUT.replace("-8119.12", "x");
+        let err = transform(&invalid).unwrap_err();
+        assert!(err.ends_with(r#"invalid coordinate: "x""#), "{}", err);
+
+        let mismatched = INPUT.replace("</地図名>", "</地図>");
+        assert!(transform(&mismatched).is_err());
+    }
+}
@@ -0,0 +1,98 @@
+//! Floating point functions by `libm` for `no_std`.
+//!
+//! `std` の `f64` と同名のメソッドを提供する。`std` が有効な場合はこのモジュールは使われない。
+//! テストではハーネスが `std` をリンクし固有メソッドが優先されるため、インポートしない。
+
+pub(crate) trait Float: Sized {
+    fn asin(self) -> Self;
+    fn atan(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn atanh(self) -> Self;
+    fn cbrt(self) -> Self;
+    fn ceil(self) -> Self;
+    fn cos(self) -> Self;
+    fn cosh(self) -> Self;
+    fn floor(self) -> Self;
+    fn hypot(self, other: Self) -> Self;
+    fn powi(self, n: i32) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
+    fn round(self) -> Self;
+    fn sin(self) -> Self;
+    fn sin_cos(self) -> (Self, Self);
+    fn sinh(self) -> Self;
+    fn sqrt(self) -> Self;
+}
+impl Float for f64 {
+    fn asin(self) -> Self {
+        libm::asin(self)
+    }
+
+    fn atan(self) -> Self {
+        libm::atan(self)
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        libm::atan2(self, other)
+    }
+
+    fn atanh(self) -> Self {
+        libm::atanh(self)
+    }
+
+    fn cbrt(self) -> Self {
+        libm::cbrt(self)
+    }
+
+    fn ceil(self) -> Self {
+        libm::ceil(self)
+    }
+
+    fn cos(self) -> Self {
+        libm::cos(self)
+    }
+
+    fn cosh(self) -> Self {
+        libm::cosh(self)
+    }
+
+    fn floor(self) -> Self {
+        libm::floor(self)
+    }
+
+    fn hypot(self, other: Self) -> Self {
+        libm::hypot(self, other)
+    }
+
+    fn powi(self, n: i32) -> Self {
+        libm::pow(self, f64::from(n))
+    }
+
+    fn rem_euclid(self, rhs: Self) -> Self {
+        let r = libm::fmod(self, rhs);
+        if r < 0. {
+            r + if rhs < 0. { -rhs } else { rhs }
+        } else {
+            r
+        }
+    }
+
+    fn round(self) -> Self {
+        libm::round(self)
+    }
+
+    fn sin(self) -> Self {
+        libm::sin(self)
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        libm::sincos(self)
+    }
+
+    fn sinh(self) -> Self {
+        libm::sinh(self)
+    }
+
+    fn sqrt(self) -> Self {
+        libm::sqrt(self)
+    }
+}
@@ -1,4 +1,4 @@
-use std::{mem, slice};
+use core::{mem, slice};
 
 use crate::{grid::Dot, Grid};
 
@@ -13,7 +13,7 @@ pub const TOUHOKUTAIHEIYOUOKI2011: Bin<1932636> =
 #[repr(align(4))]
 pub struct Bin<const N: usize>([u8; N]);
 impl<const N: usize> Bin<N> {
-    pub const fn to_grid(&self) -> Grid {
+    pub const fn to_grid(&self) -> Grid<'_> {
         assert!(isize::MAX as usize > N);
         let data = self.0.as_ptr() as *const Dot;
         let len = self.0.len() / mem::size_of::<Dot>();
@@ -0,0 +1,280 @@
+use crate::{Ellipsoid, LatLon};
+
+#[cfg(not(any(feature = "std", test)))]
+use crate::math::Float;
+
+/// Coordinate on a plane in meters.
+///
+/// 平面座標。国土地理院の慣例に従い、X は北向き、Y は東向き。
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Xy {
+    x: f64,
+    y: f64,
+}
+impl Xy {
+    /// Constructs a [`Xy`] with northing X and easting Y in meters.
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    /// Returns northing in meters.
+    pub fn x(&self) -> f64 {
+        self.x
+    }
+
+    /// Returns easting in meters.
+    pub fn y(&self) -> f64 {
+        self.y
+    }
+}
+
+/// Gauss-Krüger projection.
+///
+/// 横メルカトル図法。国土地理院の換算式 (河瀬, 2011) と同じく、第三扁平率の級数展開による。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{LatLon, TransverseMercator, GRS80};
+///
+/// let tm = TransverseMercator::new(&GRS80, LatLon(36.0, 139.0 + 50.0 / 60.0), 0.9999);
+/// let xy = tm.project(LatLon(36.1, 139.9));
+/// # assert!(xy.x() > 0.0 && xy.y() > 0.0);
+/// let degrees = tm.unproject(xy);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TransverseMercator {
+    origin: LatLon,
+    scale: f64,
+    // 2√n / (1 + n)
+    e: f64,
+    // 縮尺係数を掛けた正距半径
+    a: f64,
+    // 原点の子午線弧長に縮尺係数を掛けたもの
+    s0: f64,
+    alpha: [f64; 5],
+    beta: [f64; 5],
+    delta: [f64; 6],
+}
+impl TransverseMercator {
+    /// Constructs a [`TransverseMercator`] with the origin in degrees and the scale factor on the central meridian.
+    pub fn new(ellipsoid: &Ellipsoid, origin: LatLon, scale: f64) -> Self {
+        let n = ellipsoid.third_flattening();
+        let [n2, n3, n4, n5, n6] = [n.powi(2), n.powi(3), n.powi(4), n.powi(5), n.powi(6)];
+        let alpha = [
+            n / 2. - 2. / 3. * n2 + 5. / 16. * n3 + 41. / 180. * n4 - 127. / 288. * n5,
+            13. / 48. * n2 - 3. / 5. * n3 + 557. / 1440. * n4 + 281. / 630. * n5,
+            61. / 240. * n3 - 103. / 140. * n4 + 15061. / 26880. * n5,
+            49561. / 161280. * n4 - 179. / 168. * n5,
+            34729. / 80640. * n5,
+        ];
+        let beta = [
+            n / 2. - 2. / 3. * n2 + 37. / 96. * n3 - 1. / 360. * n4 - 81. / 512. * n5,
+            1. / 48. * n2 + 1. / 15. * n3 - 437. / 1440. * n4 + 46. / 105. * n5,
+            17. / 480. * n3 - 37. / 840. * n4 - 209. / 4480. * n5,
+            4397. / 161280. * n4 - 11. / 504. * n5,
+            4583. / 161280. * n5,
+        ];
+        let delta = [
+            2. * n - 2. / 3. * n2 - 2. * n3 + 116. / 45. * n4 + 26. / 45. * n5 - 2854. / 675. * n6,
+            7. / 3. * n2 - 8. / 5. * n3 - 227. / 45. * n4 + 2704. / 315. * n5 + 2323. / 945. * n6,
+            56. / 15. * n3 - 136. / 35. * n4 - 1262. / 105. * n5 + 73814. / 2835. * n6,
+            4279. / 630. * n4 - 332. / 35. * n5 - 399572. / 14175. * n6,
+            4174. / 315. * n5 - 144838. / 6237. * n6,
+            601676. / 22275. * n6,
+        ];
+        Self {
+            origin,
+            scale,
+            e: 2. * n.sqrt() / (1. + n),
+            a: scale * ellipsoid.rectifying_radius(),
+            s0: scale * ellipsoid.meridian_arc_length(*origin.lat()),
+            alpha,
+            beta,
+            delta,
+        }
+    }
+
+    /// Returns the origin in degrees.
+    pub fn origin(&self) -> LatLon {
+        self.origin
+    }
+
+    /// Returns the scale factor on the central meridian.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Projects a coordinate in degrees onto the plane.
+    pub fn project(&self, degrees: LatLon) -> Xy {
+        let LatLon(lat, lon) = degrees.map(f64::to_radians);
+        let lon0 = self.origin.lon().to_radians();
+        let sin_lat = lat.sin();
+        let t = (sin_lat.atanh() - self.e * (self.e * sin_lat).atanh()).sinh();
+        let (sin_dlon, cos_dlon) = (lon - lon0).sin_cos();
+        let xi = t.atan2(cos_dlon);
+        let eta = (sin_dlon / t.hypot(1.)).atanh();
+
+        let mut x = xi;
+        let mut y = eta;
+        for (alpha, j) in self.alpha.iter().zip(1..) {
+            let j = 2. * j as f64;
+            x += alpha * (j * xi).sin() * (j * eta).cosh();
+            y += alpha * (j * xi).cos() * (j * eta).sinh();
+        }
+        Xy::new(self.a * x - self.s0, self.a * y)
+    }
+
+    /// Converts a coordinate on the plane to degrees.
+    pub fn unproject(&self, xy: Xy) -> LatLon {
+        let xi = (xy.x + self.s0) / self.a;
+        let eta = xy.y / self.a;
+
+        let mut xi1 = xi;
+        let mut eta1 = eta;
+        for (beta, j) in self.beta.iter().zip(1..) {
+            let j = 2. * j as f64;
+            xi1 -= beta * (j * xi).sin() * (j * eta).cosh();
+            eta1 -= beta * (j * xi).cos() * (j * eta).sinh();
+        }
+        // 等角緯度
+        let chi = (xi1.sin() / eta1.cosh()).asin();
+        let lat = chi
+            + self
+                .delta
+                .iter()
+                .zip(1..)
+                .map(|(delta, j)| delta * (2. * j as f64 * chi).sin())
+                .sum::<f64>();
+        let dlon = eta1.sinh().atan2(xi1.cos());
+        LatLon(lat.to_degrees(), self.origin.lon() + dlon.to_degrees())
+    }
+}
+
+/// Zone of the plane rectangular coordinate system.
+///
+/// 平面直角座標系の系番号 (1〜19)。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{LatLon, Zone, GRS80};
+///
+/// let zone = Zone::new(9).unwrap();
+/// let xy = zone.projection(&GRS80).project(LatLon(35.7, 139.7));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Zone(u8);
+impl Zone {
+    /// Scale factor on the central meridian, 0.9999.
+    pub const SCALE: f64 = 0.9999;
+
+    /// Constructs a [`Zone`] with the number from 1 to 19.
+    pub fn new(number: u8) -> Option<Self> {
+        (1..=19).contains(&number).then_some(Self(number))
+    }
+
+    /// Returns the number from 1 to 19.
+    pub fn number(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the origin in degrees.
+    ///
+    /// 出典: 平面直角座標系 (平成十四年国土交通省告示第九号)
+    pub fn origin(self) -> LatLon {
+        let (lat, lon_d, lon_m) = match self.0 {
+            1 => (33, 129, 30),
+            2 => (33, 131, 0),
+            3 => (36, 132, 10),
+            4 => (33, 133, 30),
+            5 => (36, 134, 20),
+            6 => (36, 136, 0),
+            7 => (36, 137, 10),
+            8 => (36, 138, 30),
+            9 => (36, 139, 50),
+            10 => (40, 140, 50),
+            11 => (44, 140, 15),
+            12 => (44, 142, 15),
+            13 => (44, 144, 15),
+            14 => (26, 142, 0),
+            15 => (26, 127, 30),
+            16 => (26, 124, 0),
+            17 => (26, 131, 0),
+            18 => (20, 136, 0),
+            19 => (26, 154, 0),
+            _ => unreachable!(),
+        };
+        LatLon(f64::from(lat), f64::from(lon_d) + f64::from(lon_m) / 60.)
+    }
+
+    /// Returns the projection of the zone on the ellipsoid.
+    pub fn projection(self, ellipsoid: &Ellipsoid) -> TransverseMercator {
+        TransverseMercator::new(ellipsoid, self.origin(), Self::SCALE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{LatLon, GRS80};
+
+    use super::{TransverseMercator, Zone};
+
+    #[test]
+    fn origin_is_zero() {
+        let zone = Zone::new(9).unwrap();
+        let xy = zone.projection(&GRS80).project(zone.origin());
+        assert_abs_diff_eq!(xy.x(), 0., epsilon = 1e-9);
+        assert_abs_diff_eq!(xy.y(), 0., epsilon = 1e-9);
+    }
+
+    #[test]
+    fn central_meridian() {
+        // 中央子午線上では、子午線弧長に縮尺係数を掛けた長さとなる
+        let sut = TransverseMercator::new(&GRS80, LatLon(33., 131.), 0.9999);
+        let xy = sut.project(LatLon(34.5, 131.));
+        let expected = 0.9999 * (GRS80.meridian_arc_length(34.5) - GRS80.meridian_arc_length(33.));
+        assert_abs_diff_eq!(xy.x(), expected, epsilon = 1e-6);
+        assert_abs_diff_eq!(xy.y(), 0., epsilon = 1e-9);
+    }
+
+    #[test]
+    fn round_trip() {
+        for number in 1..=19 {
+            let zone = Zone::new(number).unwrap();
+            let sut = zone.projection(&GRS80);
+            let origin = zone.origin();
+            for d in [LatLon(0.5, 1.5), LatLon(-1.2, -0.8), LatLon(2., -2.)] {
+                let degrees = origin + d;
+                let ret = sut.unproject(sut.project(degrees));
+                assert_abs_diff_eq!(ret.lat(), degrees.lat(), epsilon = 1e-10);
+                assert_abs_diff_eq!(ret.lon(), degrees.lon(), epsilon = 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn scale_off_meridian() {
+        // 中央子午線から約90km離れた東西方向の短い線分の縮尺係数
+        let sut = TransverseMercator::new(&GRS80, LatLon(36., 139.), 0.9999);
+        let [p, q] = [LatLon(36., 140.), LatLon(36., 140.0001)];
+        let plane = {
+            let (p, q) = (sut.project(p), sut.project(q));
+            (q.x() - p.x()).hypot(q.y() - p.y())
+        };
+        let ellipsoid = GRS80.geodesic_inverse(p, q).distance();
+        let y = sut.project(p).y();
+        let r = GRS80.gaussian_radius(36.);
+        let expected = 0.9999 * (1. + y.powi(2) / (2. * r.powi(2) * 0.9999_f64.powi(2)));
+        assert_abs_diff_eq!(plane / ellipsoid, expected, epsilon = 1e-7);
+    }
+
+    #[test]
+    fn zone_range() {
+        assert!(Zone::new(0).is_none());
+        assert!(Zone::new(20).is_none());
+        assert_eq!(Zone::new(19).unwrap().origin(), LatLon(26., 154.));
+    }
+}
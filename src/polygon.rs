@@ -0,0 +1,227 @@
+use crate::{
+    geodesic::{ang_diff, ang_normalize, remainder, sum_x, Karney},
+    Ellipsoid, LatLon,
+};
+
+impl Ellipsoid {
+    /// Returns the perimeter of a ring in meters.
+    ///
+    /// 各頂点を測地線で結んだ閉じた環の周長。
+    /// 最後の頂点から最初の頂点への辺は自動的に補われる。
+    pub fn geodesic_perimeter(&self, ring: &[LatLon]) -> f64 {
+        let karney = Karney::new(self);
+        let mut ret = Accumulator::default();
+        for (from, to) in edges(ring) {
+            ret.add(karney.inverse(from, to).s12);
+        }
+        ret.sum()
+    }
+
+    /// Returns the signed area of a ring in square meters.
+    ///
+    /// 各頂点を測地線で結んだ閉じた環の面積。
+    /// 反時計回りの環は正、時計回りの環は負の値となる。
+    /// 最後の頂点から最初の頂点への辺は自動的に補われる。
+    ///
+    /// 極を囲む環や、経度 ±180 度をまたぐ環も扱える。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jgd::{LatLon, GRS80};
+    ///
+    /// let ring = [
+    ///     LatLon(35.0, 135.0),
+    ///     LatLon(35.0, 135.001),
+    ///     LatLon(35.001, 135.001),
+    ///     LatLon(35.001, 135.0),
+    /// ];
+    /// let square_meters = GRS80.geodesic_signed_area(&ring);
+    /// assert!(square_meters > 0.0);
+    /// ```
+    pub fn geodesic_signed_area(&self, ring: &[LatLon]) -> f64 {
+        let karney = Karney::new(self);
+        let mut area = Accumulator::default();
+        let mut crossings = 0;
+        for (from, to) in edges(ring) {
+            area.add(karney.inverse(from, to).area);
+            crossings += transit(*from.lon(), *to.lon());
+        }
+
+        let area0 = karney.area0();
+        let mut ret = area.remainder(area0);
+        if crossings % 2 != 0 {
+            ret += if ret < 0. { 1. } else { -1. } * area0 / 2.;
+        }
+        // counter-clockwise is positive
+        ret = -ret;
+        if ret > area0 / 2. {
+            ret -= area0;
+        } else if ret <= -area0 / 2. {
+            ret += area0;
+        }
+        ret + 0.
+    }
+
+    /// Returns the area of a polygon in square meters.
+    ///
+    /// 外周の面積から各穴 (内周) の面積を差し引いた面積。
+    /// 各環の向きは問わない。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jgd::{LatLon, GRS80};
+    ///
+    /// let exterior = [
+    ///     LatLon(35.0, 135.0),
+    ///     LatLon(35.001, 135.0),
+    ///     LatLon(35.001, 135.001),
+    ///     LatLon(35.0, 135.001),
+    /// ];
+    /// let hole = [
+    ///     LatLon(35.0004, 135.0004),
+    ///     LatLon(35.0006, 135.0004),
+    ///     LatLon(35.0006, 135.0006),
+    ///     LatLon(35.0004, 135.0006),
+    /// ];
+    /// let square_meters = GRS80.geodesic_area(&exterior, &[&hole]);
+    /// ```
+    pub fn geodesic_area(&self, exterior: &[LatLon], interiors: &[&[LatLon]]) -> f64 {
+        let mut ret = Accumulator::default();
+        ret.add(self.geodesic_signed_area(exterior).abs());
+        for interior in interiors {
+            ret.add(-self.geodesic_signed_area(interior).abs());
+        }
+        ret.sum()
+    }
+}
+
+/// Edges of a ring including the closing one.
+fn edges(ring: &[LatLon]) -> impl Iterator<Item = (LatLon, LatLon)> + '_ {
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(from, to)| (*from, *to))
+}
+
+/// Counts crossings of the prime meridian, +1 for eastward and -1 for westward.
+fn transit(lon1: f64, lon2: f64) -> i32 {
+    let (lon12, _) = ang_diff(lon1, lon2);
+    let lon1 = ang_normalize(lon1);
+    let lon2 = ang_normalize(lon2);
+    if lon12 > 0. && ((lon1 < 0. && lon2 >= 0.) || (lon1 > 0. && lon2 == 0.)) {
+        1
+    } else if lon12 < 0. && lon1 >= 0. && lon2 < 0. {
+        -1
+    } else {
+        0
+    }
+}
+
+/// Sum with the rounding error compensated.
+///
+/// 各辺の面積は環の面積に比べて非常に大きいため、桁落ちを防ぐ。
+#[derive(Debug, Clone, Copy, Default)]
+struct Accumulator {
+    s: f64,
+    t: f64,
+}
+impl Accumulator {
+    fn add(&mut self, y: f64) {
+        let (y, u) = sum_x(y, self.t);
+        let (s, t) = sum_x(y, self.s);
+        self.s = s;
+        self.t = t;
+        if self.s == 0. {
+            self.s = u;
+        } else {
+            self.t += u;
+        }
+    }
+
+    fn sum(&self) -> f64 {
+        self.s + self.t
+    }
+
+    fn remainder(&self, y: f64) -> f64 {
+        let mut ret = Self {
+            s: remainder(self.s, y),
+            t: self.t,
+        };
+        ret.add(0.);
+        ret.sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use crate::{LatLon, GRS80};
+
+    const SQUARE: [LatLon; 4] = [
+        LatLon(35.0, 135.0),
+        LatLon(35.0, 135.001),
+        LatLon(35.001, 135.001),
+        LatLon(35.001, 135.0),
+    ];
+
+    #[test]
+    fn orientation() {
+        let ccw = GRS80.geodesic_signed_area(&SQUARE);
+        let mut cw = SQUARE;
+        cw.reverse();
+        assert!(ccw > 0.);
+        assert_eq!(GRS80.geodesic_signed_area(&cw), -ccw);
+    }
+
+    #[test]
+    fn closed_ring() {
+        let mut closed = SQUARE.to_vec();
+        closed.push(SQUARE[0]);
+        assert_relative_eq!(
+            GRS80.geodesic_signed_area(&closed),
+            GRS80.geodesic_signed_area(&SQUARE)
+        );
+        assert_relative_eq!(
+            GRS80.geodesic_perimeter(&closed),
+            GRS80.geodesic_perimeter(&SQUARE)
+        );
+    }
+
+    #[test]
+    fn hole() {
+        let hole = [
+            LatLon(35.0004, 135.0004),
+            LatLon(35.0006, 135.0004),
+            LatLon(35.0006, 135.0006),
+            LatLon(35.0004, 135.0006),
+        ];
+        let outer = GRS80.geodesic_area(&SQUARE, &[]);
+        let ret = GRS80.geodesic_area(&SQUARE, &[&hole]);
+        assert_relative_eq!(
+            ret,
+            outer - GRS80.geodesic_area(&hole, &[]),
+            max_relative = 1e-12
+        );
+    }
+
+    #[test]
+    fn antimeridian() {
+        let ring = SQUARE.map(|LatLon(lat, lon)| LatLon(lat, lon + 45. - 0.0005));
+        let crossing = ring.map(|LatLon(lat, lon)| LatLon(lat, lon - 360.));
+        assert_relative_eq!(
+            GRS80.geodesic_signed_area(&crossing),
+            GRS80.geodesic_signed_area(&ring),
+            max_relative = 1e-9
+        );
+    }
+
+    #[test]
+    fn enclosing_pole() {
+        let ring = [0., 90., 180., 270.].map(|lon| LatLon(89., lon));
+        let area = GRS80.geodesic_signed_area(&ring);
+        assert!(area > 0.);
+        assert!(area < 1e11);
+    }
+}
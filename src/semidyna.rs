@@ -0,0 +1,264 @@
+use std::fmt;
+
+use crate::{
+    geodetic::{MICRO_SECS, SECS},
+    Dot, Grid, LatLon, Spacing,
+};
+
+/// Parameters of the semi-dynamic correction.
+///
+/// セミ・ダイナミック補正の地殻変動補正パラメータ。
+/// 元期 (2011年5月24日) から今期までの地殻変動量を、格子点毎に緯度・経度 (秒) と楕円体高 (メートル) で表す。
+///
+/// 国土地理院が毎年公開する `SemiDyna*.par` を [`SemiDynamic::parse`] で読み込んで使用する。
+/// 格子間隔は緯度 5分、経度 7分30秒 (2次メッシュ) で、3次メッシュの格子にも対応する。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{Jgd2011, LatLon, SemiDynamic};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// # let par = "MeshCode dB(sec) dL(sec) dH(m)\n53394500 0.1 0.2 0.3\n53394600 0.1 0.2 0.3\n53395500 0.1 0.2 0.3\n53395600 0.1 0.2 0.3\n";
+/// // let par = std::fs::read_to_string("SemiDyna2024.par")?;
+/// let semidyna = SemiDynamic::parse(&par)?;
+/// let reference = Jgd2011::new(LatLon(35.7, 139.7))?;
+/// let observation = reference.to_observation_epoch(&semidyna);
+/// # assert!(observation.is_some());
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SemiDynamic {
+    spacing: Spacing,
+    dots: Vec<Dot>,
+    /// dH (meters) of each dot
+    heights: Vec<f64>,
+}
+impl SemiDynamic {
+    /// Parses the text of a parameter file.
+    ///
+    /// `MeshCode` で始まる行までをヘッダとして読み飛ばし、以降の各行を
+    /// メッシュコード, dB(秒), dL(秒), dH(メートル) として読み込む。
+    /// ヘッダがない場合は先頭行から読み込む。
+    ///
+    /// メッシュコードは3次メッシュとして読み、すべての格子点が2次メッシュの南西隅にあれば
+    /// 格子間隔を2次メッシュ、そうでなければ3次メッシュとする。
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParError`] if a record cannot be parsed.
+    pub fn parse(par: &str) -> Result<Self, ParError> {
+        let lines = par.lines().enumerate();
+        let header = par
+            .lines()
+            .position(|line| line.trim_start().starts_with("MeshCode"));
+        let skip = header.map_or(0, |i| i + 1);
+
+        let mut records = lines
+            .skip(skip)
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                Record::parse(line).map_err(|message| ParError {
+                    line: i + 1,
+                    message,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        records.sort_by_key(|record| record.mesh);
+        records.dedup_by_key(|record| record.mesh);
+
+        // 2次メッシュは3次メッシュの 10 x 10 個分
+        let coarse = records
+            .iter()
+            .all(|record| record.mesh.iter().all(|n| n % 10 == 0));
+        let (spacing, ratio) = if coarse {
+            (Spacing::MESH2, 10)
+        } else {
+            (Spacing::MESH3, 1)
+        };
+        let dots = records
+            .iter()
+            .map(|record| {
+                let [lat, lon] = record.mesh.map(|n| n / ratio);
+                let [d_lat, d_lon, _] = record.shift;
+                let micro_secs = [d_lat, d_lon].map(|x| (x * MICRO_SECS / SECS).round() as i32);
+                Dot::new([lat, lon], micro_secs)
+            })
+            .collect();
+        let heights = records.iter().map(|record| record.shift[2]).collect();
+        Ok(Self {
+            spacing,
+            dots,
+            heights,
+        })
+    }
+
+    /// Get a correction for coordinate in degrees with bilinear interpolation.
+    ///
+    /// 緯度・経度の補正量 (度) と楕円体高の補正量 (メートル) の組を返す。
+    /// 四隅のいずれかのパラメータが存在しない場合は `None` を返す。
+    pub fn bilinear(&self, degrees: LatLon) -> Option<(LatLon, f64)> {
+        let shift = self.shift(degrees)?;
+        let height = Grid::with_spacing(&self.dots, self.spacing)
+            .corners(degrees)?
+            .iter()
+            .map(|&(i, LatLon(lat_weight, lon_weight))| self.heights[i] * lat_weight * lon_weight)
+            .sum();
+        Some((shift, height))
+    }
+
+    /// Returns the correction of latitude and longitude in degrees without dH.
+    fn shift(&self, degrees: LatLon) -> Option<LatLon> {
+        Grid::with_spacing(&self.dots, self.spacing).bilinear(degrees)
+    }
+
+    /// Corrects a coordinate from the reference epoch to the observation epoch.
+    ///
+    /// 高さは補正しない。
+    pub(crate) fn forward(&self, degrees: LatLon) -> Option<LatLon> {
+        Some(degrees + self.shift(degrees)?)
+    }
+
+    /// Corrects a coordinate from the observation epoch to the reference epoch.
+    ///
+    /// パラメータの格子点は元期の座標なので、補正後の座標が収束するまで反復する。
+    pub(crate) fn backward(&self, degrees: LatLon) -> Option<LatLon> {
+        const TOLERANCE: f64 = 1e-9 / SECS;
+
+        let mut ret = degrees;
+        for _ in 0..10 {
+            let next = degrees - self.shift(ret)?;
+            let LatLon(d_lat, d_lon) = next - ret;
+            ret = next;
+            if d_lat.abs() < TOLERANCE && d_lon.abs() < TOLERANCE {
+                break;
+            }
+        }
+        Some(ret)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Record {
+    /// 3rd mesh node
+    mesh: [i16; 2],
+    /// dB (seconds), dL (seconds) and dH (meters)
+    shift: [f64; 3],
+}
+impl Record {
+    fn parse(line: &str) -> Result<Self, String> {
+        let mut tokens = line.split_whitespace();
+        let mut next = |name: &str| tokens.next().ok_or_else(|| format!("missing {}", name));
+
+        let code = next("MeshCode")?;
+        let mesh = Spacing::MESH3
+            .parse_mesh_code(code)
+            .ok_or_else(|| format!("invalid MeshCode: {:?}", code))?
+            .to_array();
+
+        let mut shift = [0.; 3];
+        for (x, name) in shift.iter_mut().zip(["dB", "dL", "dH"]) {
+            let token = next(name)?;
+            *x = token
+                .parse()
+                .map_err(|_| format!("invalid {}: {:?}", name, token))?;
+        }
+        Ok(Self { mesh, shift })
+    }
+}
+
+/// Errors in parsing a parameter file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParError {
+    line: usize,
+    message: String,
+}
+impl ParError {
+    /// Returns the line number starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+impl fmt::Display for ParError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+impl std::error::Error for ParError {}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{geodetic::SECS, LatLon, Spacing};
+
+    use super::SemiDynamic;
+
+    //          7'30"
+    //  (0, 0, 0) -- (6, 6, 6)
+    //     |            | 5'
+    //  (0,-6, 0) -- (6, 0,-6)
+    const SMALLEST: &str = "\
+SemiDyna2024.par
+MeshCode dB(sec)  dL(sec) dH(m)
+53394500  -6.0  0.0  0.0
+53394600   0.0  6.0 -6.0
+53395500   0.0  0.0  0.0
+53395600   6.0  6.0  6.0
+";
+
+    #[test]
+    fn mesh_code() {
+        let err = SemiDynamic::parse("53398800 0.0 0.0 0.0").unwrap_err();
+        assert_eq!(err.to_string(), "line 1: invalid MeshCode: \"53398800\"");
+    }
+
+    #[test]
+    fn mesh3_spacing() {
+        let sut = SemiDynamic::parse(&SMALLEST.replace("53395600", "53394511")).unwrap();
+        assert_eq!(sut.spacing, Spacing::MESH3);
+        // 53394500 と 53394511 の中間は 3次メッシュ 53394500 の北東隅付近
+        let sw = LatLon(35. + 40. / 60., 139. + 37.5 / 60.);
+        assert_eq!(sut.bilinear(sw + LatLon(10., 15.) / SECS), None);
+
+        let par = "53394511 1.0 2.0 3.0\n53394512 1.0 2.0 3.0\n53394521 1.0 2.0 3.0\n53394522 1.0 2.0 3.0\n";
+        let sut = SemiDynamic::parse(par).unwrap();
+        let (shift, height) = sut.bilinear(sw + LatLon(40., 60.) / SECS).unwrap();
+        assert_abs_diff_eq!(shift.lat() * SECS, 1., epsilon = 1e-9);
+        assert_abs_diff_eq!(shift.lon() * SECS, 2., epsilon = 1e-9);
+        assert_abs_diff_eq!(height, 3., epsilon = 1e-9);
+    }
+
+    #[test]
+    fn parse_error() {
+        let err = SemiDynamic::parse("MeshCode\n53394500 0.0 x 0.0\n").unwrap_err();
+        assert_eq!(err.line(), 2);
+        assert_eq!(err.to_string(), "line 2: invalid dL: \"x\"");
+    }
+
+    #[test]
+    fn interpolate_middle() {
+        let sut = SemiDynamic::parse(SMALLEST).unwrap();
+        let sw = LatLon(35. + 40. / 60., 139. + 37.5 / 60.);
+        let (shift, height) = sut.bilinear(sw + LatLon(100., 150.) / SECS).unwrap();
+        assert_abs_diff_eq!(shift.lat() * SECS, -2., epsilon = 1e-12);
+        assert_abs_diff_eq!(shift.lon() * SECS, 2., epsilon = 1e-9);
+        assert_abs_diff_eq!(height, -2. / 3., epsilon = 1e-12);
+    }
+
+    #[test]
+    fn interpolate_out_of_grid() {
+        let sut = SemiDynamic::parse(SMALLEST).unwrap();
+        assert_eq!(sut.bilinear(LatLon(35.0, 139.0)), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        let sut = SemiDynamic::parse(SMALLEST).unwrap();
+        let degrees = LatLon(35.7, 139.7);
+        let ret = sut.backward(sut.forward(degrees).unwrap()).unwrap();
+        assert_abs_diff_eq!(ret.lat(), degrees.lat(), epsilon = 1e-12);
+        assert_abs_diff_eq!(ret.lon(), degrees.lon(), epsilon = 1e-12);
+    }
+}
@@ -0,0 +1,285 @@
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, MapAccess, SeqAccess, Unexpected, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{Datum, DatumKind, Dms, Jgd2000, Jgd2011, LatLon, Tokyo, Tokyo97};
+
+/// Serializes as `[lat, lon]`.
+impl<T: Serialize> Serialize for LatLon<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let LatLon(lat, lon) = self;
+        (lat, lon).serialize(serializer)
+    }
+}
+
+/// Deserializes from `[lat, lon]` or `{"lat": lat, "lon": lon}`.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for LatLon<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct LatLonVisitor<T>(PhantomData<T>);
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for LatLonVisitor<T> {
+            type Value = LatLon<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("[lat, lon] or {lat, lon}")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let lat = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let lon = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                    return Err(de::Error::invalid_length(3, &self));
+                }
+                Ok(LatLon(lat, lon))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let (mut lat, mut lon) = (None, None);
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "lat" if lat.is_none() => lat = Some(map.next_value()?),
+                        "lon" if lon.is_none() => lon = Some(map.next_value()?),
+                        "lat" | "lon" => {
+                            return Err(de::Error::custom(format!("duplicate field `{}`", key)))
+                        }
+                        _ => return Err(de::Error::unknown_field(&key, &["lat", "lon"])),
+                    }
+                }
+                let lat = lat.ok_or_else(|| de::Error::missing_field("lat"))?;
+                let lon = lon.ok_or_else(|| de::Error::missing_field("lon"))?;
+                Ok(LatLon(lat, lon))
+            }
+        }
+        deserializer.deserialize_any(LatLonVisitor(PhantomData))
+    }
+}
+
+/// Serializes as a string like `35°39'29.1572"`.
+///
+/// [`Dms`] の [`Display`](fmt::Display) と同じ表記で、精度は失われない。
+impl Serialize for Dms {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes from a string parsed by [`FromStr`](std::str::FromStr) or a tuple `[d, m, s]`.
+///
+/// タプルでは、いずれかの成分が負であれば全体が負の値となる。
+impl<'de> Deserialize<'de> for Dms {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DmsVisitor;
+        impl<'de> Visitor<'de> for DmsVisitor {
+            type Value = Dms;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a DMS string or [d, m, s]")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut values = [0f64; 3];
+                for (i, value) in values.iter_mut().enumerate() {
+                    *value = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+                }
+                if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                    return Err(de::Error::invalid_length(4, &self));
+                }
+                let negative = values.iter().any(|v| v.is_sign_negative());
+                let [d, m, s] = values.map(f64::abs);
+                for v in [d, m] {
+                    if v.fract() != 0. || v > f64::from(u32::MAX) {
+                        return Err(de::Error::invalid_value(
+                            Unexpected::Float(v),
+                            &"an integer",
+                        ));
+                    }
+                }
+                let dms = Dms::new(d as u32, m as u32, s).map_err(de::Error::custom)?;
+                Ok(if negative { -dms } else { dms })
+            }
+        }
+        deserializer.deserialize_any(DmsVisitor)
+    }
+}
+
+/// Serializes as the name like `"JGD2011"`.
+impl Serialize for DatumKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// Deserializes from the name like `"JGD2011"`.
+impl<'de> Deserialize<'de> for DatumKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KindVisitor;
+        impl Visitor<'_> for KindVisitor {
+            type Value = DatumKind;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a datum name")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                DatumKind::ALL
+                    .into_iter()
+                    .find(|kind| kind.name() == v)
+                    .ok_or_else(|| E::invalid_value(Unexpected::Str(v), &self))
+            }
+        }
+        deserializer.deserialize_str(KindVisitor)
+    }
+}
+
+const FIELDS: &[&str] = &["datum", "degrees"];
+
+/// Serializes a [`Datum`] as `{"datum": name, "degrees": [lat, lon]}`.
+fn serialize_datum<D: Datum, S: Serializer>(coord: &D, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut state = serializer.serialize_struct(D::NAME, FIELDS.len())?;
+    state.serialize_field("datum", &D::KIND)?;
+    state.serialize_field("degrees", &coord.degrees())?;
+    state.end()
+}
+
+/// Deserializes a [`Datum`], rejecting other datums.
+fn deserialize_datum<'de, D: Datum, De: Deserializer<'de>>(
+    deserializer: De,
+) -> Result<D, De::Error> {
+    struct DatumVisitor<D>(PhantomData<D>);
+    impl<D: Datum> DatumVisitor<D> {
+        fn build<E: de::Error>(kind: DatumKind, degrees: LatLon) -> Result<D, E> {
+            if kind != D::KIND {
+                return Err(E::invalid_value(Unexpected::Str(kind.name()), &D::NAME));
+            }
+            D::new(degrees).map_err(E::custom)
+        }
+    }
+    impl<'de, D: Datum> Visitor<'de> for DatumVisitor<D> {
+        type Value = D;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a coordinate in {}", D::NAME)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let kind = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+            let degrees = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+            Self::build(kind, degrees)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let (mut kind, mut degrees) = (None, None);
+            while let Some(key) = map.next_key::<String>()? {
+                match key.as_str() {
+                    "datum" if kind.is_none() => kind = Some(map.next_value()?),
+                    "degrees" if degrees.is_none() => degrees = Some(map.next_value()?),
+                    "datum" | "degrees" => {
+                        return Err(de::Error::custom(format!("duplicate field `{}`", key)))
+                    }
+                    _ => return Err(de::Error::unknown_field(&key, FIELDS)),
+                }
+            }
+            let kind = kind.ok_or_else(|| de::Error::missing_field("datum"))?;
+            let degrees = degrees.ok_or_else(|| de::Error::missing_field("degrees"))?;
+            Self::build(kind, degrees)
+        }
+    }
+    deserializer.deserialize_struct(D::NAME, FIELDS, DatumVisitor(PhantomData))
+}
+
+macro_rules! impl_serde_datum {
+    ($($ty:ty),*) => {
+        $(
+            /// Serializes as `{"datum": name, "degrees": [lat, lon]}`.
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serialize_datum(self, serializer)
+                }
+            }
+
+            /// Deserializes from `{"datum": name, "degrees": [lat, lon]}` of the same datum.
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserialize_datum(deserializer)
+                }
+            }
+        )*
+    };
+}
+impl_serde_datum!(Tokyo, Tokyo97, Jgd2000, Jgd2011);
+
+#[cfg(test)]
+mod tests {
+    use crate::{DegreesError, Dms, Jgd2011, LatLon, Tokyo};
+
+    #[test]
+    fn lat_lon() {
+        let degrees = LatLon(35.5, 135.25);
+        let json = serde_json::to_string(&degrees).unwrap();
+        assert_eq!(json, "[35.5,135.25]");
+        assert_eq!(serde_json::from_str::<LatLon>(&json).unwrap(), degrees);
+        let ret: LatLon = serde_json::from_str(r#"{"lon": 135.25, "lat": 35.5}"#).unwrap();
+        assert_eq!(ret, degrees);
+        assert!(serde_json::from_str::<LatLon>("[35.5]").is_err());
+        assert!(serde_json::from_str::<LatLon>(r#"{"lat": 35.5}"#).is_err());
+    }
+
+    #[test]
+    fn dms() {
+        let dms = -Dms::new(35, 39, 29.1572).unwrap();
+        let json = serde_json::to_string(&dms).unwrap();
+        assert_eq!(json, r#""-35°39'29.1572\"""#);
+        assert_eq!(serde_json::from_str::<Dms>(&json).unwrap(), dms);
+        assert_eq!(
+            serde_json::from_str::<Dms>("[-35, 39, 29.1572]").unwrap(),
+            dms
+        );
+        assert!(serde_json::from_str::<Dms>("[35, 60, 0]").is_err());
+        assert!(serde_json::from_str::<Dms>("[35.5, 0, 0]").is_err());
+
+        let dms = LatLon(Dms::new(35, 0, 0.).unwrap(), Dms::new(135, 0, 0.).unwrap());
+        let json = serde_json::to_string(&dms).unwrap();
+        assert_eq!(serde_json::from_str::<LatLon<Dms>>(&json).unwrap(), dms);
+    }
+
+    #[test]
+    fn datum() {
+        let jgd2011 = Jgd2011::new(LatLon(35.5, 135.25)).unwrap();
+        let json = serde_json::to_string(&jgd2011).unwrap();
+        assert_eq!(json, r#"{"datum":"JGD2011","degrees":[35.5,135.25]}"#);
+        assert_eq!(serde_json::from_str::<Jgd2011>(&json).unwrap(), jgd2011);
+
+        let err = serde_json::from_str::<Tokyo>(&json).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with(r#"invalid value: string "JGD2011", expected Tokyo"#));
+        let err = serde_json::from_str::<Jgd2011>(r#"{"datum":"JGD2011","degrees":[135,35]}"#)
+            .unwrap_err();
+        assert!(err.to_string().starts_with("degrees out of range"));
+    }
+
+    #[test]
+    fn degrees_error() {
+        let err = Jgd2011::new(LatLon(135., 35.)).unwrap_err();
+        let json = serde_json::to_string(&err).unwrap();
+        assert_eq!(json, r#"{"possibly_reversed":true}"#);
+        assert_eq!(serde_json::from_str::<DegreesError>(&json).unwrap(), err);
+    }
+}
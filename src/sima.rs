@@ -0,0 +1,583 @@
+use std::{
+    fmt,
+    io::{self, Read, Write},
+};
+
+use encoding_rs::SHIFT_JIS;
+
+use crate::{Crs, DegreesError, Transformer, Xy};
+
+/// Survey data in SIMA, the exchange format of JACIC.
+///
+/// 日本建設情報総合センター (JACIC) の SIMA 共通フォーマット。文字コードは Shift_JIS、改行は CRLF。
+/// 座標 (`A01`) と画地 (`D00` から `D99` まで) を解析し、その他のレコードはそのまま保持する。
+///
+/// 座標は平面直角座標で、系番号はファイルに含まれないため、変換時に [`Transformer`] で指定する。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{Crs, DatumKind, Sima, Transformer, Zone};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let input = b"G00,01,test,\r\nA00,\r\nA01,1,K1,-37934.234,-8119.123,10.000,\r\nA99,\r\n";
+/// let tokyo = Sima::read(&input[..])?;
+///
+/// let zone = Zone::new(9).unwrap();
+/// let transformer = Transformer::new(
+///     Crs::Plane(DatumKind::Tokyo, zone),
+///     Crs::Plane(DatumKind::Jgd2011, zone),
+/// )?;
+/// let jgd2011 = tokyo.transform(&transformer)?;
+///
+/// let mut output = vec![];
+/// jgd2011.write(&mut output)?;
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sima {
+    records: Vec<SimaRecord>,
+}
+impl Sima {
+    /// Constructs a [`Sima`] of the records.
+    pub fn new(records: Vec<SimaRecord>) -> Self {
+        Self { records }
+    }
+
+    /// Reads all in Shift_JIS.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimaError`] if failed to read or the data is invalid.
+    pub fn read(mut reader: impl Read) -> Result<Self, SimaError> {
+        let mut bytes = vec![];
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(|e| SimaError::new(None, ErrorKind::Io(e)))?;
+        let mut lines = vec![];
+        for (i, line) in bytes.split(|&b| b == b'\n').enumerate() {
+            let line = SHIFT_JIS
+                .decode_without_bom_handling_and_without_replacement(line)
+                .ok_or(SimaError::new(Some(i + 1), ErrorKind::Encoding))?;
+            lines.push(line.into_owned());
+        }
+        Self::parse_lines(lines.iter().map(String::as_str))
+    }
+
+    /// Parses a text already decoded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimaError`] if the data is invalid.
+    pub fn parse(text: &str) -> Result<Self, SimaError> {
+        Self::parse_lines(text.split('\n'))
+    }
+
+    fn parse_lines<'a>(lines: impl Iterator<Item = &'a str>) -> Result<Self, SimaError> {
+        let mut records = vec![];
+        let mut parcel: Option<SimaParcel> = None;
+        for (i, line) in lines.enumerate() {
+            let error = |kind| SimaError::new(Some(i + 1), kind);
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut fields: Vec<String> = line.split(',').map(str::to_owned).collect();
+            // 行末のカンマ
+            if fields.len() > 1 && fields.last().is_some_and(|f| f.trim().is_empty()) {
+                fields.pop();
+            }
+            let code = fields[0].trim().to_owned();
+
+            if let Some(mut current) = parcel.take() {
+                match code.as_str() {
+                    "D99" => records.push(SimaRecord::Parcel(current)),
+                    "D00" => return Err(error(ErrorKind::UnclosedParcel)),
+                    _ => {
+                        current.lines.push(fields);
+                        parcel = Some(current);
+                    }
+                }
+                continue;
+            }
+            match code.as_str() {
+                "A01" => records.push(SimaRecord::Point(
+                    SimaPoint::from_fields(&fields).map_err(error)?,
+                )),
+                "D00" => {
+                    fields.remove(0);
+                    parcel = Some(SimaParcel {
+                        fields,
+                        lines: vec![],
+                    });
+                }
+                "D99" => return Err(error(ErrorKind::Unexpected("D99"))),
+                _ => records.push(SimaRecord::Other(fields)),
+            }
+        }
+        if parcel.is_some() {
+            return Err(SimaError::new(None, ErrorKind::UnclosedParcel));
+        }
+        Ok(Self { records })
+    }
+
+    /// Writes all in Shift_JIS with CRLF.
+    ///
+    /// 読み込んだ座標はそのまま、変換した座標と標高は小数点以下 3桁 (1mm) で出力する。
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::Error`] if failed to write, or with [`io::ErrorKind::InvalidData`]
+    /// if a character is not representable in Shift_JIS.
+    pub fn write(&self, mut writer: impl Write) -> io::Result<()> {
+        let text = self.to_string();
+        let (bytes, _, had_errors) = SHIFT_JIS.encode(&text);
+        if had_errors {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not representable in Shift_JIS",
+            ));
+        }
+        writer.write_all(&bytes)
+    }
+
+    /// Returns all records.
+    pub fn records(&self) -> &[SimaRecord] {
+        &self.records
+    }
+
+    /// Returns an iterator over the points.
+    pub fn points(&self) -> impl Iterator<Item = &SimaPoint> {
+        self.records.iter().filter_map(|record| match record {
+            SimaRecord::Point(point) => Some(point),
+            _ => None,
+        })
+    }
+
+    /// Returns an iterator over the parcels.
+    pub fn parcels(&self) -> impl Iterator<Item = &SimaParcel> {
+        self.records.iter().filter_map(|record| match record {
+            SimaRecord::Parcel(parcel) => Some(parcel),
+            _ => None,
+        })
+    }
+
+    /// Transforms all points, keeping the other records.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimaError`] if `transformer` is not between plane CRSs,
+    /// or if a point in degrees is out of range.
+    pub fn transform(&self, transformer: &Transformer) -> Result<Self, SimaError> {
+        let records = self
+            .records
+            .iter()
+            .map(|record| match record {
+                SimaRecord::Point(point) => point.transform(transformer).map(SimaRecord::Point),
+                _ => Ok(record.clone()),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { records })
+    }
+}
+/// Formats as SIMA text with CRLF.
+impl fmt::Display for Sima {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn line<T: fmt::Display>(f: &mut fmt::Formatter<'_>, fields: &[T]) -> fmt::Result {
+            for field in fields {
+                write!(f, "{},", field)?;
+            }
+            f.write_str("\r\n")
+        }
+        for record in &self.records {
+            match record {
+                SimaRecord::Point(point) if !point.fields.is_empty() => line(f, &point.fields)?,
+                SimaRecord::Point(point) => {
+                    let xy = point.xy;
+                    let height = point.height.map(|h| format!("{:.3}", h));
+                    line(
+                        f,
+                        &[
+                            "A01",
+                            &point.number,
+                            &point.name,
+                            &format!("{:.3}", xy.x()),
+                            &format!("{:.3}", xy.y()),
+                            height.as_deref().unwrap_or(""),
+                        ],
+                    )?;
+                }
+                SimaRecord::Parcel(parcel) => {
+                    f.write_str("D00,")?;
+                    line(f, &parcel.fields)?;
+                    for fields in &parcel.lines {
+                        line(f, fields)?;
+                    }
+                    line(f, &["D99"])?;
+                }
+                SimaRecord::Other(fields) => line(f, fields)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A record of [`Sima`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimaRecord {
+    /// `A01` point.
+    Point(SimaPoint),
+    /// Block from `D00` to `D99`.
+    Parcel(SimaParcel),
+    /// Other record like `G00` or `A00` in fields including the code.
+    Other(Vec<String>),
+}
+
+/// `A01` point record of [`Sima`].
+///
+/// `A01,点番号,点名,X座標,Y座標,標高,` の形式。X は北向き、Y は東向きで、単位は m。
+///
+/// 読み込んだレコードは、標高より後ろのフィールドも含めて元の文字列のまま書き出す。
+/// 変換や変更をした座標と標高のみ、小数点以下 3桁 (1mm) で書き直す。
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimaPoint {
+    number: String,
+    name: String,
+    xy: Xy,
+    height: Option<f64>,
+    /// Fields as read including the code, or empty if constructed.
+    fields: Vec<String>,
+}
+impl SimaPoint {
+    /// Constructs a [`SimaPoint`] without height.
+    pub fn new(number: impl Into<String>, name: impl Into<String>, xy: Xy) -> Self {
+        Self {
+            number: number.into(),
+            name: name.into(),
+            xy,
+            height: None,
+            fields: vec![],
+        }
+    }
+
+    /// Returns self with the height in meters.
+    pub fn with_height(mut self, height: f64) -> Self {
+        self.height = Some(height);
+        self.set_field(5, format!("{:.3}", height));
+        self
+    }
+
+    /// Returns the point number.
+    pub fn number(&self) -> &str {
+        &self.number
+    }
+
+    /// Returns the point name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the plane rectangular coordinate.
+    pub fn xy(&self) -> Xy {
+        self.xy
+    }
+
+    /// Returns the height in meters, if any.
+    pub fn height(&self) -> Option<f64> {
+        self.height
+    }
+
+    /// Returns the fields after the height as read.
+    pub fn extra_fields(&self) -> &[String] {
+        self.fields.get(6..).unwrap_or_default()
+    }
+
+    /// Transforms the coordinate, keeping the others.
+    ///
+    /// 標高は変換しない。
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimaError`] if `transformer` is not between plane CRSs,
+    /// or if the coordinate in degrees is out of range.
+    pub fn transform(&self, transformer: &Transformer) -> Result<Self, SimaError> {
+        let error = |kind| SimaError::new(None, kind);
+        let (Crs::Plane(..), Crs::Plane(..)) = (transformer.from(), transformer.to()) else {
+            return Err(error(ErrorKind::Crs(transformer.from(), transformer.to())));
+        };
+        let [x, y] = transformer
+            .transform([self.xy.x(), self.xy.y()])
+            .map_err(|e| error(ErrorKind::Point(self.number.clone(), e)))?;
+        let mut ret = self.clone();
+        ret.xy = Xy::new(x, y);
+        ret.set_field(3, format!("{:.3}", x));
+        ret.set_field(4, format!("{:.3}", y));
+        Ok(ret)
+    }
+
+    /// Replaces a field as read, if any.
+    fn set_field(&mut self, index: usize, value: String) {
+        if self.fields.is_empty() {
+            return;
+        }
+        if self.fields.len() <= index {
+            self.fields.resize(index + 1, String::new());
+        }
+        self.fields[index] = value;
+    }
+
+    fn from_fields(fields: &[String]) -> Result<Self, ErrorKind> {
+        let field = |index: usize, label| {
+            fields
+                .get(index)
+                .map(|f| f.trim())
+                .filter(|f| !f.is_empty())
+                .ok_or(ErrorKind::Missing(label))
+        };
+        let number = |index, label| {
+            field(index, label)?
+                .parse::<f64>()
+                .map_err(|_| ErrorKind::Invalid(label))
+        };
+        let height = match field(5, "height") {
+            Ok(_) => Some(number(5, "height")?),
+            Err(_) => None,
+        };
+        Ok(Self {
+            number: field(1, "point number")?.to_owned(),
+            name: fields.get(2).cloned().unwrap_or_default(),
+            xy: Xy::new(number(3, "X")?, number(4, "Y")?),
+            height,
+            fields: fields.to_vec(),
+        })
+    }
+}
+
+/// Parcel block from `D00` to `D99` of [`Sima`].
+///
+/// `D00,画地番号,画地名,...,` に続き、構成点を `B01,点番号,点名,` で列挙する。
+/// `B01` 以外のレコードもそのまま保持する。
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimaParcel {
+    /// Fields of `D00` after the code.
+    fields: Vec<String>,
+    /// Records between `D00` and `D99`.
+    lines: Vec<Vec<String>>,
+}
+impl SimaParcel {
+    /// Constructs a [`SimaParcel`] of the point numbers and names.
+    pub fn new<N, P>(number: impl Into<String>, name: impl Into<String>, points: P) -> Self
+    where
+        P: IntoIterator<Item = (N, N)>,
+        N: Into<String>,
+    {
+        let lines = points
+            .into_iter()
+            .map(|(number, name)| vec!["B01".to_owned(), number.into(), name.into()])
+            .collect();
+        Self {
+            fields: vec![number.into(), name.into()],
+            lines,
+        }
+    }
+
+    /// Returns the parcel number.
+    pub fn number(&self) -> &str {
+        self.fields.first().map_or("", String::as_str)
+    }
+
+    /// Returns the parcel name.
+    pub fn name(&self) -> &str {
+        self.fields.get(1).map_or("", String::as_str)
+    }
+
+    /// Returns an iterator over the point numbers and names of the `B01` records.
+    pub fn points(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.lines
+            .iter()
+            .filter(|fields| fields[0].trim() == "B01")
+            .map(|fields| {
+                let field = |i| fields.get(i).map_or("", |f: &String| f.trim());
+                (field(1), field(2))
+            })
+    }
+}
+
+/// Errors in reading or transforming [`Sima`].
+#[derive(Debug)]
+pub struct SimaError {
+    line: Option<usize>,
+    kind: ErrorKind,
+}
+impl SimaError {
+    fn new(line: Option<usize>, kind: ErrorKind) -> Self {
+        Self { line, kind }
+    }
+
+    /// Returns the line number starting from 1, if any.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+}
+impl fmt::Display for SimaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        match &self.kind {
+            ErrorKind::Io(e) => write!(f, "{}", e),
+            ErrorKind::Encoding => f.write_str("invalid Shift_JIS"),
+            ErrorKind::Missing(label) => write!(f, "missing {}", label),
+            ErrorKind::Invalid(label) => write!(f, "invalid {}", label),
+            ErrorKind::Unexpected(code) => write!(f, "unexpected {}", code),
+            ErrorKind::UnclosedParcel => f.write_str("D00 without D99"),
+            ErrorKind::Point(number, e) => write!(f, "point {}: {}", number, e),
+            ErrorKind::Crs(from, to) => write!(f, "not between plane CRSs: {} to {}", from, to),
+        }
+    }
+}
+impl std::error::Error for SimaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Io(e) => Some(e),
+            ErrorKind::Point(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum ErrorKind {
+    Io(io::Error),
+    Encoding,
+    Missing(&'static str),
+    Invalid(&'static str),
+    Unexpected(&'static str),
+    UnclosedParcel,
+    Point(String, DegreesError),
+    Crs(Crs, Crs),
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::SHIFT_JIS;
+
+    use crate::{Crs, DatumKind, Transformer, Xy, Zone};
+
+    use super::{Sima, SimaParcel, SimaPoint, SimaRecord};
+
+    const TEXT: &str = "G00,01,測量,\r\nZ00,座標ﾃﾞｰﾀ,\r\nA00,\r\nA01,1,基準点,-37934.234,-8119.123,10.500,\r\nA01,2,K2,-37900.0,-8100,,ﾒﾓ,\r\nA99,\r\nD00,1,1番地,\r\nB01,1,基準点,\r\nB01,2,K2,\r\nD99,\r\n";
+
+    #[test]
+    fn round_trip() {
+        let (input, _, _) = SHIFT_JIS.encode(TEXT);
+        let sima = Sima::read(&input[..]).unwrap();
+        assert_eq!(sima.records().len(), 7);
+
+        let points: Vec<_> = sima.points().collect();
+        let expected =
+            SimaPoint::new("1", "基準点", Xy::new(-37934.234, -8119.123)).with_height(10.5);
+        assert_eq!(
+            (points[0].number(), points[0].name(), points[0].xy()),
+            (expected.number(), expected.name(), expected.xy())
+        );
+        assert_eq!(points[0].height(), expected.height());
+        assert_eq!(points[1].height(), None);
+        assert_eq!(points[1].extra_fields(), ["ﾒﾓ"]);
+
+        let parcel = sima.parcels().next().unwrap();
+        assert_eq!(
+            *parcel,
+            SimaParcel::new("1", "1番地", [("1", "基準点"), ("2", "K2")])
+        );
+        assert_eq!(
+            sima.records()[0],
+            SimaRecord::Other(vec!["G00".into(), "01".into(), "測量".into()])
+        );
+
+        let mut output = vec![];
+        sima.write(&mut output).unwrap();
+        assert_eq!(output, &input[..]);
+    }
+
+    #[test]
+    fn errors() {
+        for (text, message) in [
+            ("A01,1,P,x,0,\r\n", "line 1: invalid X"),
+            ("A00,\r\nA01,1,P,0,\r\n", "line 2: missing Y"),
+            ("A01,,P,0,0,\r\n", "line 1: missing point number"),
+            ("D00,1,\r\nB01,1,P,\r\n", "D00 without D99"),
+            ("D99,\r\n", "line 1: unexpected D99"),
+        ] {
+            assert_eq!(Sima::parse(text).unwrap_err().to_string(), message);
+        }
+        let err = Sima::read(&b"A00,\r\n\x82\r\n"[..]).unwrap_err();
+        assert_eq!(err.to_string(), "line 2: invalid Shift_JIS");
+
+        let sima = Sima::new(vec![SimaRecord::Other(vec!["G00".into(), "😀".into()])]);
+        assert!(sima.write(vec![]).is_err());
+    }
+
+    #[test]
+    fn transform() {
+        let zone = Zone::new(9).unwrap();
+        let transformer = Transformer::new(
+            Crs::Plane(DatumKind::Tokyo97, zone),
+            Crs::Plane(DatumKind::Jgd2000, zone),
+        )
+        .unwrap();
+        let sima = Sima::parse(TEXT).unwrap();
+        let ret = sima.transform(&transformer).unwrap();
+        let points: Vec<_> = ret.points().collect();
+        let [x, y] = transformer.transform([-37934.234, -8119.123]).unwrap();
+        assert_eq!(points[0].xy(), Xy::new(x, y));
+        assert_eq!(points[0].height(), Some(10.5));
+        assert_eq!(ret.parcels().count(), 1);
+        assert_eq!(ret.records()[0], sima.records()[0]);
+
+        // 変換した座標のみ書き直し、標高と他のフィールドは元のまま
+        let [x, y] = transformer.transform([-37900., -8100.]).unwrap();
+        let expected = format!("A01,2,K2,{:.3},{:.3},,ﾒﾓ,\r\n", x, y);
+        assert!(ret.to_string().contains(&expected), "{}", ret);
+        assert!(ret.to_string().contains(",10.500,\r\n"));
+
+        let geographic = Transformer::new(
+            Crs::Geographic(DatumKind::Tokyo97),
+            Crs::Geographic(DatumKind::Jgd2000),
+        )
+        .unwrap();
+        let err = sima.transform(&geographic).unwrap_err();
+        assert_eq!(err.line(), None);
+        assert!(err.to_string().starts_with("not between plane CRSs"));
+    }
+
+    #[cfg(all(feature = "tky2jgd", feature = "patchjgd"))]
+    #[test]
+    fn tokyo_to_jgd2011() {
+        use crate::{LatLon, Tokyo, BESSEL, GRS80};
+
+        let zone = Zone::new(9).unwrap();
+        let transformer = Transformer::new(
+            Crs::Plane(DatumKind::Tokyo, zone),
+            Crs::Plane(DatumKind::Jgd2011, zone),
+        )
+        .unwrap();
+        let sima = Sima::parse(TEXT).unwrap().transform(&transformer).unwrap();
+
+        let tokyo = zone
+            .projection(&BESSEL)
+            .unproject(Xy::new(-37934.234, -8119.123));
+        let LatLon(lat, lon) = Tokyo::new(tokyo)
+            .unwrap()
+            .to_jgd2000()
+            .to_jgd2011()
+            .degrees();
+        let expected = zone.projection(&GRS80).project(LatLon(lat, lon));
+        let xy = sima.points().next().unwrap().xy();
+        assert!((xy.x() - expected.x()).abs() < 1e-6);
+        assert!((xy.y() - expected.y()).abs() < 1e-6);
+        // 旧日本測地系から世界測地系へは数百 m 移動する
+        assert!((xy.x() + 37934.234).abs() > 100.);
+    }
+}
@@ -0,0 +1,621 @@
+use std::{fmt, str::FromStr};
+
+use crate::{DatumKind, DegreesError, Jgd2000, LatLon, Tokyo97, TransverseMercator, Xy, Zone};
+
+#[cfg(feature = "patchjgd")]
+use crate::{Jgd2011, TOUHOKUTAIHEIYOUOKI2011};
+#[cfg(feature = "tky2jgd")]
+use crate::{Tokyo, TKY2JGD};
+
+/// Coordinate reference system known at runtime.
+///
+/// 実行時に決まる座標参照系。EPSG コードから構築できる。
+///
+/// | 測地系 | 緯度経度 | 平面直角座標系 (1〜19系) |
+/// | --- | --- | --- |
+/// | [`Tokyo`](crate::Tokyo) | 4301 | 30161〜30179 |
+/// | [`Jgd2000`] | 4612 | 2443〜2461 |
+/// | [`Jgd2011`](crate::Jgd2011) | 6668 | 6669〜6687 |
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{Crs, DatumKind, Zone};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let crs: Crs = "EPSG:6677".parse()?;
+/// assert_eq!(crs, Crs::Plane(DatumKind::Jgd2011, Zone::new(9).unwrap()));
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Crs {
+    /// Latitude and longitude in degrees.
+    Geographic(DatumKind),
+    /// Plane rectangular coordinate system.
+    Plane(DatumKind, Zone),
+}
+impl Crs {
+    /// Constructs from an EPSG code.
+    pub fn from_epsg(code: u32) -> Option<Self> {
+        let datum = DatumKind::ALL
+            .into_iter()
+            .find(|datum| datum.epsg() == Some(code));
+        if let Some(datum) = datum {
+            return Some(Crs::Geographic(datum));
+        }
+        let (datum, first) = match code {
+            30161..=30179 => (DatumKind::Tokyo, 30161),
+            2443..=2461 => (DatumKind::Jgd2000, 2443),
+            6669..=6687 => (DatumKind::Jgd2011, 6669),
+            _ => return None,
+        };
+        let zone = Zone::new((code - first + 1) as u8)?;
+        Some(Crs::Plane(datum, zone))
+    }
+
+    /// Returns the EPSG code, if registered.
+    pub fn epsg(self) -> Option<u32> {
+        match self {
+            Crs::Geographic(datum) => datum.epsg(),
+            Crs::Plane(datum, zone) => {
+                let first = match datum {
+                    DatumKind::Tokyo => 30161,
+                    DatumKind::Jgd2000 => 2443,
+                    DatumKind::Jgd2011 => 6669,
+                    DatumKind::Tokyo97 => return None,
+                };
+                Some(first + u32::from(zone.number()) - 1)
+            }
+        }
+    }
+
+    /// Returns the datum.
+    pub fn datum(self) -> DatumKind {
+        match self {
+            Crs::Geographic(datum) | Crs::Plane(datum, _) => datum,
+        }
+    }
+}
+
+/// Parses an EPSG code like `EPSG:6668`, `6668` or `urn:ogc:def:crs:EPSG::6668`.
+impl FromStr for Crs {
+    type Err = ParseCrsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseCrsError {
+            input: s.to_owned(),
+        };
+        let trimmed = s.trim();
+        let upper = trimmed.to_ascii_uppercase();
+        let code = upper
+            .strip_prefix("URN:OGC:DEF:CRS:EPSG:")
+            .map(|rest| rest.rsplit(':').next().unwrap_or(rest))
+            .or_else(|| upper.strip_prefix("EPSG:"))
+            .unwrap_or(&upper);
+        let code = code.parse().map_err(|_| err())?;
+        Crs::from_epsg(code).ok_or_else(err)
+    }
+}
+
+/// Formats as `EPSG:<code>`, or the name if not registered.
+impl fmt::Display for Crs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.epsg(), self) {
+            (Some(code), _) => write!(f, "EPSG:{}", code),
+            (None, Crs::Geographic(datum)) => f.write_str(datum.name()),
+            (None, Crs::Plane(datum, zone)) => {
+                write!(f, "{} / zone {}", datum.name(), zone.number())
+            }
+        }
+    }
+}
+
+/// Errors in parsing [`Crs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseCrsError {
+    input: String,
+}
+impl fmt::Display for ParseCrsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unsupported CRS: {:?}", self.input)
+    }
+}
+impl std::error::Error for ParseCrsError {}
+
+/// Transformation between two [`Crs`] built once and applied to many coordinates.
+///
+/// 投影の逆変換、測地系の変換、投影を組み合わせた経路を、構築時に一度だけ求めて保持する。
+/// 測地系の変換は [`Datum::transform_to`](crate::Datum::transform_to) と同じ経路をたどる。
+///
+/// 座標は EPSG の軸順で与える。緯度経度は `[lat, lon]`、平面直角座標は `[x, y]` (X は北向き)。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{Crs, Transformer};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let transformer = Transformer::new("EPSG:4612".parse()?, "EPSG:2451".parse()?)?;
+/// let [x, y] = transformer.transform([35.7, 139.7])?;
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Transformer {
+    from: Crs,
+    to: Crs,
+    steps: Vec<Step>,
+}
+impl Transformer {
+    /// Constructs a [`Transformer`] from `from` to `to`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransformerError`] if there is no path between the datums.
+    pub fn new(from: Crs, to: Crs) -> Result<Self, TransformerError> {
+        let mut steps = vec![];
+        if let Crs::Plane(datum, zone) = from {
+            steps.push(Step::Unproject(zone.projection(&datum.ellipsoid())));
+        }
+        let path = path(from.datum(), to.datum()).ok_or(TransformerError { from, to })?;
+        steps.extend(path.into_iter().map(Step::Datum));
+        if let Crs::Plane(datum, zone) = to {
+            steps.push(Step::Project(zone.projection(&datum.ellipsoid())));
+        }
+        Ok(Self { from, to, steps })
+    }
+
+    /// Returns the source [`Crs`].
+    pub fn from(&self) -> Crs {
+        self.from
+    }
+
+    /// Returns the target [`Crs`].
+    pub fn to(&self) -> Crs {
+        self.to
+    }
+
+    /// Returns the names of the methods applied in order.
+    ///
+    /// 経緯度どうしで測地系が同じ場合は空となる。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jgd::{Crs, DatumKind, Transformer};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let tokyo97 = Crs::Geographic(DatumKind::Tokyo97);
+    /// let transformer = Transformer::new(tokyo97, "EPSG:6668".parse()?)?;
+    /// assert_eq!(transformer.methods().collect::<Vec<_>>(), ["3-parameter", "PatchJGD"]);
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn methods(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.steps.iter().map(Step::name)
+    }
+
+    /// Transforms a coordinate in the axis order of EPSG.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DegreesError`] if the coordinate in degrees is out of range.
+    pub fn transform(&self, coord: [f64; 2]) -> Result<[f64; 2], DegreesError> {
+        self.apply(coord, |_| {})
+    }
+
+    /// Transforms a coordinate, returning the names of the methods actually applied.
+    ///
+    /// [`Transformer::methods`] と異なり、その座標に用いられた方法を返す。
+    /// [`TKY2JGD`](crate::TKY2JGD) の範囲外では3パラメータ変換となり、
+    /// [`TOUHOKUTAIHEIYOUOKI2011`](crate::TOUHOKUTAIHEIYOUOKI2011) のパラメータがない地点では PatchJGD を含まない。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jgd::{Crs, DatumKind, Transformer};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let tokyo97 = Crs::Geographic(DatumKind::Tokyo97);
+    /// let transformer = Transformer::new(tokyo97, "EPSG:6668".parse()?)?;
+    /// let (_, methods) = transformer.transform_with_methods([35.0, 135.0])?;
+    /// assert_eq!(methods, ["3-parameter"]);
+    /// #   Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DegreesError`] if the coordinate in degrees is out of range.
+    pub fn transform_with_methods(
+        &self,
+        coord: [f64; 2],
+    ) -> Result<([f64; 2], Vec<&'static str>), DegreesError> {
+        let mut methods = vec![];
+        let ret = self.apply(coord, |method| methods.push(method))?;
+        Ok((ret, methods))
+    }
+
+    fn apply(
+        &self,
+        coord: [f64; 2],
+        mut method: impl FnMut(&'static str),
+    ) -> Result<[f64; 2], DegreesError> {
+        let [a, b] = coord;
+        let mut point = match self.from {
+            Crs::Geographic(_) => {
+                let degrees = LatLon(a, b);
+                degrees.validate_degrees()?;
+                Point::Degrees(degrees)
+            }
+            Crs::Plane(..) => Point::Plane(Xy::new(a, b)),
+        };
+        for step in &self.steps {
+            let (next, name) = step.apply(point)?;
+            point = next;
+            name.into_iter().for_each(&mut method);
+        }
+        Ok(match point {
+            Point::Degrees(LatLon(lat, lon)) => [lat, lon],
+            Point::Plane(xy) => [xy.x(), xy.y()],
+        })
+    }
+}
+
+/// Errors in constructing [`Transformer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransformerError {
+    from: Crs,
+    to: Crs,
+}
+impl fmt::Display for TransformerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no transformation from {} to {}", self.from, self.to)
+    }
+}
+impl std::error::Error for TransformerError {}
+
+#[derive(Debug, Clone, Copy)]
+enum Point {
+    Degrees(LatLon),
+    Plane(Xy),
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Unproject(TransverseMercator),
+    Datum(Edge),
+    Project(TransverseMercator),
+}
+impl Step {
+    fn name(&self) -> &'static str {
+        match self {
+            Step::Unproject(_) => "inverse Gauss-Krüger",
+            Step::Datum(edge) => edge.name(),
+            Step::Project(_) => "Gauss-Krüger",
+        }
+    }
+
+    /// Applies the step, returning the name of the method if any.
+    fn apply(&self, point: Point) -> Result<(Point, Option<&'static str>), DegreesError> {
+        Ok(match (self, point) {
+            (Step::Unproject(tm), Point::Plane(xy)) => {
+                (Point::Degrees(tm.unproject(xy)), Some(self.name()))
+            }
+            (Step::Datum(edge), Point::Degrees(degrees)) => {
+                let (degrees, name) = edge.apply(degrees)?;
+                (Point::Degrees(degrees), name)
+            }
+            (Step::Project(tm), Point::Degrees(degrees)) => {
+                (Point::Plane(tm.project(degrees)), Some(self.name()))
+            }
+            _ => unreachable!("steps are built in order"),
+        })
+    }
+}
+
+/// Transformation between two datums.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edge {
+    #[cfg(feature = "tky2jgd")]
+    TokyoToJgd2000,
+    Tokyo97ToJgd2000,
+    Jgd2000ToTokyo97,
+    #[cfg(feature = "tky2jgd")]
+    Jgd2000ToTokyo,
+    #[cfg(feature = "patchjgd")]
+    Jgd2000ToJgd2011,
+    #[cfg(feature = "patchjgd")]
+    Jgd2011ToJgd2000,
+}
+impl Edge {
+    const ALL: &'static [Edge] = &[
+        #[cfg(feature = "tky2jgd")]
+        Edge::TokyoToJgd2000,
+        Edge::Tokyo97ToJgd2000,
+        Edge::Jgd2000ToTokyo97,
+        #[cfg(feature = "tky2jgd")]
+        Edge::Jgd2000ToTokyo,
+        #[cfg(feature = "patchjgd")]
+        Edge::Jgd2000ToJgd2011,
+        #[cfg(feature = "patchjgd")]
+        Edge::Jgd2011ToJgd2000,
+    ];
+
+    fn endpoints(self) -> (DatumKind, DatumKind) {
+        match self {
+            #[cfg(feature = "tky2jgd")]
+            Edge::TokyoToJgd2000 => (DatumKind::Tokyo, DatumKind::Jgd2000),
+            Edge::Tokyo97ToJgd2000 => (DatumKind::Tokyo97, DatumKind::Jgd2000),
+            Edge::Jgd2000ToTokyo97 => (DatumKind::Jgd2000, DatumKind::Tokyo97),
+            #[cfg(feature = "tky2jgd")]
+            Edge::Jgd2000ToTokyo => (DatumKind::Jgd2000, DatumKind::Tokyo),
+            #[cfg(feature = "patchjgd")]
+            Edge::Jgd2000ToJgd2011 => (DatumKind::Jgd2000, DatumKind::Jgd2011),
+            #[cfg(feature = "patchjgd")]
+            Edge::Jgd2011ToJgd2000 => (DatumKind::Jgd2011, DatumKind::Jgd2000),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "tky2jgd")]
+            Edge::TokyoToJgd2000 => "TKY2JGD",
+            Edge::Tokyo97ToJgd2000 => "3-parameter",
+            Edge::Jgd2000ToTokyo97 => "inverse 3-parameter",
+            #[cfg(feature = "tky2jgd")]
+            Edge::Jgd2000ToTokyo => "inverse TKY2JGD",
+            #[cfg(feature = "patchjgd")]
+            Edge::Jgd2000ToJgd2011 => "PatchJGD",
+            #[cfg(feature = "patchjgd")]
+            Edge::Jgd2011ToJgd2000 => "inverse PatchJGD",
+        }
+    }
+
+    /// Applies the transformation, returning the name of the method actually used.
+    ///
+    /// グリッドの範囲外では、TKY2JGD は3パラメータ変換となり、PatchJGD は何もしないため `None` となる。
+    fn apply(self, degrees: LatLon) -> Result<(LatLon, Option<&'static str>), DegreesError> {
+        Ok(match self {
+            #[cfg(feature = "tky2jgd")]
+            Edge::TokyoToJgd2000 => {
+                let ret = Tokyo::new(degrees)?.to_jgd2000().degrees();
+                let name = match TKY2JGD.bilinear(degrees) {
+                    Some(_) => self.name(),
+                    None => Edge::Tokyo97ToJgd2000.name(),
+                };
+                (ret, Some(name))
+            }
+            #[cfg(feature = "tky2jgd")]
+            Edge::Jgd2000ToTokyo => {
+                let ret = Jgd2000::new(degrees)?.to_tokyo().degrees();
+                // 格子点は日本測地系の座標
+                let name = match TKY2JGD.bilinear(ret) {
+                    Some(_) => self.name(),
+                    None => Edge::Jgd2000ToTokyo97.name(),
+                };
+                (ret, Some(name))
+            }
+            #[cfg(feature = "patchjgd")]
+            Edge::Jgd2000ToJgd2011 => {
+                let ret = Jgd2000::new(degrees)?.to_jgd2011().degrees();
+                let name = TOUHOKUTAIHEIYOUOKI2011
+                    .bilinear(degrees)
+                    .map(|_| self.name());
+                (ret, name)
+            }
+            #[cfg(feature = "patchjgd")]
+            Edge::Jgd2011ToJgd2000 => {
+                let ret = Jgd2011::new(degrees)?.to_jgd2000().degrees();
+                let name = TOUHOKUTAIHEIYOUOKI2011.bilinear(ret).map(|_| self.name());
+                (ret, name)
+            }
+            Edge::Tokyo97ToJgd2000 => (
+                Tokyo97::new(degrees)?.to_jgd2000().degrees(),
+                Some(self.name()),
+            ),
+            Edge::Jgd2000ToTokyo97 => (
+                Jgd2000::new(degrees)?.to_tokyo97().degrees(),
+                Some(self.name()),
+            ),
+        })
+    }
+}
+
+/// Finds the shortest path between datums by breadth-first search.
+fn path(from: DatumKind, to: DatumKind) -> Option<Vec<Edge>> {
+    let mut visited = vec![(from, None::<Edge>)];
+    let mut i = 0;
+    while let Some(&(current, _)) = visited.get(i) {
+        if current == to {
+            let mut ret = vec![];
+            let mut node = to;
+            while let Some(&(_, Some(edge))) = visited.iter().find(|(datum, _)| *datum == node) {
+                ret.push(edge);
+                node = edge.endpoints().0;
+            }
+            ret.reverse();
+            return Some(ret);
+        }
+        for &edge in Edge::ALL {
+            let (start, end) = edge.endpoints();
+            if start == current && visited.iter().all(|(datum, _)| *datum != end) {
+                visited.push((end, Some(edge)));
+            }
+        }
+        i += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{Crs, DatumKind, LatLon, Transformer, Zone, GRS80};
+
+    #[test]
+    fn epsg() {
+        let zone = |n| Zone::new(n).unwrap();
+        for (code, crs) in [
+            (4301, Crs::Geographic(DatumKind::Tokyo)),
+            (4612, Crs::Geographic(DatumKind::Jgd2000)),
+            (6668, Crs::Geographic(DatumKind::Jgd2011)),
+            (30161, Crs::Plane(DatumKind::Tokyo, zone(1))),
+            (2461, Crs::Plane(DatumKind::Jgd2000, zone(19))),
+            (6677, Crs::Plane(DatumKind::Jgd2011, zone(9))),
+        ] {
+            assert_eq!(Crs::from_epsg(code), Some(crs));
+            assert_eq!(crs.epsg(), Some(code));
+        }
+        assert_eq!(Crs::from_epsg(4326), None);
+        assert_eq!(Crs::Plane(DatumKind::Tokyo97, zone(9)).epsg(), None);
+    }
+
+    #[test]
+    fn parse() {
+        let expected = Ok(Crs::Geographic(DatumKind::Jgd2011));
+        for s in [
+            "EPSG:6668",
+            "epsg:6668",
+            " 6668 ",
+            "urn:ogc:def:crs:EPSG::6668",
+        ] {
+            assert_eq!(s.parse(), expected, "{}", s);
+        }
+        let err = "EPSG:4326".parse::<Crs>().unwrap_err();
+        assert_eq!(err.to_string(), "unsupported CRS: \"EPSG:4326\"");
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Crs::from_epsg(2451).unwrap().to_string(), "EPSG:2451");
+        let crs = Crs::Plane(DatumKind::Tokyo97, Zone::new(9).unwrap());
+        assert_eq!(crs.to_string(), "Tokyo 97 / zone 9");
+    }
+
+    #[cfg(not(feature = "tky2jgd"))]
+    #[test]
+    fn no_path() {
+        let from = Crs::Geographic(DatumKind::Jgd2000);
+        let to = Crs::Geographic(DatumKind::Tokyo);
+        let err = Transformer::new(from, to).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no transformation from EPSG:4612 to EPSG:4301"
+        );
+    }
+
+    #[cfg(feature = "patchjgd")]
+    #[test]
+    fn inverse() {
+        let from = Crs::Geographic(DatumKind::Jgd2011);
+        let to = Crs::Plane(DatumKind::Tokyo97, Zone::new(10).unwrap());
+        let sut = Transformer::new(from, to).unwrap();
+        assert!(sut
+            .methods()
+            .eq(["inverse PatchJGD", "inverse 3-parameter", "Gauss-Krüger"]));
+
+        // PatchJGD の逆変換は反復により往復で一致する
+        let jgd2000 = Crs::Geographic(DatumKind::Jgd2000);
+        let sut = Transformer::new(from, jgd2000).unwrap();
+        let back = Transformer::new(jgd2000, from).unwrap();
+        let [lat, lon] = back
+            .transform(sut.transform([38.25, 140.875]).unwrap())
+            .unwrap();
+        assert_abs_diff_eq!(lat, 38.25, epsilon = 1e-12);
+        assert_abs_diff_eq!(lon, 140.875, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn datum_and_projection() {
+        let from = Crs::Geographic(DatumKind::Tokyo97);
+        let to = Crs::Plane(DatumKind::Jgd2000, Zone::new(9).unwrap());
+        let sut = Transformer::new(from, to).unwrap();
+        let [x, y] = sut.transform([35.7, 139.7]).unwrap();
+
+        let degrees = crate::Tokyo97::new(LatLon(35.7, 139.7))
+            .unwrap()
+            .to_jgd2000()
+            .degrees();
+        let expected = Zone::new(9).unwrap().projection(&GRS80).project(degrees);
+        assert_eq!([x, y], [expected.x(), expected.y()]);
+        assert!(sut.methods().eq(["3-parameter", "Gauss-Krüger"]));
+
+        let back = Transformer::new(to, from).unwrap();
+        assert!(back
+            .methods()
+            .eq(["inverse Gauss-Krüger", "inverse 3-parameter"]));
+        let [lat, lon] = back.transform([x, y]).unwrap();
+        // 楕円体高を捨てる Tokyo97 の往復変換の誤差 (数mm) を含む
+        assert_abs_diff_eq!(lat, 35.7, epsilon = 1e-7);
+        assert_abs_diff_eq!(lon, 139.7, epsilon = 1e-7);
+    }
+
+    #[test]
+    fn identity() {
+        let crs = Crs::Geographic(DatumKind::Jgd2000);
+        let sut = Transformer::new(crs, crs).unwrap();
+        assert_eq!(sut.transform([35., 135.]), Ok([35., 135.]));
+        assert!(sut.transform([135., 35.]).is_err());
+        assert_eq!(sut.methods().count(), 0);
+    }
+
+    #[cfg(feature = "patchjgd")]
+    #[test]
+    fn composed() {
+        let from = Crs::Geographic(DatumKind::Tokyo97);
+        let to = Crs::Geographic(DatumKind::Jgd2011);
+        let sut = Transformer::new(from, to).unwrap();
+        let ret = sut.transform([38., 141.]).unwrap();
+        let LatLon(lat, lon) = crate::Tokyo97::new(LatLon(38., 141.))
+            .unwrap()
+            .to_jgd2000()
+            .to_jgd2011()
+            .degrees();
+        assert_eq!(ret, [lat, lon]);
+    }
+
+    #[cfg(feature = "patchjgd")]
+    #[test]
+    fn methods_per_point() {
+        let from = Crs::Geographic(DatumKind::Tokyo97);
+        let to = Crs::Plane(DatumKind::Jgd2011, Zone::new(10).unwrap());
+        let sut = Transformer::new(from, to).unwrap();
+        let (ret, methods) = sut.transform_with_methods([38.26, 140.87]).unwrap();
+        assert_eq!(ret, sut.transform([38.26, 140.87]).unwrap());
+        assert_eq!(methods, ["3-parameter", "PatchJGD", "Gauss-Krüger"]);
+        // PatchJGD のパラメータがない地点
+        let (_, methods) = sut.transform_with_methods([35., 135.]).unwrap();
+        assert_eq!(methods, ["3-parameter", "Gauss-Krüger"]);
+
+        let back = Transformer::new(to, from).unwrap();
+        let (_, methods) = back.transform_with_methods(ret).unwrap();
+        assert_eq!(
+            methods,
+            [
+                "inverse Gauss-Krüger",
+                "inverse PatchJGD",
+                "inverse 3-parameter"
+            ]
+        );
+    }
+
+    #[cfg(feature = "tky2jgd")]
+    #[test]
+    fn tky2jgd_fallback() {
+        let from = Crs::Geographic(DatumKind::Tokyo);
+        let to = Crs::Geographic(DatumKind::Jgd2000);
+        let sut = Transformer::new(from, to).unwrap();
+        let (_, methods) = sut.transform_with_methods([35.7, 139.7]).unwrap();
+        assert_eq!(methods, ["TKY2JGD"]);
+        // 海上は TKY2JGD の範囲外
+        let (ret, methods) = sut.transform_with_methods([30., 150.]).unwrap();
+        assert_eq!(methods, ["3-parameter"]);
+
+        let back = Transformer::new(to, from).unwrap();
+        let (_, methods) = back.transform_with_methods(ret).unwrap();
+        assert_eq!(methods, ["inverse 3-parameter"]);
+    }
+}
@@ -0,0 +1,1088 @@
+use std::fmt::{self, Write};
+
+use crate::{Crs, Datum, DegreesError, LatLon, TransformTo, Transformer, TransformerError};
+
+/// Vertex of a [`WktGeometry`] with an optional Z.
+///
+/// 軸順序は WKT の慣例に従い、経緯度では X が経度、Y が緯度、平面直角座標では X が東向き、Y が北向き。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    x: f64,
+    y: f64,
+    z: Option<f64>,
+}
+impl Position {
+    /// Constructs a 2D [`Position`].
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y, z: None }
+    }
+
+    /// Returns self with Z.
+    pub const fn with_z(mut self, z: f64) -> Self {
+        self.z = Some(z);
+        self
+    }
+
+    /// Returns X.
+    pub fn x(&self) -> f64 {
+        self.x
+    }
+
+    /// Returns Y.
+    pub fn y(&self) -> f64 {
+        self.y
+    }
+
+    /// Returns Z, if any.
+    pub fn z(&self) -> Option<f64> {
+        self.z
+    }
+}
+/// Converts to a 2D [`Position`] with longitude as X and latitude as Y.
+impl From<LatLon> for Position {
+    fn from(degrees: LatLon) -> Self {
+        let LatLon(lat, lon) = degrees;
+        Self::new(lon, lat)
+    }
+}
+/// Converts from a [`Position`] with longitude as X and latitude as Y, dropping Z.
+impl From<Position> for LatLon {
+    fn from(position: Position) -> Self {
+        LatLon(position.y, position.x)
+    }
+}
+
+/// Geometry read from and written to WKT, EWKT, WKB and EWKB.
+///
+/// PostGIS の出力に対応する。M 値を持つジオメトリは扱わない。
+/// 1つのジオメトリの中で Z の有無は揃っている必要がある。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{WktGeometry, Jgd2000, Tokyo97};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let tokyo97 = WktGeometry::from_wkt("LINESTRING (135 35, 135.1 35.1)")?;
+/// let jgd2000 = tokyo97.transform_datum::<Tokyo97, Jgd2000>()?;
+/// let wkb = jgd2000.to_ewkb(Some(4612));
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum WktGeometry {
+    /// `POINT`
+    Point(Position),
+    /// `LINESTRING`
+    LineString(Vec<Position>),
+    /// `POLYGON` with the exterior and interior rings.
+    Polygon(Vec<Vec<Position>>),
+    /// `MULTIPOINT`
+    MultiPoint(Vec<Position>),
+    /// `MULTILINESTRING`
+    MultiLineString(Vec<Vec<Position>>),
+    /// `MULTIPOLYGON`
+    MultiPolygon(Vec<Vec<Vec<Position>>>),
+    /// `GEOMETRYCOLLECTION`
+    GeometryCollection(Vec<WktGeometry>),
+    /// Empty geometry of the type like `POINT EMPTY`, with `true` for Z like `POINT Z EMPTY`.
+    Empty(WktGeometryType, bool),
+}
+impl WktGeometry {
+    /// Parses WKT, ignoring `SRID=...;` of EWKT if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WktError`] if the text is not a valid WKT.
+    pub fn from_wkt(s: &str) -> Result<Self, WktError> {
+        Self::from_ewkt(s).map(|(_, geometry)| geometry)
+    }
+
+    /// Parses EWKT like `SRID=4612;POINT(135 35)`, returning the SRID if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WktError`] if the text is not a valid EWKT.
+    pub fn from_ewkt(s: &str) -> Result<(Option<u32>, Self), WktError> {
+        let mut parser = WktParser {
+            input: s,
+            pos: 0,
+            depth: 0,
+        };
+        let srid = parser.srid()?;
+        let geometry = parser.geometry()?;
+        parser.skip_whitespace();
+        if parser.pos < s.len() {
+            return Err(parser.error("unexpected trailing characters"));
+        }
+        Ok((srid, geometry))
+    }
+
+    /// Returns WKT like `POINT Z (135 35 10)`.
+    pub fn to_wkt(&self) -> String {
+        let mut ret = String::new();
+        self.write_wkt(&mut ret);
+        ret
+    }
+
+    /// Returns EWKT with the SRID, if any, like `SRID=4612;POINT (135 35)`.
+    pub fn to_ewkt(&self, srid: Option<u32>) -> String {
+        let mut ret = match srid {
+            Some(srid) => format!("SRID={};", srid),
+            None => String::new(),
+        };
+        self.write_wkt(&mut ret);
+        ret
+    }
+
+    /// Parses WKB or EWKB in either byte order, ignoring the SRID if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WktError`] if the bytes are not a valid WKB.
+    pub fn from_wkb(bytes: &[u8]) -> Result<Self, WktError> {
+        Self::from_ewkb(bytes).map(|(_, geometry)| geometry)
+    }
+
+    /// Parses EWKB or WKB in either byte order, returning the SRID if any.
+    ///
+    /// Z は ISO 形式 (型番号 +1000) と EWKB 形式 (フラグ) のいずれにも対応する。
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WktError`] if the bytes are not a valid EWKB.
+    pub fn from_ewkb(bytes: &[u8]) -> Result<(Option<u32>, Self), WktError> {
+        let mut reader = WkbReader {
+            bytes,
+            pos: 0,
+            depth: 0,
+        };
+        let ret = reader.geometry()?;
+        if reader.pos < bytes.len() {
+            return Err(reader.error("unexpected trailing bytes"));
+        }
+        Ok(ret)
+    }
+
+    /// Returns ISO WKB in little endian.
+    pub fn to_wkb(&self) -> Vec<u8> {
+        let mut ret = vec![];
+        self.write_wkb(&mut ret, Wkb::Iso);
+        ret
+    }
+
+    /// Returns EWKB of PostGIS in little endian with the SRID, if any.
+    pub fn to_ewkb(&self, srid: Option<u32>) -> Vec<u8> {
+        let mut ret = vec![];
+        self.write_wkb(&mut ret, Wkb::Extended(srid));
+        ret
+    }
+
+    /// Transforms all positions in degrees from datum `S` to datum `T`, keeping Z.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DegreesError`] if a position is out of range in degrees.
+    pub fn transform_datum<S, T>(&self) -> Result<Self, DegreesError>
+    where
+        S: Datum + TransformTo<T>,
+        T: Datum,
+    {
+        self.map(&mut |position| {
+            let degrees = S::new(position.into())?.transform_to::<T>().degrees();
+            Ok(Position {
+                z: position.z,
+                ..degrees.into()
+            })
+        })
+    }
+
+    /// Transforms all positions by the [`Transformer`], keeping Z.
+    ///
+    /// 経緯度、平面直角座標のいずれも WKT の軸順序で扱う。
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DegreesError`] if a position is out of range in degrees.
+    pub fn transform(&self, transformer: &Transformer) -> Result<Self, DegreesError> {
+        self.map(&mut |position| {
+            // EPSG の軸順序は、経緯度では緯度、平面直角座標では北向きが先
+            let [a, b] = transformer.transform([position.y, position.x])?;
+            Ok(Position {
+                x: b,
+                y: a,
+                z: position.z,
+            })
+        })
+    }
+
+    fn map<E>(&self, f: &mut impl FnMut(Position) -> Result<Position, E>) -> Result<Self, E> {
+        fn positions<E>(
+            positions: &[Position],
+            f: &mut impl FnMut(Position) -> Result<Position, E>,
+        ) -> Result<Vec<Position>, E> {
+            positions.iter().map(|p| f(*p)).collect()
+        }
+        fn rings<E>(
+            rings: &[Vec<Position>],
+            f: &mut impl FnMut(Position) -> Result<Position, E>,
+        ) -> Result<Vec<Vec<Position>>, E> {
+            rings.iter().map(|ring| positions(ring, f)).collect()
+        }
+        Ok(match self {
+            WktGeometry::Point(p) => WktGeometry::Point(f(*p)?),
+            WktGeometry::LineString(g) => WktGeometry::LineString(positions(g, f)?),
+            WktGeometry::Polygon(g) => WktGeometry::Polygon(rings(g, f)?),
+            WktGeometry::MultiPoint(g) => WktGeometry::MultiPoint(positions(g, f)?),
+            WktGeometry::MultiLineString(g) => WktGeometry::MultiLineString(rings(g, f)?),
+            WktGeometry::MultiPolygon(g) => WktGeometry::MultiPolygon(
+                g.iter()
+                    .map(|polygon| rings(polygon, f))
+                    .collect::<Result<_, _>>()?,
+            ),
+            WktGeometry::GeometryCollection(g) => WktGeometry::GeometryCollection(
+                g.iter().map(|g| g.map(f)).collect::<Result<_, _>>()?,
+            ),
+            WktGeometry::Empty(..) => self.clone(),
+        })
+    }
+
+    fn has_z(&self) -> bool {
+        let any = |positions: &[Position]| positions.iter().any(|p| p.z.is_some());
+        match self {
+            WktGeometry::Point(p) => p.z.is_some(),
+            WktGeometry::LineString(g) | WktGeometry::MultiPoint(g) => any(g),
+            WktGeometry::Polygon(g) | WktGeometry::MultiLineString(g) => g.iter().any(|g| any(g)),
+            WktGeometry::MultiPolygon(g) => g.iter().flatten().any(|g| any(g)),
+            WktGeometry::GeometryCollection(g) => g.iter().any(WktGeometry::has_z),
+            WktGeometry::Empty(_, z) => *z,
+        }
+    }
+
+    fn geometry_type(&self) -> WktGeometryType {
+        match self {
+            WktGeometry::Point(_) => WktGeometryType::Point,
+            WktGeometry::LineString(_) => WktGeometryType::LineString,
+            WktGeometry::Polygon(_) => WktGeometryType::Polygon,
+            WktGeometry::MultiPoint(_) => WktGeometryType::MultiPoint,
+            WktGeometry::MultiLineString(_) => WktGeometryType::MultiLineString,
+            WktGeometry::MultiPolygon(_) => WktGeometryType::MultiPolygon,
+            WktGeometry::GeometryCollection(_) => WktGeometryType::GeometryCollection,
+            WktGeometry::Empty(geometry_type, _) => *geometry_type,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            WktGeometry::Point(_) => false,
+            WktGeometry::LineString(g) | WktGeometry::MultiPoint(g) => g.is_empty(),
+            WktGeometry::Polygon(g) | WktGeometry::MultiLineString(g) => g.is_empty(),
+            WktGeometry::MultiPolygon(g) => g.is_empty(),
+            WktGeometry::GeometryCollection(g) => g.is_empty(),
+            WktGeometry::Empty(..) => true,
+        }
+    }
+
+    fn write_wkt(&self, out: &mut String) {
+        out.push_str(self.geometry_type().name());
+        let z = self.has_z();
+        if z {
+            out.push_str(" Z");
+        }
+        if self.is_empty() {
+            out.push_str(" EMPTY");
+            return;
+        }
+        out.push(' ');
+        let position = |out: &mut String, p: &Position| {
+            // 高さのない頂点は 0 とする
+            let _ = match (z, p.z) {
+                (true, z) => write!(out, "{} {} {}", p.x, p.y, z.unwrap_or(0.)),
+                (false, _) => write!(out, "{} {}", p.x, p.y),
+            };
+        };
+        fn list<T>(out: &mut String, items: &[T], mut f: impl FnMut(&mut String, &T)) {
+            out.push('(');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                f(out, item);
+            }
+            out.push(')');
+        }
+        let ring = |out: &mut String, ring: &Vec<Position>| list(out, ring, position);
+        let polygon = |out: &mut String, polygon: &Vec<Vec<Position>>| list(out, polygon, ring);
+        match self {
+            WktGeometry::Point(p) => list(out, &[*p], position),
+            WktGeometry::LineString(g) => list(out, g, position),
+            WktGeometry::Polygon(g) => list(out, g, ring),
+            WktGeometry::MultiPoint(g) => list(out, g, |out, p| list(out, &[*p], position)),
+            WktGeometry::MultiLineString(g) => list(out, g, ring),
+            WktGeometry::MultiPolygon(g) => list(out, g, polygon),
+            WktGeometry::GeometryCollection(g) => list(out, g, |out, g| g.write_wkt(out)),
+            WktGeometry::Empty(..) => unreachable!(),
+        }
+    }
+
+    fn write_wkb(&self, out: &mut Vec<u8>, wkb: Wkb) {
+        let z = self.has_z();
+        let code = match wkb {
+            Wkb::Iso => self.geometry_type().code() + if z { 1000 } else { 0 },
+            Wkb::Extended(srid) => {
+                let mut code = self.geometry_type().code();
+                if z {
+                    code |= EWKB_Z;
+                }
+                if srid.is_some() {
+                    code |= EWKB_SRID;
+                }
+                code
+            }
+        };
+        out.push(1);
+        out.extend(code.to_le_bytes());
+        if let Wkb::Extended(Some(srid)) = wkb {
+            out.extend(srid.to_le_bytes());
+        }
+        // 入れ子のジオメトリには SRID を付けない
+        let member = match wkb {
+            Wkb::Iso => Wkb::Iso,
+            Wkb::Extended(_) => Wkb::Extended(None),
+        };
+
+        let position = |out: &mut Vec<u8>, p: &Position| {
+            out.extend(p.x.to_le_bytes());
+            out.extend(p.y.to_le_bytes());
+            if z {
+                out.extend(p.z.unwrap_or(0.).to_le_bytes());
+            }
+        };
+        let count = |out: &mut Vec<u8>, n: usize| out.extend((n as u32).to_le_bytes());
+        let positions = |out: &mut Vec<u8>, positions: &[Position]| {
+            count(out, positions.len());
+            positions.iter().for_each(|p| position(out, p));
+        };
+        let rings = |out: &mut Vec<u8>, rings: &[Vec<Position>]| {
+            count(out, rings.len());
+            rings.iter().for_each(|ring| positions(out, ring));
+        };
+        match self {
+            WktGeometry::Point(p) => position(out, p),
+            WktGeometry::LineString(g) => positions(out, g),
+            WktGeometry::Polygon(g) => rings(out, g),
+            WktGeometry::MultiPoint(g) => {
+                count(out, g.len());
+                for p in g {
+                    WktGeometry::Point(*p).write_wkb(out, member);
+                }
+            }
+            WktGeometry::MultiLineString(g) => {
+                count(out, g.len());
+                for line in g {
+                    WktGeometry::LineString(line.clone()).write_wkb(out, member);
+                }
+            }
+            WktGeometry::MultiPolygon(g) => {
+                count(out, g.len());
+                for polygon in g {
+                    WktGeometry::Polygon(polygon.clone()).write_wkb(out, member);
+                }
+            }
+            WktGeometry::GeometryCollection(g) => {
+                count(out, g.len());
+                g.iter().for_each(|g| g.write_wkb(out, member));
+            }
+            // 空の点は NaN で表す
+            WktGeometry::Empty(WktGeometryType::Point, _) => {
+                position(out, &Position::new(f64::NAN, f64::NAN).with_z(f64::NAN))
+            }
+            WktGeometry::Empty(..) => count(out, 0),
+        }
+    }
+}
+/// Formats as WKT.
+impl fmt::Display for WktGeometry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_wkt())
+    }
+}
+
+/// Type of a [`WktGeometry`], used for empty geometries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WktGeometryType {
+    /// `POINT`
+    Point,
+    /// `LINESTRING`
+    LineString,
+    /// `POLYGON`
+    Polygon,
+    /// `MULTIPOINT`
+    MultiPoint,
+    /// `MULTILINESTRING`
+    MultiLineString,
+    /// `MULTIPOLYGON`
+    MultiPolygon,
+    /// `GEOMETRYCOLLECTION`
+    GeometryCollection,
+}
+impl WktGeometryType {
+    const ALL: [Self; 7] = [
+        Self::Point,
+        Self::LineString,
+        Self::Polygon,
+        Self::MultiPoint,
+        Self::MultiLineString,
+        Self::MultiPolygon,
+        Self::GeometryCollection,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Point => "POINT",
+            Self::LineString => "LINESTRING",
+            Self::Polygon => "POLYGON",
+            Self::MultiPoint => "MULTIPOINT",
+            Self::MultiLineString => "MULTILINESTRING",
+            Self::MultiPolygon => "MULTIPOLYGON",
+            Self::GeometryCollection => "GEOMETRYCOLLECTION",
+        }
+    }
+
+    /// Returns the WKB type code without Z.
+    fn code(self) -> u32 {
+        self as u32 + 1
+    }
+}
+
+/// Transforms EWKT from the CRS of its SRID to `to`, rewriting the SRID.
+///
+/// `to` に EPSG コードがない場合 ([`DatumKind::Tokyo97`](crate::DatumKind::Tokyo97) など) は SRID を出力しない。
+///
+/// # Examples
+///
+/// ```
+/// use jgd::{Crs, DatumKind};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let jgd2000 = jgd::transform_ewkt("SRID=2451;POINT(-8000 -38000)", Crs::Geographic(DatumKind::Jgd2000))?;
+/// assert!(jgd2000.starts_with("SRID=4612;POINT ("));
+/// #   Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Returns [`WktError`] if the input is invalid, the SRID is missing or unsupported,
+/// there is no path between the CRSs, or a position is out of range in degrees.
+pub fn transform_ewkt(input: &str, to: Crs) -> Result<String, WktError> {
+    let (srid, geometry) = WktGeometry::from_ewkt(input)?;
+    let geometry = transform_srid(srid, &geometry, to)?;
+    Ok(geometry.to_ewkt(to.epsg()))
+}
+
+/// Transforms EWKB from the CRS of its SRID to `to`, rewriting the SRID.
+///
+/// `to` に EPSG コードがない場合は SRID を出力しない。
+///
+/// # Errors
+///
+/// Returns [`WktError`] if the input is invalid, the SRID is missing or unsupported,
+/// there is no path between the CRSs, or a position is out of range in degrees.
+pub fn transform_ewkb(input: &[u8], to: Crs) -> Result<Vec<u8>, WktError> {
+    let (srid, geometry) = WktGeometry::from_ewkb(input)?;
+    let geometry = transform_srid(srid, &geometry, to)?;
+    Ok(geometry.to_ewkb(to.epsg()))
+}
+
+fn transform_srid(
+    srid: Option<u32>,
+    geometry: &WktGeometry,
+    to: Crs,
+) -> Result<WktGeometry, WktError> {
+    let srid = srid.ok_or(WktError(ErrorKind::Srid(None)))?;
+    let from = Crs::from_epsg(srid).ok_or(WktError(ErrorKind::Srid(Some(srid))))?;
+    let transformer =
+        Transformer::new(from, to).map_err(|e| WktError(ErrorKind::Transformer(e)))?;
+    geometry
+        .transform(&transformer)
+        .map_err(|e| WktError(ErrorKind::Degrees(e)))
+}
+
+/// Errors in parsing or transforming WKT and WKB.
+#[derive(Debug, PartialEq)]
+pub struct WktError(ErrorKind);
+impl fmt::Display for WktError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            ErrorKind::Wkt { message, offset } => {
+                write!(f, "{} at character {}", message, offset)
+            }
+            ErrorKind::Wkb { message, offset } => write!(f, "{} at byte {}", message, offset),
+            ErrorKind::Srid(None) => f.write_str("missing SRID"),
+            ErrorKind::Srid(Some(srid)) => write!(f, "unsupported SRID: {}", srid),
+            ErrorKind::Transformer(e) => write!(f, "{}", e),
+            ErrorKind::Degrees(e) => write!(f, "{}", e),
+        }
+    }
+}
+impl std::error::Error for WktError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.0 {
+            ErrorKind::Transformer(e) => Some(e),
+            ErrorKind::Degrees(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum ErrorKind {
+    Wkt {
+        message: &'static str,
+        offset: usize,
+    },
+    Wkb {
+        message: &'static str,
+        offset: usize,
+    },
+    Srid(Option<u32>),
+    Transformer(TransformerError),
+    Degrees(DegreesError),
+}
+
+/// Maximum nesting of geometries, bounding the recursion on untrusted input.
+const MAX_DEPTH: usize = 32;
+
+const EWKB_Z: u32 = 0x8000_0000;
+const EWKB_M: u32 = 0x4000_0000;
+const EWKB_SRID: u32 = 0x2000_0000;
+
+#[derive(Debug, Clone, Copy)]
+enum Wkb {
+    Iso,
+    Extended(Option<u32>),
+}
+
+struct WktParser<'a> {
+    input: &'a str,
+    pos: usize,
+    depth: usize,
+}
+impl WktParser<'_> {
+    fn error(&self, message: &'static str) -> WktError {
+        let offset = self.input[..self.pos].chars().count();
+        WktError(ErrorKind::Wkt { message, offset })
+    }
+
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn srid(&mut self) -> Result<Option<u32>, WktError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        if !rest
+            .get(..5)
+            .is_some_and(|s| s.eq_ignore_ascii_case("SRID="))
+        {
+            return Ok(None);
+        }
+        self.pos += 5;
+        let end = self
+            .rest()
+            .find(';')
+            .ok_or(self.error("missing ';' after SRID"))?;
+        let srid = self.rest()[..end]
+            .trim()
+            .parse()
+            .map_err(|_| self.error("invalid SRID"))?;
+        self.pos += end + 1;
+        Ok(Some(srid))
+    }
+
+    /// Consumes the word if it matches ignoring case.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        if rest[..len].eq_ignore_ascii_case(keyword) {
+            self.pos += len;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char, message: &'static str) -> Result<(), WktError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    fn number(&mut self) -> Option<f64> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | 'e' | 'E')))
+            .unwrap_or(rest.len());
+        let number = rest[..len].parse().ok()?;
+        self.pos += len;
+        Some(number)
+    }
+
+    fn geometry(&mut self) -> Result<WktGeometry, WktError> {
+        let geometry_type = WktGeometryType::ALL
+            .into_iter()
+            .find(|geometry_type| self.keyword(geometry_type.name()))
+            .ok_or(self.error("unknown geometry type"))?;
+        let z = if self.keyword("Z") {
+            Some(true)
+        } else if self.keyword("M") || self.keyword("ZM") {
+            return Err(self.error("M coordinates are not supported"));
+        } else {
+            None
+        };
+        if self.keyword("EMPTY") {
+            return Ok(WktGeometry::Empty(geometry_type, z.is_some()));
+        }
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("too deeply nested"));
+        }
+        self.depth += 1;
+        let mut body = Body { parser: self, z };
+        let geometry = match geometry_type {
+            WktGeometryType::Point => {
+                body.parser.expect('(', "expected '('")?;
+                let p = body.position()?;
+                body.parser.expect(')', "expected ')'")?;
+                WktGeometry::Point(p)
+            }
+            WktGeometryType::LineString => WktGeometry::LineString(body.list(Body::position)?),
+            WktGeometryType::Polygon => {
+                WktGeometry::Polygon(body.list(|b| b.list(Body::position))?)
+            }
+            WktGeometryType::MultiPoint => WktGeometry::MultiPoint(body.list(|b| {
+                // 各点の括弧は省略できる
+                if b.parser.eat('(') {
+                    let p = b.position()?;
+                    b.parser.expect(')', "expected ')'")?;
+                    Ok(p)
+                } else {
+                    b.position()
+                }
+            })?),
+            WktGeometryType::MultiLineString => {
+                WktGeometry::MultiLineString(body.list(|b| b.list(Body::position))?)
+            }
+            WktGeometryType::MultiPolygon => {
+                WktGeometry::MultiPolygon(body.list(|b| b.list(|b| b.list(Body::position)))?)
+            }
+            WktGeometryType::GeometryCollection => {
+                WktGeometry::GeometryCollection(body.list(|b| b.parser.geometry())?)
+            }
+        };
+        self.depth -= 1;
+        Ok(geometry)
+    }
+}
+
+/// Coordinates of a geometry, keeping the dimension consistent.
+struct Body<'a, 'b> {
+    parser: &'a mut WktParser<'b>,
+    z: Option<bool>,
+}
+impl Body<'_, '_> {
+    fn list<T>(
+        &mut self,
+        mut f: impl FnMut(&mut Self) -> Result<T, WktError>,
+    ) -> Result<Vec<T>, WktError> {
+        self.parser.expect('(', "expected '('")?;
+        let mut ret = vec![f(self)?];
+        while self.parser.eat(',') {
+            ret.push(f(self)?);
+        }
+        self.parser.expect(')', "expected ',' or ')'")?;
+        Ok(ret)
+    }
+
+    fn position(&mut self) -> Result<Position, WktError> {
+        let x = self
+            .parser
+            .number()
+            .ok_or(self.parser.error("expected a number"))?;
+        let y = self
+            .parser
+            .number()
+            .ok_or(self.parser.error("expected a number"))?;
+        let z = self.parser.number();
+        if self.parser.number().is_some() {
+            return Err(self.parser.error("M coordinates are not supported"));
+        }
+        if *self.z.get_or_insert(z.is_some()) != z.is_some() {
+            return Err(self.parser.error("mixed dimensions"));
+        }
+        Ok(Position { x, y, z })
+    }
+}
+
+struct WkbReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+impl WkbReader<'_> {
+    fn error(&self, message: &'static str) -> WktError {
+        WktError(ErrorKind::Wkb {
+            message,
+            offset: self.pos,
+        })
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], WktError> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + N)
+            .ok_or(self.error("unexpected end"))?;
+        self.pos += N;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn u32(&mut self, le: bool) -> Result<u32, WktError> {
+        let bytes = self.take()?;
+        Ok(if le {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn f64(&mut self, le: bool) -> Result<f64, WktError> {
+        let bytes = self.take()?;
+        Ok(if le {
+            f64::from_le_bytes(bytes)
+        } else {
+            f64::from_be_bytes(bytes)
+        })
+    }
+
+    /// Reads a count, bounded by the remaining bytes to avoid huge allocations.
+    fn count(&mut self, le: bool, min_size: usize) -> Result<usize, WktError> {
+        let count = self.u32(le)? as usize;
+        if count > (self.bytes.len() - self.pos) / min_size {
+            return Err(self.error("count exceeds the input"));
+        }
+        Ok(count)
+    }
+
+    fn geometry(&mut self) -> Result<(Option<u32>, WktGeometry), WktError> {
+        let le = match self.take::<1>()? {
+            [0] => false,
+            [1] => true,
+            _ => return Err(self.error("invalid byte order")),
+        };
+        let code = self.u32(le)?;
+        if code & EWKB_M != 0 {
+            return Err(self.error("M coordinates are not supported"));
+        }
+        let srid = match code & EWKB_SRID {
+            0 => None,
+            _ => Some(self.u32(le)?),
+        };
+        let base = code & !(EWKB_Z | EWKB_M | EWKB_SRID);
+        let z = match base / 1000 {
+            0 => code & EWKB_Z != 0,
+            1 => true,
+            _ => return Err(self.error("M coordinates are not supported")),
+        };
+        let size = if z { 24 } else { 16 };
+
+        let position = |r: &mut Self| -> Result<Position, WktError> {
+            let (x, y) = (r.f64(le)?, r.f64(le)?);
+            let z = if z { Some(r.f64(le)?) } else { None };
+            Ok(Position { x, y, z })
+        };
+        let positions = |r: &mut Self| -> Result<Vec<Position>, WktError> {
+            let n = r.count(le, size)?;
+            (0..n).map(|_| position(r)).collect()
+        };
+        let geometry_type = match base % 1000 {
+            code @ 1..=7 => WktGeometryType::ALL[code as usize - 1],
+            _ => return Err(self.error("unknown geometry type")),
+        };
+        let empty = WktGeometry::Empty(geometry_type, z);
+        if geometry_type == WktGeometryType::Point {
+            let p = position(self)?;
+            // 空の点は NaN で表す
+            return Ok((
+                srid,
+                if p.x.is_nan() && p.y.is_nan() {
+                    empty
+                } else {
+                    WktGeometry::Point(p)
+                },
+            ));
+        }
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("too deeply nested"));
+        }
+        self.depth += 1;
+        let geometry = match geometry_type {
+            WktGeometryType::Point => unreachable!(),
+            WktGeometryType::LineString => match positions(self)? {
+                g if g.is_empty() => empty,
+                g => WktGeometry::LineString(g),
+            },
+            WktGeometryType::Polygon => match self.count(le, 4)? {
+                0 => empty,
+                n => {
+                    WktGeometry::Polygon((0..n).map(|_| positions(self)).collect::<Result<_, _>>()?)
+                }
+            },
+            _ => {
+                let n = self.count(le, 5)?;
+                let mut members = Vec::with_capacity(n);
+                for _ in 0..n {
+                    members.push(self.geometry()?.1);
+                }
+                let invalid = || self.error("invalid member of a multi geometry");
+                match geometry_type {
+                    _ if n == 0 => empty,
+                    WktGeometryType::MultiPoint => WktGeometry::MultiPoint(
+                        members
+                            .into_iter()
+                            .map(|g| match g {
+                                WktGeometry::Point(p) => Ok(p),
+                                _ => Err(invalid()),
+                            })
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    WktGeometryType::MultiLineString => WktGeometry::MultiLineString(
+                        members
+                            .into_iter()
+                            .map(|g| match g {
+                                WktGeometry::LineString(g) => Ok(g),
+                                WktGeometry::Empty(WktGeometryType::LineString, _) => Ok(vec![]),
+                                _ => Err(invalid()),
+                            })
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    WktGeometryType::MultiPolygon => WktGeometry::MultiPolygon(
+                        members
+                            .into_iter()
+                            .map(|g| match g {
+                                WktGeometry::Polygon(g) => Ok(g),
+                                WktGeometry::Empty(WktGeometryType::Polygon, _) => Ok(vec![]),
+                                _ => Err(invalid()),
+                            })
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    _ => WktGeometry::GeometryCollection(members),
+                }
+            }
+        };
+        self.depth -= 1;
+        Ok((srid, geometry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use std::error::Error;
+
+    use crate::{Crs, DatumKind, DegreesError, Jgd2000, LatLon, Tokyo97, Zone, GRS80};
+
+    use super::{transform_ewkb, transform_ewkt, Position, WktGeometry};
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn wkt_round_trip() {
+        for wkt in [
+            "POINT (135 35)",
+            "POINT Z (135 35 10.5)",
+            "POINT EMPTY",
+            "POINT Z EMPTY",
+            "LINESTRING Z EMPTY",
+            "LINESTRING (135 35, 135.1 35.1)",
+            "POLYGON ((0 0, 1 0, 1 1, 0 0), (0.2 0.2, 0.5 0.2, 0.2 0.5, 0.2 0.2))",
+            "MULTIPOINT ((1 2), (3 4))",
+            "MULTILINESTRING Z ((1 2 3, 4 5 6))",
+            "MULTIPOLYGON (((0 0, 1 0, 1 1, 0 0)), ((2 2, 3 2, 3 3, 2 2)))",
+            "GEOMETRYCOLLECTION Z (POINT Z (1 2 3), LINESTRING Z (1 2 3, 4 5 6))",
+            "GEOMETRYCOLLECTION (POINT (1 2), POLYGON EMPTY)",
+            "GEOMETRYCOLLECTION EMPTY",
+            "GEOMETRYCOLLECTION Z (POINT Z EMPTY)",
+        ] {
+            let geometry = WktGeometry::from_wkt(wkt).unwrap();
+            assert_eq!(geometry.to_wkt(), wkt);
+            assert_eq!(WktGeometry::from_wkb(&geometry.to_wkb()).unwrap(), geometry);
+            assert_eq!(
+                WktGeometry::from_ewkb(&geometry.to_ewkb(Some(4612))).unwrap(),
+                (Some(4612), geometry)
+            );
+        }
+    }
+
+    #[test]
+    fn wkt_variants() {
+        let point = WktGeometry::Point(Position::new(135., 35.).with_z(10.));
+        assert_eq!(WktGeometry::from_wkt("point(135 35 10)").unwrap(), point);
+        assert_eq!(
+            WktGeometry::from_ewkt(" SRID=4612; POINT Z (135 35 10) ").unwrap(),
+            (Some(4612), point)
+        );
+        assert_eq!(
+            WktGeometry::from_wkt("MULTIPOINT (1 2, 3 4)").unwrap(),
+            WktGeometry::MultiPoint(vec![Position::new(1., 2.), Position::new(3., 4.)])
+        );
+    }
+
+    #[test]
+    fn wkt_errors() {
+        for (wkt, message) in [
+            ("CIRCLE (1 2)", "unknown geometry type at character 0"),
+            ("POINT (1)", "expected a number at character 8"),
+            (
+                "POINT M (1 2 3)",
+                "M coordinates are not supported at character 7",
+            ),
+            (
+                "POINT (1 2 3 4)",
+                "M coordinates are not supported at character 14",
+            ),
+            (
+                "LINESTRING (1 2, 3 4 5)",
+                "mixed dimensions at character 22",
+            ),
+            ("POINT Z (1 2)", "mixed dimensions at character 12"),
+            (
+                "POINT (1 2) x",
+                "unexpected trailing characters at character 12",
+            ),
+            ("SRID=x;POINT (1 2)", "invalid SRID at character 5"),
+            (
+                &"GEOMETRYCOLLECTION (".repeat(40),
+                "too deeply nested at character 659",
+            ),
+        ] {
+            assert_eq!(
+                WktGeometry::from_wkt(wkt).unwrap_err().to_string(),
+                message,
+                "{}",
+                wkt
+            );
+        }
+    }
+
+    #[test]
+    fn wkb() {
+        let point = WktGeometry::Point(Position::new(1., 2.));
+        let wkb = hex("0101000000000000000000F03F0000000000000040");
+        assert_eq!(point.to_wkb(), wkb);
+        assert_eq!(WktGeometry::from_wkb(&wkb).unwrap(), point);
+        // PostGIS: SELECT ST_AsEWKB('SRID=4326;POINT(1 2)')
+        let ewkb = hex("0101000020E6100000000000000000F03F0000000000000040");
+        assert_eq!(point.to_ewkb(Some(4326)), ewkb);
+        assert_eq!(
+            WktGeometry::from_ewkb(&ewkb).unwrap(),
+            (Some(4326), point.clone())
+        );
+        // big endian
+        let xdr = hex("00000000013FF00000000000004000000000000000");
+        assert_eq!(WktGeometry::from_wkb(&xdr).unwrap(), point);
+
+        let point_z = WktGeometry::Point(Position::new(1., 2.).with_z(3.));
+        assert_eq!(point_z.to_wkb()[1..5], 1001u32.to_le_bytes());
+        assert_eq!(point_z.to_ewkb(None)[1..5], 0x8000_0001u32.to_le_bytes());
+
+        let err = WktGeometry::from_wkb(&wkb[..20]).unwrap_err();
+        assert_eq!(err.to_string(), "unexpected end at byte 13");
+        let huge = hex("010200000000FFFFFF");
+        assert!(WktGeometry::from_wkb(&huge).is_err());
+
+        let nested = hex(&"010700000001000000".repeat(40));
+        let err = WktGeometry::from_wkb(&nested).unwrap_err();
+        assert_eq!(err.to_string(), "too deeply nested at byte 293");
+    }
+
+    #[test]
+    fn transform_datum() {
+        let geometry = WktGeometry::from_wkt("LINESTRING Z (135 35 10, 135.1 35.1 20)").unwrap();
+        let WktGeometry::LineString(ret) = geometry.transform_datum::<Tokyo97, Jgd2000>().unwrap()
+        else {
+            panic!()
+        };
+        let expected = Tokyo97::new(LatLon(35.1, 135.1))
+            .unwrap()
+            .to_jgd2000()
+            .degrees();
+        assert_eq!(LatLon::from(ret[1]), expected);
+        assert_eq!(ret[1].z(), Some(20.));
+
+        let err = WktGeometry::from_wkt("POINT (35 135)")
+            .unwrap()
+            .transform_datum::<Tokyo97, Jgd2000>()
+            .unwrap_err();
+        assert!(err.to_string().ends_with("reversed?"));
+    }
+
+    #[test]
+    fn srid() {
+        let to = Crs::Geographic(DatumKind::Jgd2000);
+        let ret = transform_ewkt("SRID=2451;POINT(-8000 -38000)", to).unwrap();
+        let (srid, WktGeometry::Point(p)) = WktGeometry::from_ewkt(&ret).unwrap() else {
+            panic!()
+        };
+        assert_eq!(srid, Some(4612));
+        let zone = Zone::new(9).unwrap().projection(&GRS80);
+        let expected = zone.unproject(crate::Xy::new(-38000., -8000.));
+        assert_abs_diff_eq!(p.y(), *expected.lat(), epsilon = 1e-12);
+        assert_abs_diff_eq!(p.x(), *expected.lon(), epsilon = 1e-12);
+
+        let ewkb = WktGeometry::from_wkt("POINT (135 35)")
+            .unwrap()
+            .to_ewkb(Some(4612));
+        let ret = transform_ewkb(&ewkb, Crs::Geographic(DatumKind::Tokyo97)).unwrap();
+        assert_eq!(WktGeometry::from_ewkb(&ret).unwrap().0, None);
+
+        for (input, message) in [
+            ("POINT (135 35)", "missing SRID"),
+            ("SRID=4326;POINT (135 35)", "unsupported SRID: 4326"),
+            (
+                "SRID=4612;POINT (35 135)",
+                "degrees out of range; may be lat and lon reversed?",
+            ),
+        ] {
+            assert_eq!(transform_ewkt(input, to).unwrap_err().to_string(), message);
+        }
+        let err = transform_ewkt("SRID=4612;POINT (35 135)", to).unwrap_err();
+        assert!(err.source().is_some_and(|e| e.is::<DegreesError>()));
+    }
+}
@@ -1,18 +1,24 @@
+#![cfg(feature = "std")]
+
 use approx::assert_abs_diff_eq;
-use jgd::{Dms, LatLon};
+use jgd::{Dms, DmsError, Hemisphere, LatLon};
 
 mod testing;
 
 #[test]
 fn from_dms() {
-    let ret = LatLon(Dms(35, 39, 29.1572), Dms(139, 44, 28.8869)).to_degrees();
+    let ret = LatLon(
+        testing::dms(35, 39, 29.1572),
+        testing::dms(139, 44, 28.8869),
+    )
+    .to_degrees();
     let expected = LatLon(35.65809922, 139.74135747);
     testing::assert_distance(ret, expected);
 }
 
 #[test]
 fn to_dms() {
-    let LatLon(lat, lon) = LatLon(35.65809922, 139.74135747).to_dms();
+    let LatLon(lat, lon) = LatLon(35.65809922, 139.74135747).to_dms().unwrap();
 
     assert_eq!(lat.d(), 35);
     assert_eq!(lat.m(), 39);
@@ -22,3 +28,63 @@ fn to_dms() {
     assert_eq!(lon.m(), 44);
     assert_abs_diff_eq!(lon.s(), 28.8869, epsilon = 0.00001);
 }
+
+#[test]
+fn parse() {
+    let ret: LatLon<Dms> = "北緯35度39分29.1572秒、東経139度44分28.8869秒"
+        .parse()
+        .unwrap();
+    let expected = LatLon(35.65809922, 139.74135747);
+    testing::assert_distance(ret.to_degrees(), expected);
+}
+
+#[test]
+fn signed() {
+    // 昭和基地
+    let ret: LatLon<Dms> = "69°00'25\"S 39°35'24\"E".parse().unwrap();
+    assert_eq!(
+        *ret.lat(),
+        testing::dms(69, 0, 25.).with_hemisphere(Hemisphere::South)
+    );
+    assert_eq!(ret.lat().lat_hemisphere(), Hemisphere::South);
+    assert_eq!(ret.lon().lon_hemisphere(), Hemisphere::East);
+    testing::assert_distance(ret.to_degrees(), LatLon(-69.00694444, 39.59));
+
+    let half = -testing::dms(0, 30, 0.);
+    assert_eq!(half.to_degrees(), -0.5);
+    assert_eq!(Dms::from_degrees(-0.5).unwrap(), half);
+    assert_eq!(-testing::dms(0, 0, 0.), Dms::default());
+    assert!(half < Dms::default());
+}
+
+#[test]
+fn out_of_range() {
+    assert_eq!(Dms::new(35, 60, 0.), Err(DmsError::Minutes(60)));
+    assert_eq!(Dms::new(35, 0, 60.), Err(DmsError::Seconds(60.)));
+    assert_eq!(Dms::new(35, 0, -1.), Err(DmsError::Seconds(-1.)));
+    assert!(Dms::new(35, 0, f64::NAN).is_err());
+    assert_eq!(
+        Dms::new(35, 60, 0.).unwrap_err().to_string(),
+        "minutes out of range: 60"
+    );
+}
+
+#[test]
+fn from_degrees_carry() {
+    let dms = Dms::from_degrees(35. + 59. / 60. + 59.999_999_999_999 / 3600.).unwrap();
+    assert!(dms.m() < 60);
+    assert!(dms.s() < 60.);
+    assert_eq!(Dms::from_degrees(-35.5).unwrap(), -testing::dms(35, 30, 0.));
+}
+
+#[test]
+fn from_degrees_not_finite() {
+    for deg in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1e10] {
+        assert!(matches!(Dms::from_degrees(deg), Err(DmsError::Degrees(_))));
+    }
+    assert!(LatLon(f64::NAN, 135.).to_dms().is_err());
+    assert_eq!(
+        Dms::from_degrees(f64::INFINITY).unwrap_err().to_string(),
+        "degrees out of range: inf"
+    );
+}
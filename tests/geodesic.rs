@@ -0,0 +1,176 @@
+use approx::assert_abs_diff_eq;
+use geo::{GeodesicArea, GeodesicBearing, GeodesicDestination, Point};
+use jgd::{Enu, LatLon, LocalFrame, Xy, Zone, GRS80, WGS84};
+
+mod testing;
+
+// geo は WGS84 を用いる
+const POINTS: [LatLon; 8] = [
+    LatLon(35.6812, 139.7671),
+    LatLon(43.0687, 141.3508),
+    LatLon(26.2124, 127.6809),
+    LatLon(24.3448, 124.1572),
+    LatLon(-33.8688, 151.2093),
+    LatLon(51.4779, -0.0015),
+    LatLon(0.0, 0.0),
+    LatLon(-89.9, 45.0),
+];
+
+fn point(LatLon(lat, lon): LatLon) -> Point {
+    Point::new(lon, lat)
+}
+
+#[test]
+fn inverse() {
+    for from in POINTS {
+        for to in POINTS {
+            let ret = WGS84.geodesic_inverse(from, to);
+            let (bearing, distance) = point(from).geodesic_bearing_distance(point(to));
+            assert_abs_diff_eq!(ret.distance(), distance, epsilon = 1e-8);
+            if distance > 0. {
+                assert_abs_diff_eq!(
+                    ret.forward_azimuth(),
+                    bearing.rem_euclid(360.),
+                    epsilon = 1e-9
+                );
+                let back = point(to).geodesic_bearing(point(from));
+                assert_abs_diff_eq!(ret.back_azimuth(), back.rem_euclid(360.), epsilon = 1e-9);
+            }
+        }
+    }
+}
+
+#[test]
+fn direct() {
+    for from in POINTS {
+        for azimuth in [0., 30., 135., 270.] {
+            for distance in [1., 1_000., 1_000_000., 15_000_000.] {
+                let ret = WGS84.geodesic_direct(from, azimuth, distance);
+                let exp = point(from).geodesic_destination(azimuth, distance);
+                testing::assert_distance(ret.to(), LatLon(exp.y(), exp.x()));
+            }
+        }
+    }
+}
+
+#[test]
+fn round_trip() {
+    for from in POINTS {
+        for to in POINTS {
+            let ret = WGS84.geodesic_inverse(from, to);
+            let back = WGS84.geodesic_direct(from, ret.forward_azimuth(), ret.distance());
+            testing::assert_distance(back.to(), to);
+        }
+    }
+}
+
+#[test]
+fn area() {
+    let rings = [
+        // 時計回りの細長い土地
+        vec![
+            LatLon(35.6812, 139.7671),
+            LatLon(35.6815, 139.7672),
+            LatLon(35.6813, 139.7676),
+            LatLon(35.6811, 139.7674),
+        ],
+        POINTS[..5].to_vec(),
+        vec![LatLon(0., -1.), LatLon(0., 1.), LatLon(1., 0.)],
+    ];
+    // geo は各辺の面積を単純に合計するため、桁落ちによる誤差がある
+    for ring in rings {
+        let polygon = geo::Polygon::new(ring.iter().copied().map(point).collect(), vec![]);
+        let (perimeter, area) = polygon.geodesic_perimeter_area_signed();
+        assert_abs_diff_eq!(WGS84.geodesic_perimeter(&ring), perimeter, epsilon = 1e-6);
+        assert_abs_diff_eq!(
+            WGS84.geodesic_signed_area(&ring),
+            area,
+            epsilon = area.abs() * 1e-12 + 0.001
+        );
+    }
+}
+
+#[test]
+fn area_of_parcel() {
+    // 測量の精度では、小さな土地は局所的な平面上の面積と一致する
+    let origin = LatLon(35.6812, 139.7671);
+    let frame = LocalFrame::new(&WGS84, origin);
+    let corners = [(0., 0.), (30., 0.), (30., 20.), (10., 25.), (0., 20.)];
+    let ring = corners.map(|(e, n)| frame.to_geodetic(Enu::new(e, n, 0.)));
+    let shoelace = corners
+        .iter()
+        .zip(corners.iter().cycle().skip(1))
+        .map(|((e0, n0), (e1, n1))| e0 * n1 - e1 * n0)
+        .sum::<f64>()
+        / 2.;
+    assert_abs_diff_eq!(
+        WGS84.geodesic_signed_area(&ring),
+        shoelace,
+        epsilon = 0.0001
+    );
+}
+
+#[test]
+fn area_on_plane() {
+    // 平面直角座標系の面積を縮尺係数の2乗で割ると、楕円体上の面積に一致する
+    let zone = Zone::new(9).unwrap();
+    let projection = zone.projection(&GRS80);
+    let (x0, y0) = (-37900., 80_000.);
+    let corners = [(0., 0.), (0., 30.), (20., 30.), (25., 10.), (20., 0.)];
+    let ring = corners.map(|(x, y)| projection.unproject(Xy::new(x0 + x, y0 + y)));
+    let shoelace = corners
+        .iter()
+        .zip(corners.iter().cycle().skip(1))
+        .map(|((x0, y0), (x1, y1))| y0 * x1 - y1 * x0)
+        .sum::<f64>()
+        / 2.;
+
+    let m0 = projection.scale();
+    let lat = zone.origin().lat() + x0 / 111_000.;
+    let r = GRS80.gaussian_radius(lat);
+    let y = y0 + 15.;
+    let scale = m0 * (1. + y.powi(2) / (2. * r.powi(2) * m0.powi(2)));
+    assert_abs_diff_eq!(
+        GRS80.geodesic_signed_area(&ring),
+        shoelace / scale.powi(2),
+        epsilon = 0.0001
+    );
+}
+
+#[test]
+fn meridian_arc_length() {
+    for lat in [-60.0, 0.0, 20.0, 35.0, 45.5, 89.0] {
+        let ret = GRS80.geodesic_inverse(LatLon(0.0, 135.0), LatLon(lat, 135.0));
+        assert_abs_diff_eq!(
+            ret.distance(),
+            GRS80.meridian_arc_length(lat).abs(),
+            epsilon = 1e-6
+        );
+    }
+}
+
+#[test]
+fn flinders_peak_to_buninyong() {
+    // Geoscience Australia による GRS80 上の Vincenty の計算例
+    // 距離は 1mm、方位角は 0.01秒単位で公表されている
+    let from = LatLon(
+        -testing::dms(37, 57, 3.72030),
+        testing::dms(144, 25, 29.52440),
+    )
+    .to_degrees();
+    let to = LatLon(
+        -testing::dms(37, 39, 10.15610),
+        testing::dms(143, 55, 35.38390),
+    )
+    .to_degrees();
+
+    let ret = GRS80.geodesic_inverse(from, to);
+    assert_abs_diff_eq!(ret.distance(), 54972.271, epsilon = 0.0005);
+    let azimuth = testing::dms(306, 52, 5.37).to_degrees();
+    assert_abs_diff_eq!(ret.forward_azimuth(), azimuth, epsilon = 0.005 / 3600.);
+    let back = testing::dms(127, 10, 25.07).to_degrees();
+    assert_abs_diff_eq!(ret.back_azimuth(), back, epsilon = 0.005 / 3600.);
+
+    let ret = GRS80.geodesic_direct(from, azimuth, 54972.271);
+    testing::assert_distance(ret.to(), to);
+}
@@ -1,7 +1,7 @@
 //! 国土地理院によるオリジナルの PatchJGD と比較するテスト。
 #![cfg(feature = "patchjgd")]
 
-use jgd::{Jgd2000, LatLon};
+use jgd::{Jgd2000, Jgd2011, LatLon};
 
 mod testing;
 
@@ -13,6 +13,9 @@ fn sendai() {
         .degrees();
     let patchjgd = LatLon(38.259991997, 140.870036378);
     testing::assert_distance(ret, patchjgd);
+
+    let ret = Jgd2011::new(patchjgd).unwrap().to_jgd2000().degrees();
+    testing::assert_distance(ret, LatLon(38.26, 140.87));
 }
 
 #[test]
@@ -23,6 +26,9 @@ fn iwaki_1() {
         .degrees();
     let patchjgd = LatLon(37.090532997, 140.840375142);
     testing::assert_distance(ret, patchjgd);
+
+    let ret = Jgd2011::new(patchjgd).unwrap().to_jgd2000().degrees();
+    testing::assert_distance(ret, LatLon(37.090536, 140.840350));
 }
 
 /// パラメータグリッドがない地域
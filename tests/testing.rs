@@ -1,5 +1,5 @@
 use geo::{GeodesicDistance, Point};
-use jgd::LatLon;
+use jgd::{Dms, LatLon};
 
 const MILLI_METERS: f64 = 0.001;
 const ACCURACY: f64 = 1. * MILLI_METERS;
@@ -20,3 +20,8 @@ pub fn assert_distance(left: LatLon, right: LatLon) {
         left
     );
 }
+
+#[allow(dead_code)]
+pub fn dms(d: u32, m: u32, s: f64) -> Dms {
+    Dms::new(d, m, s).unwrap()
+}